@@ -10,7 +10,7 @@ use eschec::{
         SearchEngine,
         alpha_beta::AlphaBetaSearch,
         common::SearchConfig,
-        move_ordering::{MainSearchPolicy, QSearchPolicy, sort_moves},
+        move_ordering::{HistoryTable, MainSearchPolicy, QSearchPolicy, sort_moves},
     },
     tuning::params::TunableParams,
 };
@@ -197,8 +197,8 @@ fn bench_ordering(c: &mut Criterion) {
                     moves.as_mut_slice(),
                     &[None; 2],
                     None,
-                    &[[0; 64]; 64],
-                    0xAB_CDEF_ABCD,
+                    &HistoryTable::EMPTY,
+                    Some(0xAB_CDEF_ABCD),
                 );
                 black_box(moves)
             },
@@ -219,8 +219,8 @@ fn bench_ordering(c: &mut Criterion) {
                     moves.as_mut_slice(),
                     &[None; 2],
                     None,
-                    &[[0; 64]; 64],
-                    0xAB_CDEF_ABCD,
+                    &HistoryTable::EMPTY,
+                    Some(0xAB_CDEF_ABCD),
                 );
                 black_box(moves)
             },
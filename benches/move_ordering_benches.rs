@@ -3,7 +3,7 @@ use std::hint::black_box;
 use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
 use eschec::{
     prelude::*,
-    search::move_ordering::{MainSearchPolicy, MoveScoringPolicy},
+    search::move_ordering::{HistoryTable, MainSearchPolicy, MoveScoringPolicy},
 };
 
 const POSITIONS: &[(&str, &str)] = &[
@@ -48,7 +48,13 @@ fn bench_move_scoring(c: &mut Criterion) {
                         let scores: Vec<i32> = moves
                             .iter()
                             .map(|&mv| {
-                                MainSearchPolicy::score(board, mv, &[None; 2], None, &[[0; 64]; 64])
+                                MainSearchPolicy::score(
+                                    board,
+                                    mv,
+                                    &[None; 2],
+                                    None,
+                                    &HistoryTable::EMPTY,
+                                )
                             })
                             .collect();
                         black_box(scores)
@@ -87,8 +93,8 @@ fn bench_current_sort(c: &mut Criterion) {
                             moves.as_mut_slice(),
                             &[None; 2],
                             None,
-                            &[[0; 64]; 64],
-                            board.hash,
+                            &HistoryTable::EMPTY,
+                            Some(board.hash),
                         );
                         black_box(moves)
                     },
@@ -130,8 +136,8 @@ fn bench_realistic_search_scenario(c: &mut Criterion) {
                             moves.as_mut_slice(),
                             &[None; 2],
                             None,
-                            &[[0; 64]; 64],
-                            board.hash,
+                            &HistoryTable::EMPTY,
+                            Some(board.hash),
                         );
 
                         // Simulate searching first N moves
@@ -164,7 +170,7 @@ fn bench_realistic_search_scenario(c: &mut Criterion) {
                             moves.as_mut_slice(),
                             &[None; 2],
                             None,
-                            &[[0; 64]; 64],
+                            &HistoryTable::EMPTY,
                         );
 
                         // Pick only what we need
@@ -211,7 +217,7 @@ fn bench_move_picker_precomputed(c: &mut Criterion) {
                                 moves.as_mut_slice(),
                                 &[None; 2],
                                 None,
-                                &[[0; 64]; 64],
+                                &HistoryTable::EMPTY,
                             );
 
                             let mut picked = Vec::with_capacity(cutoff);
@@ -0,0 +1,205 @@
+//! Set-wise bitboard operations
+//!
+//! Square 0 is a1 and square 63 is h8, so north is `<< 8` and east is `<< 1`.
+//! East/west shifts mask off the file the bits would wrap onto.
+
+use crate::prelude::*;
+
+const NOT_A_FILE: u64 = !FILE_MASKS[0];
+const NOT_H_FILE: u64 = !FILE_MASKS[7];
+
+#[inline(always)]
+pub const fn north(bb: BitBoard) -> BitBoard {
+    BitBoard(bb.0 << 8)
+}
+
+#[inline(always)]
+pub const fn south(bb: BitBoard) -> BitBoard {
+    BitBoard(bb.0 >> 8)
+}
+
+#[inline(always)]
+pub const fn east(bb: BitBoard) -> BitBoard {
+    BitBoard((bb.0 & NOT_H_FILE) << 1)
+}
+
+#[inline(always)]
+pub const fn west(bb: BitBoard) -> BitBoard {
+    BitBoard((bb.0 & NOT_A_FILE) >> 1)
+}
+
+#[inline(always)]
+pub const fn north_east(bb: BitBoard) -> BitBoard {
+    BitBoard((bb.0 & NOT_H_FILE) << 9)
+}
+
+#[inline(always)]
+pub const fn north_west(bb: BitBoard) -> BitBoard {
+    BitBoard((bb.0 & NOT_A_FILE) << 7)
+}
+
+#[inline(always)]
+pub const fn south_east(bb: BitBoard) -> BitBoard {
+    BitBoard((bb.0 & NOT_H_FILE) >> 7)
+}
+
+#[inline(always)]
+pub const fn south_west(bb: BitBoard) -> BitBoard {
+    BitBoard((bb.0 & NOT_A_FILE) >> 9)
+}
+
+/// Shifts one rank towards the opponent of `side`
+#[inline(always)]
+pub const fn forward(bb: BitBoard, side: Side) -> BitBoard {
+    match side {
+        Side::White => north(bb),
+        Side::Black => south(bb),
+    }
+}
+
+/// Every square on or north of a set bit, on the same file
+pub const fn north_fill(bb: BitBoard) -> BitBoard {
+    let mut b = bb.0;
+    b |= b << 8;
+    b |= b << 16;
+    b |= b << 32;
+    BitBoard(b)
+}
+
+/// Every square on or south of a set bit, on the same file
+pub const fn south_fill(bb: BitBoard) -> BitBoard {
+    let mut b = bb.0;
+    b |= b >> 8;
+    b |= b >> 16;
+    b |= b >> 32;
+    BitBoard(b)
+}
+
+/// Every file that has at least one set bit
+pub const fn file_fill(bb: BitBoard) -> BitBoard {
+    BitBoard(north_fill(bb).0 | south_fill(bb).0)
+}
+
+/// The files next to every file that has a set bit
+pub const fn adjacent_files(bb: BitBoard) -> BitBoard {
+    let files = file_fill(bb);
+    BitBoard(east(files).0 | west(files).0)
+}
+
+/// Squares attacked by `pawns` of `side`
+pub const fn pawn_attacks_bb(pawns: BitBoard, side: Side) -> BitBoard {
+    match side {
+        Side::White => BitBoard(north_east(pawns).0 | north_west(pawns).0),
+        Side::Black => BitBoard(south_east(pawns).0 | south_west(pawns).0),
+    }
+}
+
+/// Squares in front of `pawns` on their own files, from `side`'s point of view
+pub const fn front_span(side: Side, pawns: BitBoard) -> BitBoard {
+    match side {
+        Side::White => north_fill(north(pawns)),
+        Side::Black => south_fill(south(pawns)),
+    }
+}
+
+/// Squares behind `pawns` on their own files, from `side`'s point of view
+pub const fn rear_span(side: Side, pawns: BitBoard) -> BitBoard {
+    front_span(side.flip(), pawns)
+}
+
+/// Squares `pawns` could ever attack while advancing, i.e. the front span of the adjacent files
+pub const fn attack_span(side: Side, pawns: BitBoard) -> BitBoard {
+    let front = front_span(side, pawns);
+    BitBoard(east(front).0 | west(front).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn sq(file: usize, rank: usize) -> u64 {
+        1 << (rank * 8 + file)
+    }
+
+    #[test]
+    fn test_shifts_mask_wraparound() {
+        let a1 = BitBoard(sq(0, 0));
+        let h1 = BitBoard(sq(7, 0));
+        let e4 = BitBoard(sq(4, 3));
+
+        assert_eq!(north(e4), BitBoard(sq(4, 4)));
+        assert_eq!(south(e4), BitBoard(sq(4, 2)));
+        assert_eq!(east(e4), BitBoard(sq(5, 3)));
+        assert_eq!(west(e4), BitBoard(sq(3, 3)));
+        assert_eq!(north_east(e4), BitBoard(sq(5, 4)));
+        assert_eq!(north_west(e4), BitBoard(sq(3, 4)));
+        assert_eq!(south_east(e4), BitBoard(sq(5, 2)));
+        assert_eq!(south_west(e4), BitBoard(sq(3, 2)));
+
+        assert!(east(h1).is_empty());
+        assert!(north_east(h1).is_empty());
+        assert!(west(a1).is_empty());
+        assert!(north_west(a1).is_empty());
+        assert!(south(a1).is_empty());
+        assert!(north(BitBoard(sq(0, 7))).is_empty());
+
+        assert_eq!(forward(e4, Side::White), north(e4));
+        assert_eq!(forward(e4, Side::Black), south(e4));
+    }
+
+    #[test]
+    fn test_fills() {
+        let e4 = BitBoard(sq(4, 3));
+
+        assert_eq!(north_fill(e4), BitBoard(0x1010_1010_1000_0000));
+        assert_eq!(south_fill(e4), BitBoard(0x0000_0000_1010_1010));
+        assert_eq!(file_fill(e4), BitBoard(FILE_MASKS[4]));
+        assert_eq!(
+            file_fill(BitBoard(sq(0, 6) | sq(7, 1))),
+            BitBoard(FILE_MASKS[0] | FILE_MASKS[7])
+        );
+        assert_eq!(adjacent_files(e4), BitBoard(FILE_MASKS[3] | FILE_MASKS[5]));
+        assert_eq!(adjacent_files(BitBoard(sq(0, 0))), BitBoard(FILE_MASKS[1]));
+    }
+
+    #[test]
+    fn test_pawn_attacks_bb() {
+        // a2, e4, h7
+        let pawns = BitBoard(sq(0, 1) | sq(4, 3) | sq(7, 6));
+
+        assert_eq!(
+            pawn_attacks_bb(pawns, Side::White),
+            BitBoard(sq(1, 2) | sq(3, 4) | sq(5, 4) | sq(6, 7))
+        );
+        assert_eq!(
+            pawn_attacks_bb(pawns, Side::Black),
+            BitBoard(sq(1, 0) | sq(3, 2) | sq(5, 2) | sq(6, 5))
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let e4 = BitBoard(sq(4, 3));
+
+        assert_eq!(front_span(Side::White, e4), BitBoard(0x1010_1010_0000_0000));
+        assert_eq!(front_span(Side::Black, e4), BitBoard(0x0000_0000_0010_1010));
+        assert_eq!(rear_span(Side::White, e4), front_span(Side::Black, e4));
+        assert_eq!(rear_span(Side::Black, e4), front_span(Side::White, e4));
+        assert_eq!(
+            attack_span(Side::White, e4),
+            BitBoard(0x2828_2828_0000_0000)
+        );
+
+        // Matches the precomputed passed pawn masks
+        for side in [Side::White, Side::Black] {
+            for sq_idx in 8..56 {
+                let pawn = BitBoard(1 << sq_idx);
+                assert_eq!(
+                    front_span(side, pawn) | attack_span(side, pawn),
+                    PAWN_TABLES.passed_pawn_blocking_masks[side.index()][sq_idx],
+                    "{side} {sq_idx}"
+                );
+            }
+        }
+    }
+}
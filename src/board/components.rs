@@ -6,7 +6,7 @@ use std::{
 
 use miette::Context;
 
-use crate::prelude::*;
+use crate::{prelude::*, tuning::params::TunableParams};
 
 #[derive(Debug, Default, Hash, PartialEq, Eq, PartialOrd, Clone, Copy)]
 #[repr(transparent)]
@@ -336,6 +336,15 @@ impl Side {
             Side::Black => 1,
         }
     }
+    /// `sq` as seen from `side`. Black's squares are flipped vertically (`sq ^ 56`),
+    /// so tables written from White's point of view can be shared by both sides
+    #[inline(always)]
+    pub const fn relative_square(&self, sq: usize) -> usize {
+        match self {
+            Side::White => sq,
+            Side::Black => sq ^ 56,
+        }
+    }
 }
 
 #[derive(Default, PartialEq, Eq, Debug, PartialOrd, Clone, Copy, Hash)]
@@ -491,6 +500,13 @@ impl Piece {
         }
     }
 
+    /// Tapered PST value of this piece belonging to `side` on `sq`, from `side`'s point of view.
+    /// Reads `params.psts`, so it agrees with the eval using the same params
+    #[inline(always)]
+    pub fn pst_value(&self, params: &TunableParams, side: Side, sq: usize, phase: Phase) -> i32 {
+        params.psts[self.index() * 64 + side.relative_square(sq)].taper(phase)
+    }
+
     #[inline(always)]
     pub const fn victim_score(&self) -> i32 {
         match self {
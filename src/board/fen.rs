@@ -25,6 +25,21 @@ pub static PIECE_CHAR_LOOKUP_TABLE: LazyLock<HashMap<char, (Piece, Side)>> = Laz
 });
 
 pub fn to_fen(board: &Board) -> miette::Result<String> {
+    let mut fen = to_fen_minimal(board);
+    let halfmove_clock: &str = &board.halfmove_clock.to_string();
+    let fullmove_clock: &str = &board.fullmove_counter.to_string();
+
+    fen.push(' ');
+    fen.push_str(halfmove_clock);
+    fen.push(' ');
+    fen.push_str(fullmove_clock);
+    Ok(fen)
+}
+
+/// The first four FEN fields: piece placement, side to move, castling rights and en passant.
+/// Leaves out the halfmove and fullmove clocks, so boards that are the same position for
+/// repetition or opening book purposes get the same string
+pub fn to_fen_minimal(board: &Board) -> String {
     let mut fen = String::new();
     let piece_placement: &str = &board.positions.to_fen_pieces();
     let stm: &str = match &board.stm {
@@ -36,8 +51,6 @@ pub fn to_fen(board: &Board) -> miette::Result<String> {
         Some(sq) => &sq.to_string().to_ascii_lowercase(),
         None => "-",
     };
-    let halfmove_clock: &str = &board.halfmove_clock.to_string();
-    let fullmove_clock: &str = &board.fullmove_counter.to_string();
 
     fen.push_str(piece_placement);
     fen.push(' ');
@@ -46,11 +59,7 @@ pub fn to_fen(board: &Board) -> miette::Result<String> {
     fen.push_str(castling_rights);
     fen.push(' ');
     fen.push_str(enpassent_square);
-    fen.push(' ');
-    fen.push_str(halfmove_clock);
-    fen.push(' ');
-    fen.push_str(fullmove_clock);
-    Ok(fen)
+    fen
 }
 
 /// Parse the FEN string to extract the piece placement part. This part of the FEN string represents the positions of the pieces on the board.
@@ -101,7 +110,7 @@ pub fn parse_fen(fen: &str) -> miette::Result<Board> {
     Ok(board)
 }
 
-fn is_fen_ep_square_legal(positions: &BoardState, stm: Side, ep_square: Square) -> bool {
+pub(crate) fn is_fen_ep_square_legal(positions: &BoardState, stm: Side, ep_square: Square) -> bool {
     match stm {
         Side::White => {
             if ep_square.row() != 5 {
@@ -248,6 +257,22 @@ mod tests {
         assert_eq!(board.fullmove_counter, 1);
     }
 
+    #[test]
+    fn test_to_fen_minimal_ignores_clocks() {
+        let fen = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+        let board = Board::from_fen(fen);
+        let later =
+            Board::from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 12 40");
+
+        assert_eq!(
+            to_fen_minimal(&board),
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6"
+        );
+        assert_eq!(to_fen_minimal(&board), to_fen_minimal(&later));
+        assert_ne!(to_fen(&board).unwrap(), to_fen(&later).unwrap());
+        assert_eq!(to_fen(&board).unwrap(), fen);
+    }
+
     #[test]
     fn test_parse_enpassant() {
         // Valid en passant
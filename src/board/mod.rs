@@ -1,6 +1,6 @@
 use crate::{
-    board::zobrist::calculate_hash,
-    evaluation::score::Phase,
+    board::zobrist::{calculate_hash, calculate_pawn_hash},
+    evaluation::{pst::IncrementalScore, score::Phase},
     moves::{attack_data::calculate_attack_data, move_gen},
     prelude::*,
     tuning::params::TunableParams,
@@ -8,6 +8,7 @@ use crate::{
 use miette::Context;
 use std::fmt::Display;
 
+pub mod bitboard_ops;
 pub mod components;
 pub mod fen;
 #[cfg(test)]
@@ -30,11 +31,15 @@ pub struct Board {
     pub halfmove_clock: u8,
     ///  The number of the full moves in a game. It starts at 1, and is incremented after each Black's move.
     pub fullmove_counter: u8,
-    /// Material left for each side [White, Black]
+    /// Material left for each side [White, Black], kings included. Only computed when the
+    /// board is set up and never updated by moves, use `evaluation::pst::IncrementalScore`
+    /// or `material_balance` instead
     #[deprecated]
     pub material: [Score; 2],
     /// Zobrist hash
     pub hash: u64,
+    /// Zobrist hash of the pawns only, keys the pawn hash table
+    pub pawn_hash: u64,
 }
 
 impl Display for Board {
@@ -125,24 +130,185 @@ impl Board {
                 panic!("very bad fen")
             }
         };
-        board.recalculate_material();
-        board.hash = calculate_hash(&board);
+        board.compute_derived_state();
         board
     }
 
-    pub fn try_from_fen(fen: &str) -> miette::Result<Self> {
+    pub fn try_from_fen(fen: &str) -> Result<Self, EschecError> {
         let parsed = fen::parse_fen(fen);
         let mut board = match parsed {
             Ok(b) => b,
             Err(e) => {
-                miette::bail!("Got error while parsing given fen'{fen}': {e}");
+                // Keep the whole chain, the outer context alone doesn't say what's wrong
+                let reason = e.chain().map(|e| e.to_string()).collect::<Vec<_>>();
+                return Err(EschecError::InvalidFen {
+                    fen: fen.to_string(),
+                    reason: reason.join(": "),
+                });
             }
         };
-        board.recalculate_material();
-        board.hash = calculate_hash(&board);
+        board.compute_derived_state();
         Ok(board)
     }
 
+    /// Checks that the position can be played from: one king per side, at most 8 pawns per
+    /// side and none on the back ranks, and the side that just moved not left in check
+    pub fn validate(&self) -> Result<(), EschecError> {
+        let invalid = |reason: String| EschecError::InvalidFen {
+            fen: self.to_fen().unwrap_or_default(),
+            reason,
+        };
+
+        for side in [Side::White, Side::Black] {
+            let kings = self.positions.get_piece_bb(side, Piece::King).pop_count();
+            if kings != 1 {
+                return Err(invalid(format!("{side} has {kings} kings")));
+            }
+            let pawns = self.positions.get_piece_bb(side, Piece::Pawn);
+            if pawns.pop_count() > 8 {
+                return Err(invalid(format!("{side} has more than 8 pawns")));
+            }
+            if (*pawns & BitBoard(RANK_MASKS[0] | RANK_MASKS[7])).any() {
+                return Err(invalid(format!("{side} has a pawn on the back rank")));
+            }
+        }
+
+        if self.is_in_check(self.stm.flip()) {
+            return Err(invalid(format!(
+                "{} is in check but it is {} to move",
+                self.stm.flip(),
+                self.stm
+            )));
+        }
+        Ok(())
+    }
+
+    /// Lists what differs between `self` and `other`, one difference per line as
+    /// `what: ours -> theirs`. Empty if the boards are equal. Meant for test failure messages
+    pub fn diff(&self, other: &Board) -> String {
+        fn piece_name(piece: Option<(Piece, Side)>) -> String {
+            piece.map_or("empty".to_owned(), |(piece, side)| {
+                format!("{side} {piece}")
+            })
+        }
+        fn ep_name(sq: Option<Square>) -> String {
+            sq.map_or("-".to_owned(), |sq| sq.to_string())
+        }
+
+        let mut lines = Vec::new();
+        for sq in (0..64).filter_map(Square::new) {
+            let ours = self.positions.get_piece_at(&sq);
+            let theirs = other.positions.get_piece_at(&sq);
+            if ours != theirs {
+                lines.push(format!(
+                    "{sq}: {} -> {}",
+                    piece_name(ours),
+                    piece_name(theirs)
+                ));
+            }
+        }
+        // Bitboards out of sync with the mailbox don't show up square by square
+        if lines.is_empty() && self.positions != other.positions {
+            lines.push("bitboards: differ with the same pieces on every square".to_owned());
+        }
+
+        let mut field = |name: &str, ours: String, theirs: String| {
+            if ours != theirs {
+                lines.push(format!("{name}: {ours} -> {theirs}"));
+            }
+        };
+        field("side to move", self.stm.to_string(), other.stm.to_string());
+        field(
+            "castling",
+            self.castling_rights.to_string(),
+            other.castling_rights.to_string(),
+        );
+        field(
+            "en passant",
+            ep_name(self.enpassant_square),
+            ep_name(other.enpassant_square),
+        );
+        field(
+            "halfmove clock",
+            self.halfmove_clock.to_string(),
+            other.halfmove_clock.to_string(),
+        );
+        field(
+            "fullmove counter",
+            self.fullmove_counter.to_string(),
+            other.fullmove_counter.to_string(),
+        );
+        field(
+            "hash",
+            format!("{:#018x}", self.hash),
+            format!("{:#018x}", other.hash),
+        );
+        field(
+            "pawn hash",
+            format!("{:#018x}", self.pawn_hash),
+            format!("{:#018x}", other.pawn_hash),
+        );
+        lines.join("\n")
+    }
+
+    /// Builds a board from a square-indexed array of pieces (a1 = 0, h8 = 63),
+    /// for setting up positions without going through a FEN string
+    pub fn from_squares(
+        pieces: [Option<PieceInfo>; 64],
+        stm: Side,
+        castling_rights: CastlingRights,
+        enpassant_square: Option<Square>,
+        halfmove_clock: u8,
+        fullmove_counter: u8,
+    ) -> miette::Result<Self> {
+        let mut board = Board {
+            stm,
+            castling_rights,
+            halfmove_clock,
+            fullmove_counter,
+            ..Default::default()
+        };
+        for (sq, info) in pieces.iter().enumerate() {
+            if let Some(PieceInfo { piece, side }) = *info {
+                board.positions.set_piece(side, piece, sq)?;
+            }
+        }
+
+        for side in [Side::White, Side::Black] {
+            let kings = board.positions.get_piece_bb(side, Piece::King).pop_count();
+            miette::ensure!(
+                kings == 1,
+                "{side} must have exactly one king, found {kings}"
+            );
+        }
+        if let Some(ep_sq) = enpassant_square {
+            miette::ensure!(
+                fen::is_fen_ep_square_legal(&board.positions, stm, ep_sq),
+                "En passant square {ep_sq} doesn't follow a double pawn push"
+            );
+        }
+        board.enpassant_square = enpassant_square;
+
+        board.compute_derived_state();
+        Ok(board)
+    }
+
+    /// Material and hashes, computed from scratch from the piece placement
+    fn compute_derived_state(&mut self) {
+        self.recalculate_material();
+        self.hash = calculate_hash(self);
+        self.pawn_hash = calculate_pawn_hash(self);
+    }
+
+    /// Key for repetition detection. Two positions repeat (same pieces on the same squares,
+    /// same side to move, castling rights and en passant captures) iff their keys match,
+    /// up to Zobrist collisions. This is just `hash`, since the en passant file is only
+    /// hashed when a capture is actually possible
+    #[inline(always)]
+    pub const fn repetition_key(&self) -> u64 {
+        self.hash
+    }
+
     pub fn to_fen(&self) -> miette::Result<String> {
         fen::to_fen(self)
     }
@@ -156,6 +322,7 @@ impl Board {
     }
 
     /// Only to be used internally;
+    #[cfg(test)]
     fn get_legal_moves(&self, captures_only: bool) -> MoveBuffer {
         let mut buffer = MoveBuffer::new();
         self.generate_legal_moves(&mut buffer, captures_only);
@@ -173,15 +340,69 @@ impl Board {
         );
     }
 
+    /// Slow reference move generator used to cross-check `generate_legal_moves`.
+    /// Generates pseudo-legal moves and keeps those that don't leave our king in check.
+    /// Castling additionally requires that the king isn't in check and doesn't pass
+    /// through an attacked square, which the pseudo-legal generator doesn't look at.
+    #[cfg(any(test, debug_assertions))]
+    pub fn generate_legal_moves_reference(&self, buffer: &mut MoveBuffer) {
+        let mut pseudo = MoveBuffer::new();
+        self.generate_pseudo_legal_moves(&mut pseudo, None);
+
+        let leaves_king_safe = |m: Move| {
+            let mut copy = *self;
+            copy.make_move(m).is_ok() && !copy.is_in_check(self.stm)
+        };
+
+        for m in pseudo {
+            if m.is_castling() {
+                let from = m.from_idx();
+                let transit = (from + m.to_idx()) / 2;
+                if self.is_in_check(self.stm)
+                    || !leaves_king_safe(Move::new(from, transit, Move::QUIET))
+                {
+                    continue;
+                }
+            }
+            if leaves_king_safe(m) {
+                buffer.push(m);
+            }
+        }
+    }
+
+    /// Panics if `generate_legal_moves` and `generate_legal_moves_reference` disagree
+    #[cfg(any(test, debug_assertions))]
+    pub fn assert_legal_moves_match_reference(&self) {
+        let sorted_uci = |buffer: MoveBuffer| {
+            let mut moves: Vec<String> = buffer.into_iter().map(|m| m.uci()).collect();
+            moves.sort_unstable();
+            moves
+        };
+
+        let mut fast = MoveBuffer::new();
+        self.generate_legal_moves(&mut fast, false);
+        let mut reference = MoveBuffer::new();
+        self.generate_legal_moves_reference(&mut reference);
+
+        let fen = self.to_fen().unwrap_or_default();
+        assert_eq!(
+            sorted_uci(fast),
+            sorted_uci(reference),
+            "\nLegal move generation disagrees with the reference for FEN: '{fen}'"
+        );
+    }
+
     /// Primary "safe" method for applying a move.
-    /// Checks for legality before making the move.
-    pub fn try_move(&mut self, m: Move) -> miette::Result<()> {
+    /// Checks for legality before making the move, returns the info needed to unmake it.
+    pub fn try_move(&mut self, m: Move) -> Result<MoveInfo, EschecError> {
         let mut legal_moves = MoveBuffer::new();
         self.generate_legal_moves(&mut legal_moves, false);
 
         if legal_moves.contains(&m) {
-            let _ = self.make_move(m)?;
-            Ok(())
+            self.make_move(m).map_err(|e| EschecError::IllegalMove {
+                mv: m.uci(),
+                reason: e.to_string(),
+            })
         } else {
             let mut possible_moves = String::new();
             for mv in legal_moves {
@@ -189,7 +410,10 @@ impl Board {
                 possible_moves.push(' ');
             }
             println!("Possibe moves: {possible_moves}");
-            miette::bail!("Illegal move for {} from {}", &self.stm, m.uci());
+            Err(EschecError::IllegalMove {
+                mv: m.uci(),
+                reason: format!("Not legal for {}", self.stm),
+            })
         }
     }
 
@@ -200,6 +424,7 @@ impl Board {
         self.enpassant_square = move_data.enpassant_square;
         self.halfmove_clock = move_data.halfmove_clock;
         self.hash = move_data.zobrist_hash;
+        self.pawn_hash = move_data.pawn_hash;
 
         // if black move was just unmade, decrement full move counter
         if self.stm == Side::Black {
@@ -276,6 +501,32 @@ impl Board {
             .with_context(|| format!("A piece should exist at {from} sq"))?;
         let opponent = self.stm.flip();
 
+        // Catch malformed moves (e.g. from external sources) before touching the board
+        match self.positions.get_piece_at(&to) {
+            Some((target, side)) => {
+                miette::ensure!(
+                    side == opponent,
+                    "Move {} would capture its own {target} on {to}",
+                    m.uci()
+                );
+                miette::ensure!(
+                    m.is_capture() && !m.is_enpassant(),
+                    "Move {} isn't flagged as a capture, but {to} holds a {target}",
+                    m.uci()
+                );
+            }
+            None => miette::ensure!(
+                !m.is_capture() || m.is_enpassant(),
+                "Move {} is flagged as a capture, but {to} is empty",
+                m.uci()
+            ),
+        }
+        miette::ensure!(
+            !m.is_enpassant() || self.enpassant_square == Some(to),
+            "Move {} is flagged as en passant, but {to} isn't the en passant square",
+            m.uci()
+        );
+
         // Store current state for unmake
         let move_data = MoveInfo {
             from,
@@ -286,8 +537,10 @@ impl Board {
             enpassant_square: self.enpassant_square,
             halfmove_clock: self.halfmove_clock,
             zobrist_hash: self.hash,
+            pawn_hash: self.pawn_hash,
             is_castling: m.is_castling(),
             is_en_passant: m.is_enpassant(),
+            check_given: false,
             promotion: m.promoted_piece(),
             // material: self.material,
             captured_piece: if m.is_enpassant() {
@@ -314,15 +567,26 @@ impl Board {
                 .remove_piece(opponent, captured_piece, to.index())?;
             // XOR out key for removed piece
             self.hash ^= &ZOBRIST.pieces[opponent.index()][captured_piece.index()][to.index()];
+            if captured_piece == Piece::Pawn {
+                self.pawn_hash ^= &ZOBRIST.pieces[opponent.index()][Piece::pawn()][to.index()];
+            }
             // self.material[opponent.index()] -= captured_piece.score();
         }
 
+        debug_assert!(
+            self.positions.get_piece_at(&to).is_none(),
+            "Destination {to} must be empty once captures are removed"
+        );
         // Move the piece from 'from' to 'to'
         self.positions.move_piece(from, to)?;
         // XOR out key for moved piece at source sq 'from'
         self.hash ^= &ZOBRIST.pieces[self.stm.index()][piece.index()][from.index()];
         // XOR in key for moved piece at destination sq 'to'
         self.hash ^= &ZOBRIST.pieces[self.stm.index()][piece.index()][to.index()];
+        if piece == Piece::Pawn {
+            self.pawn_hash ^= &ZOBRIST.pieces[self.stm.index()][Piece::pawn()][from.index()];
+            self.pawn_hash ^= &ZOBRIST.pieces[self.stm.index()][Piece::pawn()][to.index()];
+        }
 
         match m.flags() {
             Move::DOUBLE_PAWN => {
@@ -349,6 +613,8 @@ impl Board {
                     .remove_piece(opponent, Piece::Pawn, captured_pawn_idx)?;
                 // XOR out captured opponent pawn
                 self.hash ^= &ZOBRIST.pieces[opponent.index()][Piece::pawn()][captured_pawn_idx];
+                self.pawn_hash ^=
+                    &ZOBRIST.pieces[opponent.index()][Piece::pawn()][captured_pawn_idx];
                 // self.material[opponent.index()] -= Piece::Pawn.score();
             }
             Move::KING_CASTLE => {
@@ -382,6 +648,7 @@ impl Board {
                 // self.material[self.stm.index()] -= Piece::Pawn.score();
                 // XOR out pawn
                 self.hash ^= &ZOBRIST.pieces[self.stm.index()][Piece::pawn()][to.index()];
+                self.pawn_hash ^= &ZOBRIST.pieces[self.stm.index()][Piece::pawn()][to.index()];
 
                 self.positions
                     .set_piece(self.stm, promo_piece, to.index())?;
@@ -417,9 +684,39 @@ impl Board {
     }
 
     pub fn static_exchange_evaluation(&self, mv: Move) -> i32 {
+        self.see_for_side(mv, self.stm)
+    }
+
+    /// True if the exchange started by `mv` wins at least `threshold`
+    pub fn see_ge(&self, mv: Move, threshold: i32) -> bool {
+        self.static_exchange_evaluation(mv) >= threshold
+    }
+
+    /// Static exchange value of the cheapest capture of the piece on `sq` by `side`,
+    /// whichever side is to move. 0 if `sq` holds no enemy piece or `side` doesn't attack it
+    pub fn see_square(&self, sq: Square, side: Side) -> i32 {
+        match self.positions.get_piece_at(&sq) {
+            Some((_, owner)) if owner != side => {}
+            _ => return 0,
+        }
+
+        let attackers =
+            move_gen::get_attackers_to(self, sq, side, self.positions.get_occupied_bb());
+        for piece in Piece::all_pieces() {
+            let candidates = attackers & *self.positions.get_piece_bb(side, piece);
+            if let Some(from) = candidates.lsb() {
+                let mv = Move::new(from as u8, sq.index() as u8, Move::CAPTURE);
+                return self.see_for_side(mv, side);
+            }
+        }
+        0
+    }
+
+    /// Swap-off for `mv` played by `side`
+    fn see_for_side(&self, mv: Move, side: Side) -> i32 {
         let from_sq = mv.from_sq();
         let to_sq = mv.to_sq();
-        let mut side_to_move = self.stm;
+        let mut side_to_move = side;
 
         let mut occupied = self.positions.get_occupied_bb();
         let mut gain = [0; 32];
@@ -579,21 +876,117 @@ impl Board {
         Phase(scaled_phase)
     }
 
+    /// Every square attacked by `side`, including squares of its own pieces
+    pub fn attack_map(&self, side: Side) -> BitBoard {
+        Piece::all_pieces()
+            .map(|piece| self.piece_attack_map(side, piece))
+            .fold(BitBoard(0), |acc, attacks| acc | attacks)
+    }
+
+    /// Squares attacked by the `piece`s of `side`
+    pub fn piece_attack_map(&self, side: Side, piece: Piece) -> BitBoard {
+        self.positions
+            .get_piece_bb(side, piece)
+            .iter_bits()
+            .map(|sq| move_gen::get_piece_attacks(self, side, piece, sq))
+            .fold(BitBoard(0), |acc, attacks| acc | attacks)
+    }
+
+    /// Pieces of `side` that are protected by another piece of `side`
+    pub fn defended_map(&self, side: Side) -> BitBoard {
+        self.attack_map(side) & *self.positions.get_side_bb(side)
+    }
+
     pub fn is_in_check(&self, side: Side) -> bool {
         let attack_data = calculate_attack_data(self, side);
         attack_data.in_check
     }
 
+    /// Enemy pieces giving check to `side`'s king. Two bits set means double check
+    pub fn checkers(&self, side: Side) -> BitBoard {
+        calculate_attack_data(self, side).checker_mask
+    }
+
+    /// `make_move` for callers that want to report on the move, like a playing UI.
+    /// Also fills in `MoveInfo::check_given`, which `make_move` skips to stay cheap in search
+    pub fn play_move(&mut self, m: Move) -> miette::Result<MoveInfo> {
+        let check_given = move_gen::gives_check(self, m);
+        let mut move_info = self.make_move(m)?;
+        move_info.check_given = check_given;
+        Ok(move_info)
+    }
+
+    /// Whether `mv` puts the opponent in check. Plays the move on `self` and takes it back
+    /// instead of copying the board. Returns false if `mv` can't be made
+    pub fn is_check_after(&mut self, mv: Move) -> bool {
+        let Ok(move_info) = self.make_move(mv) else {
+            return false;
+        };
+        let gives_check = self.is_in_check(self.stm);
+        self.unmake_move(&move_info)
+            .expect("Should be able to unmake a move that was just made");
+        gives_check
+    }
+
+    /// Renders `moves`, played in order from this position, as SAN with move numbers:
+    /// `["1. e4", "e5", "2. Nf3"]`, or `["1... e5", ...]` when Black moves first.
+    /// Stops at the first move that isn't legal in its position
+    pub fn san_line(&self, moves: &[Move]) -> Vec<String> {
+        let mut board = *self;
+        let mut line = Vec::with_capacity(moves.len());
+        for &mv in moves {
+            let before = board;
+            if board.try_move(mv).is_err() {
+                break;
+            }
+            let san = mv.to_san(&before);
+            let move_number = before.fullmove_counter;
+            line.push(match before.stm {
+                Side::White => format!("{move_number}. {san}"),
+                Side::Black if line.is_empty() => format!("{move_number}... {san}"),
+                Side::Black => san,
+            });
+        }
+        line
+    }
+
+    /// Legal moves that put the opponent in check, quiet or not
+    pub fn checking_moves(&self) -> Vec<Move> {
+        let mut moves = MoveBuffer::new();
+        self.generate_legal_moves(&mut moves, false);
+        moves
+            .into_iter()
+            .filter(|&mv| move_gen::gives_check(self, mv))
+            .collect()
+    }
+
     pub fn is_checkmate(&self, side: Side) -> bool {
-        self.is_in_check(side) && self.get_legal_moves(false).is_empty()
+        self.is_in_check(side) && self.legal_move_count(side) == 0
     }
 
     pub fn is_stalemate(&self, side: Side) -> bool {
-        !self.is_in_check(side) && self.get_legal_moves(false).is_empty()
+        !self.is_in_check(side) && self.legal_move_count(side) == 0
+    }
+
+    /// Number of legal moves `side` has, counted without generating them.
+    /// For the side not to move, it's counted as if it were its turn
+    pub fn legal_move_count(&self, side: Side) -> usize {
+        if side == self.stm {
+            return move_gen::count_legal_moves(self);
+        }
+        let mut turned = *self;
+        turned.stm = side;
+        turned.enpassant_square = None;
+        move_gen::count_legal_moves(&turned)
     }
 
     pub fn is_draw(&self) -> bool {
-        self.is_stalemate(self.stm) || self.halfmove_clock >= 100 || self.is_insufficient_material()
+        self.is_stalemate(self.stm) || self.is_fifty_move_draw() || self.is_insufficient_material()
+    }
+
+    /// Fifty move rule. Mate on the move that completes the fifty moves still counts as a win
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100 && !self.is_checkmate(self.stm)
     }
 
     pub fn evaluate_position(&self, params: &TunableParams) -> i32 {
@@ -601,10 +994,35 @@ impl Board {
         evaluate(self, params).taper(phase)
     }
 
+    /// Same as `evaluate_position`, but takes the PST and material terms from `incremental`,
+    /// which must have been valued with `params`
+    pub fn evaluate_position_incremental(
+        &self,
+        params: &TunableParams,
+        incremental: IncrementalScore,
+    ) -> i32 {
+        let phase = self.game_phase();
+        evaluation::evaluate_incremental(self, params, incremental).taper(phase)
+    }
+
     pub fn get_piece_at(&self, square: Square) -> Option<Piece> {
         self.positions.get_piece_at(&square).map(|(piece, _)| piece)
     }
 
+    /// White's material minus Black's, counted from the bitboards. Like every White
+    /// relative score, positive means White is ahead. Kings aren't counted.
+    /// Uses the fixed `Piece::score` values rather than the eval params, unlike
+    /// `IncrementalScore::material`
+    pub fn material_balance(&self) -> Score {
+        let mut balance = Score::default();
+        for piece in Piece::all_pieces().filter(|&piece| piece != Piece::King) {
+            let white = self.positions.get_piece_bb(Side::White, piece).pop_count() as i32;
+            let black = self.positions.get_piece_bb(Side::Black, piece).pop_count() as i32;
+            balance += piece.score() * (white - black);
+        }
+        balance
+    }
+
     #[allow(deprecated)]
     fn recalculate_material(&mut self) {
         // Reset material
@@ -620,7 +1038,7 @@ impl Board {
         }
     }
 
-    fn is_insufficient_material(&self) -> bool {
+    pub fn is_insufficient_material(&self) -> bool {
         let white_pieces = self.positions.get_side_bb(Side::White);
         let black_pieces = self.positions.get_side_bb(Side::Black);
 
@@ -235,6 +235,68 @@ mod see_tests {
     }
 }
 
+mod see_square_tests {
+    use super::*;
+    use crate::prelude::*;
+
+    /// SEE of the legal capture onto `sq` with the least valuable attacker
+    fn cheapest_capture_see(board: &Board, sq: Square) -> i32 {
+        let mut moves = MoveBuffer::new();
+        board.generate_legal_moves(&mut moves, false);
+        moves
+            .iter()
+            .filter(|m| m.is_capture() && m.to_sq() == sq)
+            .min_by_key(|m| board.get_piece_at(m.from_sq()).unwrap().victim_score())
+            .map(|m| board.static_exchange_evaluation(*m))
+            .expect("Square should be attacked")
+    }
+
+    #[test]
+    fn test_see_square_matches_cheapest_capture() {
+        for (fen, sq) in [
+            // e5 attacked by Nd3 and Re2, defended by Nd7, Bf6, Rd8 and Qh8
+            (
+                "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+                "e5",
+            ),
+            // e5 attacked by pawn and knight
+            (
+                "r1bqkbnr/pppp1ppp/2n5/4p3/3P4/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1",
+                "e5",
+            ),
+            // Undefended pawn
+            ("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e5"),
+        ] {
+            let board = Board::from_fen(fen);
+            let sq = Square::from_str(sq).unwrap();
+            assert_eq!(
+                board.see_square(sq, Side::White),
+                cheapest_capture_see(&board, sq),
+                "{fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_see_square_for_side_not_to_move() {
+        // Same Scotch position, but with Black to move
+        let board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/3P4/5N2/PPP1PPPP/RNBQKB1R b KQkq - 0 1");
+        let sq = Square::from_str("e5").unwrap();
+        assert_eq!(board.see_square(sq, Side::White), 100);
+
+        // No enemy piece or no attacker
+        assert_eq!(
+            board.see_square(Square::from_str("e4").unwrap(), Side::White),
+            0
+        );
+        assert_eq!(
+            board.see_square(Square::from_str("d4").unwrap(), Side::White),
+            0
+        );
+    }
+}
+
 #[cfg(test)]
 mod material_tests {
 
@@ -473,8 +535,10 @@ fn test_en_passant_make_unmake_symmetry() {
         "Scores are not restored perfectly"
     );
     assert_eq!(
-        board, original_board,
-        "Board state was not perfectly restored after unmaking en passant move"
+        board,
+        original_board,
+        "Board state was not perfectly restored after unmaking en passant move\n{}",
+        board.diff(&original_board)
     );
 }
 
@@ -507,6 +571,37 @@ fn test_rook_capture_removes_opponent_castling_rights() {
     );
 }
 
+#[test]
+fn test_rook_takes_rook_clears_both_sides_rights() {
+    use crate::board::zobrist::calculate_hash;
+
+    // (fen, move, rights left afterwards)
+    let cases = [
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "a1a8", "Kk"),
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "h1h8", "Qq"),
+        ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "a8a1", "Kk"),
+        ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "h8h1", "Qq"),
+    ];
+
+    for (fen, uci, expected_rights) in cases {
+        let mut board = Board::from_fen(fen);
+        let before = board;
+        let mv = Move::from_uci(&board, uci).unwrap();
+        assert!(mv.is_capture());
+
+        let info = board.make_move(mv).unwrap();
+        assert_eq!(
+            board.castling_rights.to_string(),
+            expected_rights,
+            "Rights after {uci} from {fen}"
+        );
+        assert_eq!(board.hash, calculate_hash(&board), "Hash after {uci}");
+
+        board.unmake_move(&info).unwrap();
+        assert_eq!(board, before, "Unmake of {uci}\n{}", board.diff(&before));
+    }
+}
+
 #[test]
 fn test_promotion_to_queen_with_capture() {
     init_test_logging();
@@ -547,7 +642,740 @@ fn test_promotion_to_queen_with_capture() {
     // Unmake the move and check for perfect restoration.
     board.unmake_move(&move_data).unwrap();
     assert_eq!(
-        board, original_board,
-        "Board not restored after unmaking promotion with capture"
+        board,
+        original_board,
+        "Board not restored after unmaking promotion with capture\n{}",
+        board.diff(&original_board)
+    );
+}
+
+#[test]
+fn test_incremental_pst_matches_recompute() {
+    use crate::evaluation::pst::{IncrementalScore, compute_pst};
+    use crate::moves::move_buffer::MoveBuffer;
+    use crate::tuning::params::TunableParams;
+    use crate::utils::prng::Prng;
+
+    init_test_logging();
+    let params = TunableParams::default();
+    let mut prng = Prng::init(0x5EED_CAFE);
+
+    for fen in [
+        crate::prelude::START_FEN,
+        crate::prelude::KIWIPETE,
+        "r3k2r/1Ppppppp/8/8/8/8/1P2PPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+    ] {
+        let mut board = Board::from_fen(fen);
+        let mut incremental = IncrementalScore::compute(&board, &params);
+
+        for _ in 0..80 {
+            let mut moves = MoveBuffer::default();
+            board.generate_legal_moves(&mut moves, false);
+            let moves = moves.as_slice();
+            if moves.is_empty() {
+                break;
+            }
+            let mv = moves[prng.rand() as usize % moves.len()];
+
+            let move_data = board.make_move(mv).unwrap();
+            incremental = incremental.after_move(&move_data, &params);
+            assert_eq!(
+                incremental.pst,
+                compute_pst(&board, &params),
+                "Incremental PST drifted after {mv} from {fen}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_incremental_material_matches_recompute() {
+    use crate::evaluation::{
+        evaluate, evaluate_incremental,
+        pst::{IncrementalScore, compute_material},
+    };
+    use crate::moves::move_buffer::MoveBuffer;
+    use crate::tuning::params::TunableParams;
+    use crate::utils::prng::Prng;
+
+    init_test_logging();
+    let params = TunableParams::default();
+    let mut prng = Prng::init(0x0B1A_57ED);
+
+    for fen in [
+        crate::prelude::KIWIPETE,
+        "r3k2r/1Ppppppp/8/8/8/8/1P2PPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ] {
+        let mut board = Board::from_fen(fen);
+        let mut incremental = IncrementalScore::compute(&board, &params);
+
+        for _ in 0..80 {
+            let mut moves = MoveBuffer::default();
+            board.generate_legal_moves(&mut moves, false);
+            let moves = moves.as_slice();
+            if moves.is_empty() {
+                break;
+            }
+            let mv = moves[prng.rand() as usize % moves.len()];
+
+            let move_data = board.make_move(mv).unwrap();
+            incremental = incremental.after_move(&move_data, &params);
+            assert_eq!(
+                incremental.material,
+                compute_material(&board, &params),
+                "Incremental material drifted after {mv} from {fen}"
+            );
+            assert_eq!(
+                evaluate_incremental(&board, &params, incremental),
+                evaluate(&board, &params)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_incremental_scores_follow_non_default_params() {
+    use crate::evaluation::{evaluate, evaluate_incremental, pst::IncrementalScore};
+    use crate::moves::move_buffer::MoveBuffer;
+    use crate::prelude::*;
+    use crate::tuning::params::TunableParams;
+    use crate::utils::prng::Prng;
+
+    // Built before the params exist, so nothing about the board can depend on them
+    let mut board = Board::from_fen(crate::prelude::KIWIPETE);
+
+    let mut params = TunableParams::default();
+    for (i, value) in params.psts.iter_mut().enumerate() {
+        *value += Score::new(i as i32 % 7, -(i as i32 % 5));
+    }
+    params.material[Piece::Knight.index()] += Score::splat(40);
+    params.material[Piece::Queen.index()] -= Score::splat(25);
+    assert_ne!(
+        IncrementalScore::compute(&board, &params),
+        IncrementalScore::compute(&board, &TunableParams::default())
+    );
+
+    let mut prng = Prng::init(0xD1FF_E2E7);
+    let mut incremental = IncrementalScore::compute(&board, &params);
+    for _ in 0..120 {
+        let mut moves = MoveBuffer::default();
+        board.generate_legal_moves(&mut moves, false);
+        let moves = moves.as_slice();
+        if moves.is_empty() {
+            break;
+        }
+        let mv = moves[prng.rand() as usize % moves.len()];
+        let move_data = board.make_move(mv).unwrap();
+        incremental = incremental.after_move(&move_data, &params);
+
+        assert_eq!(
+            incremental,
+            IncrementalScore::compute(&board, &params),
+            "Incremental scores drifted after {mv}"
+        );
+        assert_eq!(
+            evaluate_incremental(&board, &params, incremental),
+            evaluate(&board, &params)
+        );
+    }
+}
+
+#[test]
+fn test_pst_mirrors_black_squares() {
+    use crate::evaluation::accumulator::ScoreAccumulator;
+    use crate::evaluation::position::eval_position;
+    use crate::evaluation::pst::{compute_pst, pst_value};
+    use crate::prelude::*;
+    use crate::tuning::params::TunableParams;
+
+    let e2 = Square::from_str("e2").unwrap().index();
+    let e7 = Square::from_str("e7").unwrap().index();
+    let phase = Phase(128);
+
+    assert_eq!(Side::White.relative_square(e2), e2);
+    assert_eq!(Side::Black.relative_square(e7), e2);
+    let defaults = TunableParams::default();
+    assert_eq!(
+        Piece::Pawn.pst_value(&defaults, Side::White, e2, phase),
+        Piece::Pawn.pst_value(&defaults, Side::Black, e7, phase)
     );
+    assert_eq!(
+        pst_value(&defaults, Piece::Pawn, Side::White, e2),
+        -pst_value(&defaults, Piece::Pawn, Side::Black, e7)
+    );
+
+    // Mirrored positions cancel out, both incrementally and in `eval_position`
+    let board = Board::from_fen("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1");
+    assert_eq!(compute_pst(&board, &defaults), Score::default());
+
+    let mut params = TunableParams::zeros();
+    params.psts = TunableParams::default().psts;
+    let mut acc = ScoreAccumulator::new(&params);
+    eval_position(&board, &mut acc);
+    assert_eq!(acc.score, Score::default());
+}
+
+#[test]
+fn test_fen_round_trip_random_games() {
+    use crate::moves::move_buffer::MoveBuffer;
+    use crate::utils::prng::Prng;
+
+    const POSITIONS: usize = 1000;
+    const MAX_PLIES: usize = 50;
+
+    let mut prng = Prng::init(0xF3E2_D1C0);
+    let mut positions = 0;
+
+    while positions < POSITIONS {
+        let mut board = Board::new();
+        for _ in 0..MAX_PLIES.min(POSITIONS - positions) {
+            let fen = board.to_fen().unwrap();
+            let parsed = Board::try_from_fen(&fen).unwrap();
+
+            // Material, hash and PST score are derived from these.
+            // The "has castled" flags aren't part of FEN, so only the rights are compared
+            assert_eq!(parsed.positions, board.positions, "{fen}");
+            assert_eq!(parsed.stm, board.stm, "{fen}");
+            assert_eq!(
+                parsed.castling_rights.get_rights(),
+                board.castling_rights.get_rights(),
+                "{fen}"
+            );
+            assert_eq!(parsed.enpassant_square, board.enpassant_square, "{fen}");
+            assert_eq!(parsed.halfmove_clock, board.halfmove_clock, "{fen}");
+            assert_eq!(parsed.fullmove_counter, board.fullmove_counter, "{fen}");
+            assert_eq!(parsed.to_fen().unwrap(), fen);
+            positions += 1;
+
+            let mut moves = MoveBuffer::default();
+            board.generate_legal_moves(&mut moves, false);
+            let moves = moves.as_slice();
+            if moves.is_empty() {
+                break;
+            }
+            let mv = moves[prng.rand() as usize % moves.len()];
+            board.make_move(mv).unwrap();
+        }
+    }
+
+    assert_eq!(positions, POSITIONS);
+}
+
+mod halfmove_clock_tests {
+    use super::*;
+
+    fn play(board: &mut Board, uci: &str) {
+        let mv = Move::from_uci(board, uci).unwrap();
+        board.make_move(mv).unwrap();
+    }
+
+    #[test]
+    fn test_fifty_move_draw_boundary() {
+        let mut board = Board::new();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        // 49 reversible move pairs
+        for ply in 0..98 {
+            play(&mut board, shuffle[ply % 4]);
+            assert_eq!(board.halfmove_clock as usize, ply + 1);
+        }
+        play(&mut board, shuffle[98 % 4]);
+        assert_eq!(board.halfmove_clock, 99);
+        assert!(!board.is_draw(), "Not a draw before the 50th move pair");
+
+        play(&mut board, shuffle[99 % 4]);
+        assert_eq!(board.halfmove_clock, 100);
+        assert!(board.is_draw(), "Draw once the 50th move pair is complete");
+    }
+
+    #[test]
+    fn test_mate_beats_fifty_move_rule() {
+        // Rh8# is the 100th reversible half move
+        let mut board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 99 80");
+        play(&mut board, "h1h8");
+        assert_eq!(board.halfmove_clock, 100);
+        assert!(board.is_checkmate(board.stm));
+        assert!(!board.is_fifty_move_draw());
+        assert!(!board.is_draw());
+
+        // Anything else on the 100th half move is a draw
+        let mut board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 99 80");
+        play(&mut board, "h1h7");
+        assert!(board.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_halfmove_clock_resets() {
+        // Pawn move
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K2R w - - 40 60");
+        play(&mut board, "e2e3");
+        assert_eq!(board.halfmove_clock, 0);
+
+        // Quiet moves count up, a capture resets
+        let mut board = Board::from_fen("r3k3/8/8/8/8/8/8/R3K3 w - - 40 60");
+        play(&mut board, "e1e2");
+        play(&mut board, "e8f7");
+        assert_eq!(board.halfmove_clock, 42);
+        play(&mut board, "a1a8");
+        assert_eq!(board.halfmove_clock, 0);
+    }
+
+    #[test]
+    fn test_halfmove_clock_resets_on_en_passant() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3");
+        board.halfmove_clock = 37;
+
+        let ep = Move::from_uci(&board, "e5d6").unwrap();
+        assert!(ep.is_enpassant());
+        assert!(ep.is_capture(), "En passant must count as a capture");
+
+        let move_data = board.make_move(ep).unwrap();
+        assert_eq!(board.halfmove_clock, 0);
+
+        board.unmake_move(&move_data).unwrap();
+        assert_eq!(board.halfmove_clock, 37);
+    }
+}
+
+mod from_squares_tests {
+    use crate::prelude::*;
+
+    fn start_position_squares() -> [Option<PieceInfo>; 64] {
+        let back_rank = [
+            Piece::Rook,
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Queen,
+            Piece::King,
+            Piece::Bishop,
+            Piece::Knight,
+            Piece::Rook,
+        ];
+        let mut squares = [None; 64];
+        for file in 0..8 {
+            squares[file] = Some(PieceInfo::new(back_rank[file], Side::White));
+            squares[8 + file] = Some(PieceInfo::new(Piece::Pawn, Side::White));
+            squares[48 + file] = Some(PieceInfo::new(Piece::Pawn, Side::Black));
+            squares[56 + file] = Some(PieceInfo::new(back_rank[file], Side::Black));
+        }
+        squares
+    }
+
+    #[test]
+    fn test_from_squares_start_position() {
+        let board = Board::from_squares(
+            start_position_squares(),
+            Side::White,
+            CastlingRights::all(),
+            None,
+            0,
+            1,
+        )
+        .unwrap();
+        assert_eq!(board, Board::new());
+    }
+
+    #[test]
+    fn test_from_squares_validation() {
+        let mut squares = start_position_squares();
+        squares[4] = None;
+        let missing_king =
+            Board::from_squares(squares, Side::White, CastlingRights::all(), None, 0, 1);
+        assert!(missing_king.is_err());
+
+        let mut squares = start_position_squares();
+        squares[27] = Some(PieceInfo::new(Piece::King, Side::Black));
+        let two_kings =
+            Board::from_squares(squares, Side::White, CastlingRights::all(), None, 0, 1);
+        assert!(two_kings.is_err());
+
+        // No pawn just double pushed past e3
+        let bad_ep = Board::from_squares(
+            start_position_squares(),
+            Side::Black,
+            CastlingRights::all(),
+            Square::new(20),
+            0,
+            1,
+        );
+        assert!(bad_ep.is_err());
+    }
+}
+
+mod malformed_move_tests {
+    use super::*;
+    use crate::consts::START_FEN;
+
+    fn make_err(fen: &str, from: &str, to: &str, flags: u16) -> String {
+        let mut board = Board::from_fen(fen);
+        let before = board;
+        let mv = Move::new(
+            Square::from_str(from).unwrap().index() as u8,
+            Square::from_str(to).unwrap().index() as u8,
+            flags,
+        );
+        let err = board.make_move(mv).unwrap_err().to_string();
+        assert_eq!(
+            board, before,
+            "Board must be untouched after a rejected move"
+        );
+        err
+    }
+
+    #[test]
+    fn test_capture_flag_on_empty_square() {
+        let err = make_err(START_FEN, "g1", "f3", Move::CAPTURE);
+        assert!(err.contains("flagged as a capture"), "{err}");
+    }
+
+    #[test]
+    fn test_quiet_flag_onto_enemy_piece() {
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
+        let err = make_err(fen, "e4", "d5", Move::QUIET);
+        assert!(err.contains("isn't flagged as a capture"), "{err}");
+    }
+
+    #[test]
+    fn test_capture_of_own_piece() {
+        let err = make_err(START_FEN, "d1", "d2", Move::CAPTURE);
+        assert!(err.contains("its own"), "{err}");
+    }
+
+    #[test]
+    fn test_en_passant_without_ep_square() {
+        let fen = "rnbqkbnr/pp1p1ppp/8/2pPp3/8/8/PPP1PPPP/RNBQKBNR w KQkq - 0 3";
+        let err = make_err(fen, "d5", "e6", Move::EN_PASSANT);
+        assert!(err.contains("en passant"), "{err}");
+    }
+}
+
+mod is_check_after_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_is_check_after_matches_copy() {
+        let mut checks = 0;
+        for fen in [
+            KIWIPETE,
+            // Discovered checks, promotions with check and en passant
+            "4k3/8/8/2KPp2r/8/8/3N4/1B5R w - e6 0 1",
+            "3k4/1P6/8/8/8/8/8/4K2R w K - 0 1",
+        ] {
+            let mut board = Board::from_fen(fen);
+            let original = board;
+
+            for mv in board.get_legal_moves(false) {
+                let mut copy = board;
+                copy.make_move(mv).unwrap();
+                let expected = copy.is_in_check(copy.stm);
+
+                assert_eq!(board.is_check_after(mv), expected, "{fen}: {}", mv.uci());
+                assert_eq!(board, original, "{fen}: {} wasn't undone", mv.uci());
+                checks += expected as usize;
+            }
+        }
+        assert!(checks > 0);
+    }
+
+    #[test]
+    fn test_play_move_reports_check_given() {
+        // Scholar's mate
+        let mut board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 0 1");
+        let mate = Move::new(21, 53, Move::CAPTURE);
+        let info = board.play_move(mate).unwrap();
+        assert!(info.check_given);
+        assert_eq!(info.captured_piece, Some(Piece::Pawn));
+        assert!(board.is_checkmate(board.stm));
+
+        board.unmake_move(&info).unwrap();
+        let info = board.play_move(Move::new(21, 29, Move::QUIET)).unwrap();
+        assert!(!info.check_given);
+        assert!(!info.is_castling);
+    }
+}
+
+mod checkers_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_checkers() {
+        // Double check from the rook on e1 and the knight on d6
+        let board = Board::from_fen("4k3/8/3N4/8/8/8/8/K3R3 b - - 0 1");
+        let checkers = board.checkers(Side::Black);
+        assert_eq!(checkers.pop_count(), 2);
+        assert!(checkers.contains_square(Square::from_str("e1").unwrap().index()));
+        assert!(checkers.contains_square(Square::from_str("d6").unwrap().index()));
+
+        let board = Board::from_fen("4k3/4r3/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(board.checkers(Side::White).pop_count(), 1);
+        assert!(board.checkers(Side::Black).is_empty());
+
+        assert!(Board::new().checkers(Side::White).is_empty());
+        assert!(Board::from_fen(KIWIPETE).checkers(Side::White).is_empty());
+    }
+}
+
+mod diff_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_board_diff() {
+        let board = Board::from_fen(KIWIPETE);
+        assert_eq!(board.diff(&board), "");
+
+        // Only e2 differs, the hashes and scores follow from it
+        let pawn = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        let knight = Board::from_fen("4k3/8/8/8/8/8/4N3/4K3 w - - 0 1");
+        let diff = pawn.diff(&knight);
+        // Square lines look like `E2: ...`
+        let squares: Vec<&str> = diff
+            .lines()
+            .filter(|line| line.find(':') == Some(2))
+            .collect();
+        assert_eq!(squares, ["E2: White Pawn -> White Knight"], "{diff}");
+        assert!(diff.contains("\nhash: "), "{diff}");
+        assert!(!diff.contains("side to move"), "{diff}");
+
+        let black = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 3 1");
+        let diff = pawn.diff(&black);
+        assert!(diff.contains("side to move: White -> Black"), "{diff}");
+        assert!(diff.contains("halfmove clock: 0 -> 3"), "{diff}");
+        assert!(!diff.contains("E2"), "{diff}");
+    }
+}
+
+mod san_line_tests {
+    use crate::prelude::*;
+
+    /// Parses `ucis` one after the other from `board`
+    fn moves(board: &Board, ucis: &[&str]) -> Vec<Move> {
+        let mut board = *board;
+        ucis.iter()
+            .map(|uci| {
+                let mv = Move::from_uci(&board, uci).unwrap();
+                board.make_move(mv).unwrap();
+                mv
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_san_line() {
+        let board = Board::new();
+        let pv = moves(&board, &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"]);
+        assert_eq!(board.san_line(&pv).join(" "), "1. e4 e5 2. Nf3 Nc6 3. Bb5");
+
+        // Black to move gets an ellipsis on its first move only
+        let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let pv = moves(&board, &["e7e5", "g1f3", "d8h4"]);
+        assert_eq!(board.san_line(&pv), ["1... e5", "2. Nf3", "Qh4"]);
+
+        // Stops at a move that doesn't fit the position it's played in
+        let mut pv = moves(&Board::new(), &["e2e4"]);
+        pv.push(pv[0]);
+        assert_eq!(Board::new().san_line(&pv), ["1. e4"]);
+    }
+}
+
+mod checking_moves_tests {
+    use crate::prelude::*;
+
+    fn sorted_uci(moves: Vec<Move>) -> Vec<String> {
+        let mut moves: Vec<String> = moves.into_iter().map(|m| m.uci()).collect();
+        moves.sort_unstable();
+        moves
+    }
+
+    #[test]
+    fn test_checking_moves() {
+        // Promotions to a queen or rook, and the rook along the back rank
+        let board = Board::from_fen("3k4/1P6/8/8/8/8/8/4K2R w K - 0 1");
+        assert_eq!(
+            sorted_uci(board.checking_moves()),
+            ["b7b8q", "b7b8r", "h1h8"]
+        );
+
+        // Castling checks with the rook
+        let board = Board::from_fen("5k2/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert!(
+            board
+                .checking_moves()
+                .contains(&Move::new(4, 6, Move::KING_CASTLE))
+        );
+
+        for fen in [
+            KIWIPETE,
+            "3k4/1P6/8/8/8/8/8/4K2R w K - 0 1",
+            // Discovered checks and en passant
+            "4k3/8/8/2KPp2r/8/8/3N4/1B5R w - e6 0 1",
+            "4k3/8/8/8/1b6/8/3P4/4K3 b - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            let board = Board::from_fen(fen);
+            let checks = board.checking_moves();
+            for &mv in &checks {
+                let mut copy = board;
+                copy.make_move(mv).unwrap();
+                assert!(copy.is_in_check(copy.stm), "{fen}: {}", mv.uci());
+            }
+
+            let expected: Vec<Move> = board
+                .get_legal_moves(false)
+                .into_iter()
+                .filter(|&mv| {
+                    let mut copy = board;
+                    copy.make_move(mv).unwrap();
+                    copy.is_in_check(copy.stm)
+                })
+                .collect();
+            assert_eq!(sorted_uci(checks), sorted_uci(expected), "{fen}");
+        }
+    }
+}
+
+mod repetition_key_tests {
+    use crate::prelude::*;
+
+    fn play(fen: &str, moves: &[&str]) -> Board {
+        let mut board = Board::from_fen(fen);
+        for uci in moves {
+            let mv = Move::from_uci(&board, uci).unwrap();
+            board.make_move(mv).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn test_transpositions_share_repetition_key() {
+        let a = play(START_FEN, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+        let b = play(START_FEN, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+        assert_eq!(a.repetition_key(), b.repetition_key());
+
+        // No pawn can take on e3, so the double push doesn't stop the repetition
+        let a = play(START_FEN, &["e2e4"]);
+        let b = play(START_FEN, &["e2e4", "g8f6", "g1f3", "f6g8", "f3g1"]);
+        assert_eq!(a.repetition_key(), b.repetition_key());
+
+        // Here it can, so it's a different position
+        let a = play("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1", &["e2e4"]);
+        let b = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1");
+        assert_ne!(a.repetition_key(), b.repetition_key());
+    }
+}
+
+mod legal_move_count_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_count_matches_generated_moves() {
+        let fens = [
+            START_FEN,
+            KIWIPETE,
+            // Pins, en passant, promotions, checks and mates
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "4k3/8/8/2KPp2r/8/8/3N4/1B5R w - e6 0 1",
+            "R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+        ];
+        for fen in fens {
+            // The position and every position one move later
+            let board = Board::from_fen(fen);
+            let mut positions = vec![board];
+            for mv in board.get_legal_moves(false) {
+                let mut child = board;
+                child.make_move(mv).unwrap();
+                positions.push(child);
+            }
+
+            for pos in positions {
+                let expected = pos.get_legal_moves(false).len();
+                assert_eq!(
+                    pos.legal_move_count(pos.stm),
+                    expected,
+                    "{}",
+                    pos.to_fen().unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_for_side_not_to_move() {
+        let board = Board::from_fen(START_FEN);
+        assert_eq!(board.legal_move_count(Side::Black), 20);
+
+        let mut board = Board::from_fen(KIWIPETE);
+        let white = board.legal_move_count(Side::White);
+        board.stm = Side::Black;
+        assert_eq!(board.legal_move_count(Side::White), white);
+    }
+}
+
+mod attack_map_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_start_position_attack_map() {
+        let board = Board::new();
+        // Every square on the 2nd and 3rd rank, and the back rank except for the corners
+        let back_rank = BitBoard(RANK_MASKS[0] & !(1 << 0) & !(1 << 7));
+        let expected = BitBoard(RANK_MASKS[1] | RANK_MASKS[2]) | back_rank;
+        assert_eq!(board.attack_map(Side::White), expected);
+        assert_eq!(
+            board.defended_map(Side::White),
+            BitBoard(RANK_MASKS[1]) | back_rank
+        );
+
+        assert_eq!(
+            board.piece_attack_map(Side::White, Piece::Pawn),
+            BitBoard(RANK_MASKS[2])
+        );
+        // Knights attack a3, c3, d2 and e2, f3, h3
+        let knights = [16, 18, 11, 12, 21, 23]
+            .into_iter()
+            .fold(BitBoard(0), |acc, sq| acc | BitBoard(1 << sq));
+        assert_eq!(board.piece_attack_map(Side::White, Piece::Knight), knights);
+
+        // Black's map is the mirror image
+        let mirrored = BitBoard(board.attack_map(Side::Black).0.swap_bytes());
+        assert_eq!(mirrored, expected);
+    }
+}
+
+mod material_balance_tests {
+    use crate::prelude::*;
+    use crate::tuning::params::TunableParams;
+
+    #[test]
+    fn test_material_balance_is_white_relative() {
+        assert_eq!(Board::new().material_balance(), Score::default());
+
+        // White is up a queen, whoever is to move
+        let white_up = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let balance = Board::from_fen(white_up).material_balance();
+        assert_eq!(balance, Piece::Queen.score());
+        let black_to_move = white_up.replace(" w ", " b ");
+        assert_eq!(Board::from_fen(&black_to_move).material_balance(), balance);
+
+        // Black up a queen is the mirror image
+        let black_up = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1");
+        assert_eq!(black_up.material_balance(), -balance);
+    }
+
+    #[test]
+    fn test_evaluate_is_stm_relative() {
+        let params = TunableParams::default();
+        let white_up = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let white_to_move = Board::from_fen(white_up).evaluate_position(&params);
+        let black_to_move =
+            Board::from_fen(&white_up.replace(" w ", " b ")).evaluate_position(&params);
+
+        assert!(white_to_move > 500, "{white_to_move}");
+        assert!(black_to_move < -500, "{black_to_move}");
+    }
 }
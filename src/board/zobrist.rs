@@ -1,5 +1,11 @@
 use crate::prelude::*;
 
+/// Seed of the `Prng` the Zobrist keys are drawn from
+pub const ZOBRIST_SEED: u64 = 1070373321345817214;
+
+/// Built at compile time from `ZOBRIST_SEED`, so every build hashes a given position to the
+/// same key. Changing the seed or the order keys are drawn in invalidates hashes saved by
+/// older builds. The keys are not Polyglot's, book lookups need their own hash
 pub static ZOBRIST: ZobristKeys = ZobristKeys::new();
 
 #[derive(Debug)]
@@ -26,8 +32,9 @@ impl Default for ZobristKeys {
 }
 
 impl ZobristKeys {
+    /// Draws `black_to_move`, then the piece, castling and en passant keys in that order
     pub const fn new() -> Self {
-        let mut rng = Prng::init(1070373321345817214);
+        let mut rng = Prng::init(ZOBRIST_SEED);
         let mut keys = Self {
             pieces: [[[0; NUM_SQUARES]; NUM_PIECES]; NUM_SIDES],
             castling: [0; NUM_CASTLING_RIGHTS],
@@ -107,6 +114,19 @@ pub fn calculate_hash(board: &Board) -> u64 {
     hash
 }
 
+/// Hash of the pawn placement only, used to key the pawn hash table
+pub fn calculate_pawn_hash(board: &Board) -> u64 {
+    let mut hash = 0;
+
+    for side in [Side::White, Side::Black] {
+        for sq in board.positions.get_piece_bb(side, Piece::Pawn).iter_bits() {
+            hash ^= ZOBRIST.pieces[side.index()][Piece::Pawn.index()][sq];
+        }
+    }
+
+    hash
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -173,6 +193,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_incremental_pawn_hash() {
+        // Pawn pushes, piece moves, captures by and of pawns, en passant and castling
+        let moves = [
+            "e2e4", "d7d5", "e4d5", "g8f6", "g1f3", "f6d5", "c2c4", "d5b6", "c4c5", "e7e5", "c5b6",
+            "c7b6", "f1e2", "e5e4", "e1g1", "f8e7", "d2d4", "e4d3", "e2d3", "c8f5", "b2b4", "h7h5",
+            "b4b5", "h5h4", "g2g4", "h4g3", "h2g3", "h8h1", "g1h1", "b8d7",
+        ];
+
+        let mut board = Board::new();
+        let mut history = Vec::new();
+        for uci in moves {
+            let m = Move::from_uci(&board, uci).unwrap();
+            history.push(board.make_move(m).unwrap());
+            assert_eq!(board.pawn_hash, calculate_pawn_hash(&board), "after {uci}");
+        }
+
+        for info in history.iter().rev() {
+            board.unmake_move(info).unwrap();
+            assert_eq!(board.pawn_hash, calculate_pawn_hash(&board));
+        }
+        assert_eq!(board.pawn_hash, Board::new().pawn_hash);
+
+        // Promotions, with and without capture
+        for uci in ["b7b8q", "b7a8n"] {
+            let mut board = Board::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+            let m = Move::from_uci(&board, uci).unwrap();
+            board.make_move(m).unwrap();
+            assert_eq!(board.pawn_hash, calculate_pawn_hash(&board), "after {uci}");
+            assert_eq!(board.pawn_hash, 0);
+        }
+    }
+
+    #[test]
+    fn test_pawn_hash_ignores_pieces() {
+        let a = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let b = Board::from_fen("r1bqkb1r/pppppppp/2n2n2/8/8/2N2N2/PPPPPPPP/R1BQKB1R w KQkq - 4 3");
+        assert_eq!(a.pawn_hash, b.pawn_hash);
+        assert_ne!(a.hash, b.hash);
+    }
+
     #[test]
     #[ignore]
     fn test_zobrist_collisions_with_perft() {
@@ -371,4 +432,17 @@ mod tests {
             "Hash should be identical when FEN's EP square is physically impossible"
         );
     }
+
+    #[test]
+    fn test_hash_is_pinned() {
+        // Guards against accidental changes to the keys, which would break saved hashes
+        let mut board = Board::new();
+        assert_eq!(calculate_hash(&board), 0xa2186995b658d282);
+        assert_eq!(board.hash, calculate_hash(&board));
+
+        board
+            .make_move(Move::new(12, 28, Move::DOUBLE_PAWN))
+            .unwrap();
+        assert_eq!(board.hash, 0x0f19deb9c6f41da0);
+    }
 }
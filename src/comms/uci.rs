@@ -1,28 +1,49 @@
 use std::{
-    io::BufRead,
+    io::{BufRead, Write},
+    path::PathBuf,
     sync::{
         Arc, Mutex,
         atomic::{AtomicBool, Ordering},
     },
     thread::{self},
+    time::Duration,
 };
 
 use crate::{
     comms::uci_parser::{GoParams, UciCommand, parse_line},
     prelude::*,
-    search::{SearchStats, common::SearchConfig},
+    search::{
+        SearchStats,
+        common::{InfoFormat, SearchConfig, SearchLimits, SharedBestMove, StrengthLimit},
+    },
     tuning::params::TunableParams,
+    utils::adjudication::{GameResult, adjudicate},
 };
 
+/// Higher than the usual ~10ms on purpose: Move Overhead replaces the fixed 50ms safety
+/// margin the time manager used to subtract, so the default keeps the old behavior
+const DEFAULT_MOVE_OVERHEAD_MS: u64 = 50;
+/// Least time to think per move, as long as the clock allows it
+const MIN_MOVE_TIME_MS: u64 = 50;
+
 #[derive(Debug)]
 pub struct UciState {
     board: Board,
     search_depth: u16,
     search: Arc<Mutex<AlphaBetaSearch>>,
     search_running: Arc<AtomicBool>,
+    /// Best move of the running search's last completed depth
+    best_move: Arc<SharedBestMove>,
     search_thread: Option<thread::JoinHandle<SearchResult>>,
     move_history: Vec<MoveInfo>,
     search_stats: Arc<Mutex<SearchStats>>,
+    /// UCI_LimitStrength and UCI_Elo
+    limit_strength: bool,
+    elo: u16,
+    /// Move Overhead in ms, reserved for communication latency
+    move_overhead: u64,
+    /// HashFile, where the SaveHash and LoadHash buttons write and read the TT
+    hash_file: Option<PathBuf>,
 }
 
 impl Default for UciState {
@@ -32,9 +53,14 @@ impl Default for UciState {
             search_depth: u16::default(),
             search: Arc::default(),
             search_running: Arc::default(),
+            best_move: Arc::default(),
             search_thread: None,
             move_history: Vec::default(),
             search_stats: Arc::new(Mutex::new(SearchStats::default())),
+            limit_strength: false,
+            elo: StrengthLimit::MAX_ELO,
+            move_overhead: DEFAULT_MOVE_OVERHEAD_MS,
+            hash_file: None,
         }
     }
 }
@@ -52,13 +78,14 @@ impl UciState {
     pub fn new(depth: Option<u16>, params: TunableParams) -> miette::Result<Self> {
         let depth = depth.unwrap_or(20);
         let search_running = Arc::new(AtomicBool::new(false));
+        let best_move = Arc::new(SharedBestMove::new());
         let conf = SearchConfig {
             hash_size_mb: 256,
             ..Default::default()
         };
         let mut s = AlphaBetaSearch::with_eval(params)
             .with_config(conf)?
-            .init(search_running.clone());
+            .init(search_running.clone(), best_move.clone());
         s.set_depth(depth);
         let search = Arc::new(Mutex::new(s));
         Ok(Self {
@@ -66,12 +93,25 @@ impl UciState {
             search_depth: depth,
             search,
             search_running,
+            best_move,
             search_thread: None,
             move_history: Vec::new(),
             search_stats: Arc::new(Mutex::new(SearchStats::default())),
+            limit_strength: false,
+            elo: StrengthLimit::MAX_ELO,
+            move_overhead: DEFAULT_MOVE_OVERHEAD_MS,
+            hash_file: None,
         })
     }
 
+    /// Pushes the current UCI_LimitStrength/UCI_Elo settings to the search
+    fn apply_strength(&mut self) {
+        let strength = self
+            .limit_strength
+            .then(|| StrengthLimit::from_elo(self.elo));
+        self.search.lock().unwrap().set_strength(strength);
+    }
+
     fn reset(&mut self) {
         trace!("Resetting UciState");
         self.board = Board::new();
@@ -110,6 +150,11 @@ pub fn play(params: TunableParams) -> miette::Result<()> {
             }
             UciCommand::Stop => cmd_stop(&mut state),
             UciCommand::Quit => break,
+            UciCommand::Display => {
+                if let Err(e) = cmd_display(&state, &mut std::io::stdout()) {
+                    warn!("Error displaying position: {e:?}");
+                }
+            }
             UciCommand::Unknown(cmd) => {
                 if !cmd.is_empty() {
                     info!("Received unknown command: {cmd}");
@@ -145,13 +190,17 @@ fn cmd_position(
     let mut search_guard = state.search.lock().unwrap();
     search_guard.repetition_table.clear();
 
-    search_guard.repetition_table.push(state.board.hash);
+    search_guard
+        .repetition_table
+        .push(state.board.repetition_key());
 
     for move_uci in moves {
-        let mov = Move::from_uci(&state.board, &move_uci)?;
+        let mov = Move::from_uci(&state.board, &move_uci).into_diagnostic()?;
         let move_info = state.board.make_move(mov)?;
         state.move_history.push(move_info);
-        search_guard.repetition_table.push(state.board.hash);
+        search_guard
+            .repetition_table
+            .push(state.board.repetition_key());
     }
     // NOTE: Search's find_best_move already pushes the root node, so pop here to avoid duplication
     search_guard.repetition_table.pop();
@@ -159,58 +208,85 @@ fn cmd_position(
     Ok(())
 }
 
-// #[instrument(skip_all)]
-fn cmd_go(state: &mut UciState, params: GoParams) {
-    let board = state.board;
-    let search_running = state.search_running.clone();
-    let default_depth = state.search_depth;
-    let search = state.search.clone();
-    let search_stats = state.search_stats.clone();
-
-    // Time Management Logic
-    let mut max_time_ms: Option<u64> = None;
-    let (time_remaining, increment) = if board.stm == Side::White {
+/// Time in ms to spend on this move, `None` if the clock isn't given.
+/// `move_overhead` is kept back for the GUI's communication latency, and the result
+/// never exceeds the time left on the clock
+fn allocate_time(params: &GoParams, stm: Side, move_overhead: u64) -> Option<u64> {
+    let (time_remaining, increment) = if stm == Side::White {
         (params.wtime, params.winc.unwrap_or(0))
     } else {
         (params.btime, params.binc.unwrap_or(0))
     };
+    let time = time_remaining?;
 
-    if let Some(time) = time_remaining {
-        let overhead = 50; // Safety buffer
-        let allocation;
+    let allocation;
 
-        if let Some(moves_to_go) = params.moves_to_go {
-            // Tournament mode with move counters
-            let divisor = (moves_to_go + 2).clamp(1, 50);
-            allocation = (time / divisor).saturating_sub(overhead);
-        } else {
-            // Sudden Death (30+0.3)
+    if let Some(moves_to_go) = params.moves_to_go {
+        // Tournament mode with move counters
+        let divisor = (moves_to_go + 2).clamp(1, 50);
+        allocation = time / divisor;
+    } else {
+        // Sudden Death (30+0.3)
 
-            let divisor = 20;
-            let base_target = (time / divisor) + (increment / 2);
+        let divisor = 20;
+        let base_target = (time / divisor) + (increment / 2);
 
-            let min_think_time = 1500;
-            let panic_threshold = 5000;
+        let min_think_time = 1500;
+        let panic_threshold = 5000;
 
-            let target = if time > panic_threshold {
-                base_target.max(min_think_time)
-            } else {
-                base_target
-            };
+        let target = if time > panic_threshold {
+            base_target.max(min_think_time)
+        } else {
+            base_target
+        };
 
-            let safety_max = (time * 7) / 10;
+        let safety_max = (time * 7) / 10;
 
-            allocation = target.min(safety_max).saturating_sub(overhead);
-        }
+        allocation = target.min(safety_max);
+    }
 
-        max_time_ms = Some(allocation.max(50));
+    let max_time_ms = allocation
+        .saturating_sub(move_overhead)
+        .max(MIN_MOVE_TIME_MS)
+        .min(time.saturating_sub(move_overhead).max(1));
 
-        info!(
-            "Time Management: Remaining={:?}ms, Allocating={:?}ms",
-            time,
-            max_time_ms.unwrap()
-        );
+    info!(
+        "Time Management: Remaining={:?}ms, Overhead={:?}ms, Allocating={:?}ms",
+        time, move_overhead, max_time_ms
+    );
+    Some(max_time_ms)
+}
+
+/// Limits for one `go`, built from scratch so nothing carries over from the previous one.
+/// Depth and time can both be set, the search stops at whichever is hit first.
+/// Without a clock, the search is depth limited to `default_depth` unless told otherwise
+fn go_limits(params: &GoParams, max_time_ms: Option<u64>, default_depth: u16) -> SearchLimits {
+    if params.infinite {
+        return SearchLimits::infinite();
+    }
+    SearchLimits {
+        max_depth: params
+            .depth
+            .or(max_time_ms.is_none().then_some(default_depth)),
+        max_time: max_time_ms.map(Duration::from_millis),
+        ..Default::default()
     }
+}
+
+// #[instrument(skip_all)]
+fn cmd_go(state: &mut UciState, params: GoParams) {
+    let board = state.board;
+    let search_running = state.search_running.clone();
+    let default_depth = state.search_depth;
+    let search = state.search.clone();
+    let search_stats = state.search_stats.clone();
+
+    let max_time_ms = allocate_time(&params, board.stm, state.move_overhead);
+    let limits = go_limits(&params, max_time_ms, default_depth);
+    let game_over = game_over_line(&board, &state.move_history);
+
+    // Armed before spawning, so a `stop` that arrives before the search starts isn't lost
+    search_running.store(true, Ordering::Relaxed);
 
     info!("Spawning thread");
     state.search_thread = Some(thread::spawn(move || {
@@ -218,15 +294,11 @@ fn cmd_go(state: &mut UciState, params: GoParams) {
         let stat: SearchStats;
         {
             let mut search = search.lock().unwrap();
-            if let Some(time) = max_time_ms {
-                info!("changing time {:?}", max_time_ms);
-                search.set_time(time);
-            } else {
-                info!("changing depth {:?}", params.depth.unwrap_or(default_depth));
-                search.set_depth(params.depth.unwrap_or(default_depth));
-            }
+            // With `infinite` it runs until `stop`, the current best move is readable
+            // from `best_move`
+            info!("Searching with {:?}", limits);
+            search.set_limits(limits);
 
-            search_running.store(true, Ordering::Relaxed);
             result = search.find_best_move(&board);
             stat = search.get_stats();
             search_running.store(false, Ordering::Relaxed);
@@ -238,20 +310,41 @@ fn cmd_go(state: &mut UciState, params: GoParams) {
             current_stats.log_summary();
         }
 
-        if let Some(best_move) = result.best_move {
-            println!("bestmove {}", best_move.uci());
-        } else {
-            println!("bestmove 0000");
+        if let Some(line) = &game_over {
+            println!("{line}");
         }
+        println!("{}", bestmove_line(&result));
         result
     }));
 }
 
+/// The search falls back to its first legal move, so this is only `0000` without legal moves
+fn bestmove_line(result: &SearchResult) -> String {
+    format!("bestmove {}", result.best_move.unwrap_or(Move::NULL).uci())
+}
+
+/// `info string` saying why the game is already over at the root. Sent before `bestmove`,
+/// so a `bestmove 0000` can be told apart from a failed search
+fn game_over_line(board: &Board, history: &[MoveInfo]) -> Option<String> {
+    adjudicate(board, history).map(|result| match result {
+        GameResult::Draw(_) => format!("info string game over {result}"),
+        GameResult::WhiteWins | GameResult::BlackWins => {
+            format!("info string game over {result} (Checkmate)")
+        }
+    })
+}
+
 fn cmd_stop(state: &mut UciState) {
+    stop_search(state);
+}
+
+/// Stops the running search, if any, and waits for it to print its `bestmove`
+fn stop_search(state: &mut UciState) -> Option<SearchResult> {
     state.search_running.store(false, Ordering::Relaxed);
-    if let Some(handle) = state.search_thread.take() {
-        let _ = handle.join();
-    }
+    state
+        .search_thread
+        .take()
+        .and_then(|handle| handle.join().ok())
 }
 
 fn cmd_setoption(state: &mut UciState, name: &str, value: &str) -> miette::Result<()> {
@@ -263,23 +356,66 @@ fn cmd_setoption(state: &mut UciState, name: &str, value: &str) -> miette::Resul
         }
         "Hash" => {
             let size_mb = value.parse::<usize>().into_diagnostic()?;
-            let mut conf;
-            let params;
-            let lim;
-            {
-                let search = state.search.lock().unwrap();
-                conf = search.get_config();
-                params = search.get_params();
-                conf.hash_size_mb = size_mb;
-                lim = search.get_limits();
-            }
-            let s = AlphaBetaSearch::with_eval(params)
-                .with_config(conf)?
-                .with_limits(lim)
-                .init(state.search_running.clone());
-            state.search = Arc::new(Mutex::new(s));
+            state.search.lock().unwrap().resize_hash(size_mb)?;
             info!("Set new hash size to {size_mb}");
         }
+        "PreserveHashOnResize" => {
+            let preserve = value.to_lowercase() == "true";
+            state
+                .search
+                .lock()
+                .unwrap()
+                .set_preserve_hash_on_resize(preserve);
+            info!("Set preserve hash on resize to {preserve}");
+        }
+        "HashFile" => {
+            // GUIs send every option back, an empty path just means none is set
+            state.hash_file = (!value.is_empty()).then(|| PathBuf::from(value));
+            info!("Set hash file to {:?}", state.hash_file);
+        }
+        "SaveHash" => match &state.hash_file {
+            Some(path) => {
+                state.search.lock().unwrap().save_hash(path)?;
+                info!("Saved hash to {}", path.display());
+            }
+            None => info!("No HashFile set, not saving the hash"),
+        },
+        "LoadHash" => match &state.hash_file {
+            Some(path) => {
+                if state.search.lock().unwrap().load_hash(path)? {
+                    info!("Loaded hash from {}", path.display());
+                }
+            }
+            None => info!("No HashFile set, not loading the hash"),
+        },
+        "UCI_LimitStrength" => {
+            state.limit_strength = value.to_lowercase() == "true";
+            state.apply_strength();
+            info!("Set limit strength to {}", state.limit_strength);
+        }
+        "UCI_Elo" => {
+            state.elo = value.parse::<u16>().into_diagnostic()?;
+            state.apply_strength();
+            info!("Set elo to {}", state.elo);
+        }
+        "UCI_AnalyseMode" => {
+            let enabled = value.to_lowercase() == "true";
+            state.search.lock().unwrap().set_analyse_mode(enabled);
+            info!("Set analyse mode to {enabled}");
+        }
+        "Move Overhead" => {
+            state.move_overhead = value.parse::<u64>().into_diagnostic()?;
+            info!("Set move overhead to {}ms", state.move_overhead);
+        }
+        "InfoFormat" => {
+            let format = match value.to_lowercase().as_str() {
+                "uci" => InfoFormat::Uci,
+                "json" => InfoFormat::Json,
+                _ => miette::bail!("Unknown InfoFormat '{value}', expected 'uci' or 'json'"),
+            };
+            state.search.lock().unwrap().set_info_format(format);
+            info!("Set info format to {format:?}");
+        }
         _ => {
             info!("Unknown option: {name} = {value}");
         }
@@ -287,6 +423,27 @@ fn cmd_setoption(state: &mut UciState, name: &str, value: &str) -> miette::Resul
     Ok(())
 }
 
+/// Prints the board, its FEN, Zobrist key and static eval, like Stockfish's `d`
+fn cmd_display(state: &UciState, out: &mut impl Write) -> miette::Result<()> {
+    let board = &state.board;
+    writeln!(out, "{board}").into_diagnostic()?;
+    writeln!(out, "Fen: {}", board.to_fen()?).into_diagnostic()?;
+    writeln!(out, "Key: {:016X}", board.hash).into_diagnostic()?;
+
+    // The search thread holds the lock for the whole search, so don't wait on it
+    match state.search.try_lock() {
+        Ok(search) => {
+            let eval = board.evaluate_position(&search.get_params());
+            writeln!(out, "Eval: {eval} (stm relative)").into_diagnostic()?;
+        }
+        Err(_) => writeln!(out, "Eval: unavailable while searching").into_diagnostic()?,
+    }
+    if let Some((mv, score, depth)) = state.best_move.load() {
+        writeln!(out, "Best: {} (depth {depth}, score cp {score})", mv.uci()).into_diagnostic()?;
+    }
+    Ok(())
+}
+
 fn cmd_isready() {
     println!("readyok");
 }
@@ -297,6 +454,204 @@ fn cmd_uci() {
     println!();
     println!("option name Debug Log File type string default");
     println!("option name Hash type spin default 16 min 16 max 512");
+    println!("option name PreserveHashOnResize type check default false");
+    println!("option name HashFile type string default");
+    println!("option name SaveHash type button");
+    println!("option name LoadHash type button");
     println!("option name Threads type spin default 1 min 1 max 1");
+    println!("option name UCI_LimitStrength type check default false");
+    println!("option name UCI_AnalyseMode type check default false");
+    println!("option name InfoFormat type combo default uci var uci var json");
+    println!(
+        "option name Move Overhead type spin default {DEFAULT_MOVE_OVERHEAD_MS} min 0 max 5000"
+    );
+    println!(
+        "option name UCI_Elo type spin default {} min {} max {}",
+        StrengthLimit::MAX_ELO,
+        StrengthLimit::MIN_ELO,
+        StrengthLimit::MAX_ELO
+    );
     println!("uciok");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_prints_fen() {
+        let mut state = UciState::default();
+        cmd_position(
+            &mut state,
+            true,
+            None,
+            vec!["e2e4".to_string(), "c7c5".to_string()],
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        cmd_display(&state, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let expected = format!("Fen: {}", state.board.to_fen().unwrap());
+        assert!(
+            out.lines().any(|line| line == expected),
+            "missing `{expected}` in:\n{out}"
+        );
+        assert!(out.contains(&format!("Key: {:016X}", state.board.hash)));
+    }
+
+    #[test]
+    fn test_move_overhead_reduces_allocation() {
+        let mut state = UciState::default();
+        let params = GoParams {
+            wtime: Some(60_000),
+            btime: Some(60_000),
+            ..Default::default()
+        };
+        // A twentieth of the clock, with no hidden buffer on top of the overhead
+        let base = allocate_time(&params, Side::White, 0).unwrap();
+        assert_eq!(base, 3000);
+
+        cmd_setoption(&mut state, "Move Overhead", "200").unwrap();
+        assert_eq!(state.move_overhead, 200);
+        let reduced = allocate_time(&params, Side::White, state.move_overhead).unwrap();
+        assert_eq!(reduced, base - 200);
+
+        assert_eq!(allocate_time(&GoParams::default(), Side::White, 200), None);
+    }
+
+    #[test]
+    fn test_allocation_never_exceeds_the_clock() {
+        for (time, overhead, expected) in [(30, 0, 30), (120, 50, 50), (80, 50, 30), (20, 50, 1)] {
+            let params = GoParams {
+                wtime: Some(time),
+                btime: Some(time),
+                ..Default::default()
+            };
+            assert_eq!(
+                allocate_time(&params, Side::White, overhead),
+                Some(expected),
+                "{time}ms left, {overhead}ms overhead"
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyse_mode_disables_pruning() {
+        let mut state = UciState::default();
+        cmd_setoption(&mut state, "UCI_AnalyseMode", "true").unwrap();
+        let config = state.search.lock().unwrap().get_config();
+        assert!(!config.enable_nmp);
+        assert!(!config.enable_lmr);
+        assert!(!config.enable_main_see_pruning);
+        assert!(config.full_pv);
+
+        cmd_setoption(&mut state, "UCI_AnalyseMode", "false").unwrap();
+        let config = state.search.lock().unwrap().get_config();
+        let default = SearchConfig::default();
+        assert_eq!(config.enable_nmp, default.enable_nmp);
+        assert_eq!(config.enable_lmr, default.enable_lmr);
+        assert_eq!(config.full_pv, default.full_pv);
+    }
+
+    #[test]
+    fn test_hash_buttons_need_a_hash_file() {
+        let mut state = UciState::default();
+        // An echoed empty HashFile leaves the buttons as no-ops
+        cmd_setoption(&mut state, "HashFile", "").unwrap();
+        assert!(state.hash_file.is_none());
+        cmd_setoption(&mut state, "SaveHash", "").unwrap();
+        cmd_setoption(&mut state, "LoadHash", "").unwrap();
+
+        let path = std::env::temp_dir().join(format!("eschec-hash-{}.bin", std::process::id()));
+        cmd_setoption(&mut state, "HashFile", path.to_str().unwrap()).unwrap();
+        cmd_setoption(&mut state, "SaveHash", "").unwrap();
+        assert!(path.exists());
+        cmd_setoption(&mut state, "LoadHash", "").unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stop_right_after_go_yields_bestmove() {
+        let mut state = UciState::new(None, TunableParams::default()).unwrap();
+        let params = GoParams {
+            infinite: true,
+            ..Default::default()
+        };
+        cmd_go(&mut state, params);
+        let result = stop_search(&mut state).expect("Search thread should have run");
+
+        let mut legal = MoveBuffer::new();
+        state.board.generate_legal_moves(&mut legal, false);
+        let mv = result.best_move.expect("Stopped search should have a move");
+        assert!(legal.contains(&mv), "{}", mv.uci());
+        assert_eq!(bestmove_line(&result), format!("bestmove {}", mv.uci()));
+    }
+
+    #[test]
+    fn test_game_over_at_root_yields_null_bestmove() {
+        let mut state = UciState::new(None, TunableParams::default()).unwrap();
+        // Fool's mate, White is checkmated
+        let fen = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+        cmd_position(&mut state, false, Some(fen.to_string()), Vec::new()).unwrap();
+        assert_eq!(
+            game_over_line(&state.board, &state.move_history).as_deref(),
+            Some("info string game over 0-1 (Checkmate)")
+        );
+
+        let params = GoParams {
+            depth: Some(4),
+            ..Default::default()
+        };
+        cmd_go(&mut state, params);
+        let result = stop_search(&mut state).expect("Search thread should have run");
+        assert_eq!(result.best_move, None);
+        assert_eq!(bestmove_line(&result), "bestmove 0000");
+
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        assert_eq!(
+            game_over_line(&stalemate, &[]).as_deref(),
+            Some("info string game over 1/2-1/2 (Stalemate)")
+        );
+        assert_eq!(game_over_line(&Board::new(), &[]), None);
+    }
+
+    #[test]
+    fn test_go_limits_combine_depth_and_time() {
+        let depth_only = GoParams {
+            depth: Some(6),
+            ..Default::default()
+        };
+        let limits = go_limits(&depth_only, None, 10);
+        assert_eq!(limits.max_depth, Some(6));
+        assert_eq!(limits.max_time, None);
+
+        // Both are kept, the search stops at whichever comes first
+        let both = GoParams {
+            wtime: Some(60_000),
+            btime: Some(60_000),
+            depth: Some(6),
+            ..Default::default()
+        };
+        let time = allocate_time(&both, Side::White, 0);
+        let limits = go_limits(&both, time, 10);
+        assert_eq!(limits.max_depth, Some(6));
+        assert_eq!(limits.max_time, time.map(Duration::from_millis));
+
+        // A clock without a depth isn't capped at the default depth
+        let time_only = GoParams {
+            wtime: Some(60_000),
+            btime: Some(60_000),
+            ..Default::default()
+        };
+        let limits = go_limits(&time_only, time, 10);
+        assert_eq!(limits.max_depth, None);
+        assert!(limits.max_time.is_some());
+
+        // Neither falls back to the default depth
+        let limits = go_limits(&GoParams::default(), None, 10);
+        assert_eq!(limits.max_depth, Some(10));
+        assert_eq!(limits.max_time, None);
+    }
+}
@@ -22,6 +22,8 @@ pub enum UciCommand {
     SetOption { name: String, value: String },
     /// "quit" cmd, to exit game
     Quit,
+    /// "d" cmd, non-standard debug cmd to display the current position
+    Display,
     /// unknown or unsupported cmd
     Unknown(String),
 }
@@ -49,6 +51,7 @@ pub fn parse_line(line: &str) -> UciCommand {
         "isready" => UciCommand::IsReady,
         "stop" => UciCommand::Stop,
         "quit" => UciCommand::Quit,
+        "d" => UciCommand::Display,
         "position" => parse_position(&parts[1..]),
         "go" => parse_go(&parts[1..]),
         "ucinewgame" => UciCommand::UciNewGame,
@@ -0,0 +1,87 @@
+//! Error type of the public board and move APIs
+//!
+//! Library users can match on `EschecError` instead of going through a `miette::Report`.
+//! With the `diagnostic` feature it is also a `miette::Diagnostic`, so it converts into a
+//! report with `?`. Without it, use `IntoDiagnostic::into_diagnostic`.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EschecError {
+    /// The FEN string couldn't be parsed
+    InvalidFen { fen: String, reason: String },
+    /// The move is well formed, but can't be played in the position
+    IllegalMove { mv: String, reason: String },
+    /// Malformed move or square notation
+    ParseError { input: String, reason: String },
+}
+
+impl fmt::Display for EschecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EschecError::InvalidFen { fen, reason } => write!(f, "Invalid FEN '{fen}': {reason}"),
+            EschecError::IllegalMove { mv, reason } => write!(f, "Illegal move '{mv}': {reason}"),
+            EschecError::ParseError { input, reason } => {
+                write!(f, "Could not parse '{input}': {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EschecError {}
+
+#[cfg(feature = "diagnostic")]
+impl miette::Diagnostic for EschecError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let code = match self {
+            EschecError::InvalidFen { .. } => "eschec::invalid_fen",
+            EschecError::IllegalMove { .. } => "eschec::illegal_move",
+            EschecError::ParseError { .. } => "eschec::parse_error",
+        };
+        Some(Box::new(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_error_variants() {
+        let err = Board::try_from_fen("8/8/8/8/8/8/8/7X w - - 0 1").unwrap_err();
+        assert!(
+            matches!(&err, EschecError::InvalidFen { fen, .. } if fen == "8/8/8/8/8/8/8/7X w - - 0 1"),
+            "{err}"
+        );
+        assert!(matches!(
+            Board::try_from_fen("8/8/8/8/8/8/8/8 x - - 0 1"),
+            Err(EschecError::InvalidFen { .. })
+        ));
+
+        let mut board = Board::new();
+        assert!(matches!(
+            Move::from_uci(&board, "e2"),
+            Err(EschecError::ParseError { .. })
+        ));
+        assert!(matches!(
+            Move::from_uci(&board, "e2e4x"),
+            Err(EschecError::ParseError { .. })
+        ));
+        let err = Move::from_uci(&board, "e3e4").unwrap_err();
+        assert!(
+            matches!(&err, EschecError::IllegalMove { mv, .. } if mv == "e3e4"),
+            "{err}"
+        );
+
+        let illegal = Move::new(0, 63, Move::QUIET);
+        assert!(matches!(
+            board.try_move(illegal),
+            Err(EschecError::IllegalMove { .. })
+        ));
+        assert_eq!(board, Board::new());
+
+        // Still usable from miette code
+        let report = Move::from_uci(&board, "e2").into_diagnostic().unwrap_err();
+        assert!(report.to_string().contains("'e2'"), "{report}");
+    }
+}
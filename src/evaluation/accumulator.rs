@@ -1,3 +1,5 @@
+use crate::evaluation::pst::IncrementalScore;
+use crate::evaluation::simd::sum_pst;
 use crate::prelude::*;
 use crate::tuning::params::{self, TunableParams};
 use crate::tuning::trace::{self, EvalTrace};
@@ -15,14 +17,40 @@ pub trait EvalAccumulator {
     /// Add a Piece-Square Table value
     fn add_pst(&mut self, piece: Piece, side: Side, sq: usize);
 
+    /// Add Piece-Square Table values for every square set in `bb`
+    fn add_pst_bb(&mut self, piece: Piece, side: Side, bb: BitBoard) {
+        for sq in bb.iter_bits() {
+            self.add_pst(piece, side, sq);
+        }
+    }
+
     /// Add a fixed score (non-tunable, eg. KingSafety Lookup Table or raw material)
     fn add_fixed_score(&mut self, score: Score, side: Side);
+
+    /// If set, the PST and material terms are taken from these incrementally updated
+    /// scores instead of scanning every piece
+    fn incremental_score(&self) -> Option<IncrementalScore> {
+        None
+    }
 }
 
 /// Score Accumulator - For Playing
 pub struct ScoreAccumulator<'a> {
     pub params: &'a TunableParams,
     pub score: Score,
+    /// Incrementally updated PST and material scores, valued with `params`
+    pub incremental: Option<IncrementalScore>,
+}
+
+impl<'a> ScoreAccumulator<'a> {
+    /// Starts from a zero score, scanning the board for PST and material terms
+    pub fn new(params: &'a TunableParams) -> Self {
+        Self {
+            params,
+            score: Score::default(),
+            incremental: None,
+        }
+    }
 }
 
 impl<'a> EvalAccumulator for ScoreAccumulator<'a> {
@@ -50,9 +78,7 @@ impl<'a> EvalAccumulator for ScoreAccumulator<'a> {
     fn add_pst(&mut self, piece: Piece, side: Side, sq: usize) {
         // Mirroring is handled here
         // White reads [sq], Black reads [sq ^ 56]
-        let actual_sq = if side == Side::White { sq } else { sq ^ 56 };
-
-        let idx = (piece.index() * 64) + actual_sq;
+        let idx = (piece.index() * 64) + side.relative_square(sq);
         let weight = self.params.psts[idx];
 
         if side == Side::White {
@@ -62,6 +88,19 @@ impl<'a> EvalAccumulator for ScoreAccumulator<'a> {
         }
     }
 
+    #[inline(always)]
+    fn add_pst_bb(&mut self, piece: Piece, side: Side, bb: BitBoard) {
+        // Bulk path, uses SIMD lanes when the `simd` feature is enabled
+        let table = &self.params.psts[piece.index() * 64..][..64];
+        let total = sum_pst(table, bb, side == Side::Black);
+
+        if side == Side::White {
+            self.score += total;
+        } else {
+            self.score -= total;
+        }
+    }
+
     #[inline(always)]
     fn add_fixed_score(&mut self, score: Score, side: Side) {
         if side == Side::White {
@@ -70,6 +109,11 @@ impl<'a> EvalAccumulator for ScoreAccumulator<'a> {
             self.score -= score;
         }
     }
+
+    #[inline(always)]
+    fn incremental_score(&self) -> Option<IncrementalScore> {
+        self.incremental
+    }
 }
 
 /// Trace Accumulator - For Tuning
@@ -111,6 +155,32 @@ impl<'a> EvalAccumulator for TraceAccumulator<'a> {
 
             params::TEMPO_BONUS => trace::TEMPO_BONUS,
 
+            params::THREAT_BY_PAWN => trace::THREAT_BY_PAWN,
+            params::THREAT_BY_MINOR => trace::THREAT_BY_MINOR,
+            params::THREAT_BY_ROOK => trace::THREAT_BY_ROOK,
+
+            params::PHALANX_BONUS => trace::PHALANX_BONUS,
+
+            i if (params::KING_TROPISM_START..params::KING_TROPISM_START + 4).contains(&i) => {
+                trace::KING_TROPISM_START + (i - params::KING_TROPISM_START)
+            }
+
+            params::ROOK_ON_SEVENTH => trace::ROOK_ON_SEVENTH,
+            params::QUEEN_ON_SEVENTH => trace::QUEEN_ON_SEVENTH,
+
+            params::TRAPPED_KNIGHT_PENALTY => trace::TRAPPED_KNIGHT,
+            params::TRAPPED_BISHOP_PENALTY => trace::TRAPPED_BISHOP,
+            params::TRAPPED_ROOK_PENALTY => trace::TRAPPED_ROOK,
+
+            params::KING_CENTRALIZATION => trace::KING_CENTRALIZATION,
+            params::KING_PASSER_PROXIMITY => trace::KING_PASSER_PROXIMITY,
+
+            params::PASSED_PAWN_BLOCKADE => trace::PASSED_PAWN_BLOCKADE,
+            params::PASSED_PAWN_KING_SUPPORT => trace::PASSED_PAWN_KING_SUPPORT,
+
+            params::CONNECTED_ROOKS_BONUS => trace::CONNECTED_ROOKS,
+            params::FILE_BATTERY_BONUS => trace::FILE_BATTERY,
+
             _ => return,
         };
         if side == Side::White {
@@ -145,7 +215,7 @@ impl<'a> EvalAccumulator for TraceAccumulator<'a> {
 
     #[inline(always)]
     fn add_pst(&mut self, piece: Piece, side: Side, sq: usize) {
-        let actual_sq = if side == Side::White { sq } else { sq ^ 56 };
+        let actual_sq = side.relative_square(sq);
 
         // In trace.rs, PSTs start at t::PST_START
         // In params.rs, PSTs start at PST_START
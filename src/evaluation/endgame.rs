@@ -0,0 +1,239 @@
+//! Specialized evaluation for basic endgames
+//!
+//! The general eval has no idea how to convert won endgames like KQvK or KRvK: every
+//! king move scores about the same, so the engine shuffles until the 50 move rule.
+//! When the material signature matches, the main eval is replaced by a score that
+//! drives the lone king towards the edge and brings the strong king closer.
+//!
+//! KPvK is scored from the exact verdicts of the `kpk` bitbase.
+//!
+//! Endgames that are drawish despite a material edge keep the main eval, but have its
+//! endgame half scaled down by [`scale_factor`].
+
+use crate::{
+    evaluation::kpk::{Wdl, kpk_probe},
+    prelude::*,
+};
+
+/// Base score for a won endgame, well below `MATE_THRESHOLD`
+const KNOWN_WIN: i32 = 10_000;
+
+/// Rough piece values to keep more material preferred in won endgames
+const PIECE_VALUES: [i32; 5] = [100, 320, 330, 500, 900];
+
+/// `scale_factor` for endgames with nothing special about them
+pub const SCALE_NORMAL: i32 = 128;
+
+/// Opposite colored bishops where the side ahead has every pawn on one color
+const SCALE_OCB_FORTRESS: i32 = 32;
+
+const LIGHT_SQUARES: BitBoard = BitBoard(0x55AA_55AA_55AA_55AA);
+
+/// Scale for the endgame part of the score, out of `SCALE_NORMAL`. Only drops below it
+/// for positions that are known to be hard to win, so it never inflates an advantage
+pub fn scale_factor(board: &Board) -> i32 {
+    if is_ocb_fortress(board) {
+        SCALE_OCB_FORTRESS
+    } else {
+        SCALE_NORMAL
+    }
+}
+
+/// Only kings, pawns and one bishop each on opposite colors, with the pawns of the side
+/// with more pawns all on squares of one color. Pawns fixed on one color are easy to
+/// blockade for the defending king and bishop, as only one of the bishops can attack
+/// the squares in front of them
+fn is_ocb_fortress(board: &Board) -> bool {
+    let pos = &board.positions;
+    let count = |side, piece| pos.get_piece_bb(side, piece).pop_count();
+    for side in [Side::White, Side::Black] {
+        if count(side, Piece::Bishop) != 1
+            || count(side, Piece::Knight) + count(side, Piece::Rook) + count(side, Piece::Queen)
+                != 0
+        {
+            return false;
+        }
+    }
+
+    let white_bishop = *pos.get_piece_bb(Side::White, Piece::Bishop);
+    let black_bishop = *pos.get_piece_bb(Side::Black, Piece::Bishop);
+    let opposite_colors =
+        (white_bishop & LIGHT_SQUARES).any() != (black_bishop & LIGHT_SQUARES).any();
+    if !opposite_colors {
+        return false;
+    }
+
+    let white_pawns = *pos.get_piece_bb(Side::White, Piece::Pawn);
+    let black_pawns = *pos.get_piece_bb(Side::Black, Piece::Pawn);
+    let pawns = match white_pawns.pop_count().cmp(&black_pawns.pop_count()) {
+        std::cmp::Ordering::Greater => white_pawns,
+        std::cmp::Ordering::Less => black_pawns,
+        std::cmp::Ordering::Equal => white_pawns | black_pawns,
+    };
+    (pawns & LIGHT_SQUARES).is_empty() || (pawns & !LIGHT_SQUARES).is_empty()
+}
+
+/// Returns a White relative score if `board` matches a known endgame
+pub fn probe(board: &Board) -> Option<Score> {
+    for strong in [Side::White, Side::Black] {
+        let weak = strong.flip();
+        if board.positions.get_side_bb(weak).pop_count() != 1 {
+            continue;
+        }
+
+        let count = |piece| board.positions.get_piece_bb(strong, piece).pop_count();
+        let pawns = count(Piece::Pawn);
+        let minors = count(Piece::Knight) + count(Piece::Bishop);
+        let majors = count(Piece::Rook) + count(Piece::Queen);
+
+        let score = if pawns == 0 && majors > 0 {
+            Some(eval_kx_vs_k(board, strong))
+        } else if pawns == 1 && minors == 0 && majors == 0 {
+            Some(eval_kp_vs_k(board, strong))
+        } else {
+            None
+        };
+
+        return score.map(|s| match strong {
+            Side::White => Score::splat(s),
+            Side::Black => Score::splat(-s),
+        });
+    }
+    None
+}
+
+fn king_sq(board: &Board, side: Side) -> usize {
+    board
+        .positions
+        .get_piece_bb(side, Piece::King)
+        .lsb()
+        .expect("Both sides must have a king") as usize
+}
+
+/// Manhattan distance from the centre, 0 on d4/d5/e4/e5 and 6 in the corners
+fn centre_distance(sq: usize) -> i32 {
+    let file = (sq % 8) as i32;
+    let rank = (sq / 8) as i32;
+    (3 - file).max(file - 4) + (3 - rank).max(rank - 4)
+}
+
+/// KXvK where X contains at least a rook or queen. Score is relative to `strong`
+fn eval_kx_vs_k(board: &Board, strong: Side) -> i32 {
+    let weak_king = king_sq(board, strong.flip());
+    let strong_king = king_sq(board, strong);
+
+    let material: i32 = [
+        Piece::Pawn,
+        Piece::Knight,
+        Piece::Bishop,
+        Piece::Rook,
+        Piece::Queen,
+    ]
+    .into_iter()
+    .map(|piece| {
+        board.positions.get_piece_bb(strong, piece).pop_count() as i32 * PIECE_VALUES[piece.index()]
+    })
+    .sum();
+
+    let push_to_edge = 20 * centre_distance(weak_king);
+    let push_close = 10 * (7 - Square::distance(strong_king, weak_king));
+
+    KNOWN_WIN + material + push_to_edge + push_close
+}
+
+/// KPvK, scored from the exact `kpk` bitbase verdict. Score is relative to `strong`
+fn eval_kp_vs_k(board: &Board, strong: Side) -> i32 {
+    // Orient everything so that the pawn belongs to White
+    let flip = if strong == Side::White { 0 } else { 56 };
+    let pawn = board
+        .positions
+        .get_piece_bb(strong, Piece::Pawn)
+        .lsb()
+        .expect("Pawn count was checked") as usize
+        ^ flip;
+    let strong_king = king_sq(board, strong) ^ flip;
+    let weak_king = king_sq(board, strong.flip()) ^ flip;
+    let stm = if board.stm == strong {
+        Side::White
+    } else {
+        Side::Black
+    };
+
+    match kpk_probe(strong_king, weak_king, pawn, stm) {
+        Wdl::Draw => 0,
+        // Prefer advancing the pawn once the win is certain
+        Wdl::Win | Wdl::Loss => {
+            KNOWN_WIN + PIECE_VALUES[Piece::Pawn.index()] + 10 * (pawn / 8) as i32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::{accumulator::ScoreAccumulator, evaluate};
+    use crate::tuning::params::TunableParams;
+
+    fn eval(fen: &str) -> i32 {
+        let board = Board::from_fen(fen);
+        evaluate(&board, &TunableParams::default()).taper(board.game_phase())
+    }
+
+    #[test]
+    fn test_kqk_pushes_king_to_edge() {
+        let edge = eval("7k/8/8/8/8/8/8/3QK3 w - - 0 1");
+        let centre = eval("8/8/8/4k3/8/8/8/3QK3 w - - 0 1");
+        assert!(edge > centre, "edge: {edge}, centre: {centre}");
+        assert!(centre > KNOWN_WIN);
+    }
+
+    #[test]
+    fn test_krk_pushes_king_to_edge() {
+        let edge = eval("7k/8/8/8/8/8/8/3RK3 w - - 0 1");
+        let centre = eval("8/8/8/4k3/8/8/8/3RK3 w - - 0 1");
+        assert!(edge > centre, "edge: {edge}, centre: {centre}");
+
+        // Same from Black's point of view
+        let edge = eval("3rk3/8/8/8/8/8/8/7K b - - 0 1");
+        let centre = eval("3rk3/8/8/8/4K3/8/8/8 b - - 0 1");
+        assert!(edge > centre, "edge: {edge}, centre: {centre}");
+    }
+
+    #[test]
+    fn test_ocb_fortress_scales_towards_draw() {
+        // White is two pawns up, all on light squares, against Black's light squared bishop
+        let fortress = "8/3k1b2/8/8/P1P1P3/8/4K3/2B5 w - - 0 1";
+        let board = Board::from_fen(fortress);
+        assert_eq!(scale_factor(&board), SCALE_OCB_FORTRESS);
+
+        let params = TunableParams::default();
+        let mut acc = ScoreAccumulator::new(&params);
+        crate::evaluation::eval_all(&board, &mut acc);
+        let unscaled = acc.score.taper(board.game_phase());
+        let scaled = eval(fortress);
+        assert!(unscaled > 0, "unscaled: {unscaled}");
+        assert!(
+            scaled.abs() < unscaled.abs(),
+            "scaled: {scaled}, unscaled: {unscaled}"
+        );
+
+        // Pawns on both colors, same colored bishops, or extra pieces aren't scaled
+        for fen in [
+            "8/3k1b2/8/8/P1P2P2/8/4K3/2B5 w - - 0 1",
+            "8/3k4/8/8/P1P1P3/8/4Kb2/2B5 w - - 0 1",
+            "8/3k1b2/8/8/P1P1P3/8/4K3/2B1N3 w - - 0 1",
+        ] {
+            assert_eq!(scale_factor(&Board::from_fen(fen)), SCALE_NORMAL, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_kpk_verdicts() {
+        // Lone king is outside the square of the pawn
+        assert!(eval("k7/8/8/8/8/8/6P1/6K1 w - - 0 1") > KNOWN_WIN);
+        // Rook pawn with the lone king in the corner
+        assert_eq!(eval("k7/8/8/8/8/8/P7/K7 w - - 0 1"), 0);
+        // Strong king on a key square
+        assert!(eval("4k3/8/8/3K4/8/4P3/8/8 b - - 0 1") < -KNOWN_WIN);
+    }
+}
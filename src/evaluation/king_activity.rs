@@ -0,0 +1,98 @@
+use crate::{
+    evaluation::{accumulator::EvalAccumulator, pawn_structure::passed_pawns},
+    prelude::*,
+    tuning::params::{KING_CENTRALIZATION, KING_PASSER_PROXIMITY},
+};
+
+/// How many king steps `sq` is away from the four center squares, 0 (d4, e4, d5, e5) to 3 (edge)
+const fn center_distance(sq: usize) -> i32 {
+    let file = sq % 8;
+    let rank = sq / 8;
+    let file_dist = if file < 4 { 3 - file } else { file - 4 };
+    let rank_dist = if rank < 4 { 3 - rank } else { rank - 4 };
+    (if file_dist > rank_dist {
+        file_dist
+    } else {
+        rank_dist
+    }) as i32
+}
+
+/// Rewards an active king: one close to the center, and close to the passed pawns of either
+/// side, to escort its own or stop the opponent's. The default weights are endgame only, so
+/// the term fades in as the phase approaches `ENDGAME_PHASE` and king safety rules the midgame
+pub(crate) fn eval_king_activity(board: &Board, acc: &mut impl EvalAccumulator) {
+    let passers = passed_pawns(board, Side::White) | passed_pawns(board, Side::Black);
+
+    for side in [Side::White, Side::Black] {
+        let Some(king_sq) = board.positions.get_piece_bb(side, Piece::King).lsb() else {
+            continue;
+        };
+        let king_sq = king_sq as usize;
+
+        let centralization = 3 - center_distance(king_sq);
+        if centralization > 0 {
+            acc.add_feature(KING_CENTRALIZATION, side, centralization);
+        }
+
+        let proximity: i32 = passers
+            .iter_bits()
+            .map(|sq| 7 - Square::distance(king_sq, sq))
+            .sum();
+        if proximity > 0 {
+            acc.add_feature(KING_PASSER_PROXIMITY, side, proximity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::evaluate;
+    use crate::tuning::params::TunableParams;
+
+    fn activity_eval(fen: &str) -> i32 {
+        let mut params = TunableParams::zeros();
+        let defaults = TunableParams::default();
+        params.king_centralization = defaults.king_centralization;
+        params.king_passer_proximity = defaults.king_passer_proximity;
+        let board = Board::from_fen(fen);
+        evaluate(&board, &params).taper(board.game_phase())
+    }
+
+    #[test]
+    fn test_center_distance() {
+        for sq in ["d4", "e4", "d5", "e5"] {
+            assert_eq!(center_distance(Square::from_str(sq).unwrap().index()), 0);
+        }
+        assert_eq!(center_distance(Square::from_str("c6").unwrap().index()), 1);
+        assert_eq!(center_distance(Square::from_str("b4").unwrap().index()), 2);
+        for sq in ["a1", "h1", "a8", "h8", "e1", "a5"] {
+            assert_eq!(center_distance(Square::from_str(sq).unwrap().index()), 3);
+        }
+    }
+
+    #[test]
+    fn test_centralized_king_beats_cornered_king() {
+        // Symmetric pawns, only the white king differs
+        let central = activity_eval("7k/p5p1/8/8/4K3/8/P5P1/8 w - - 0 1");
+        let cornered = activity_eval("7k/p5p1/8/8/8/8/P5P1/K7 w - - 0 1");
+        assert!(central > 0, "central: {central}");
+        assert!(
+            central > cornered,
+            "central: {central}, cornered: {cornered}"
+        );
+
+        // Being next to a passed pawn counts too
+        let escorting = activity_eval("7k/7p/8/8/8/2KP4/7P/8 w - - 0 1");
+        let away = activity_eval("7k/7p/8/8/8/3P4/7P/2K5 w - - 0 1");
+        assert!(escorting > away, "escorting: {escorting}, away: {away}");
+
+        // Same for Black, the eval is relative to the side to move
+        let central = activity_eval("8/p5p1/8/4k3/8/8/P5P1/7K b - - 0 1");
+        let cornered = activity_eval("k7/p5p1/8/8/8/8/P5P1/7K b - - 0 1");
+        assert!(
+            central > cornered,
+            "central: {central}, cornered: {cornered}"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    evaluation::accumulator::EvalAccumulator, prelude::*, tuning::params::KING_TROPISM_START,
+};
+
+/// Chebyshev distance between every pair of squares
+static SQUARE_DISTANCE: [[u8; 64]; 64] = {
+    let mut table = [[0u8; 64]; 64];
+    let mut a: usize = 0;
+    while a < 64 {
+        let mut b: usize = 0;
+        while b < 64 {
+            let file_dist = (a % 8).abs_diff(b % 8);
+            let rank_dist = (a / 8).abs_diff(b / 8);
+            table[a][b] = if file_dist > rank_dist {
+                file_dist
+            } else {
+                rank_dist
+            } as u8;
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+};
+
+const TROPISM_PIECES: [Piece; 4] = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen];
+
+/// Rewards pieces for standing close to the enemy king. Each piece adds `7 - distance`,
+/// so a piece next to the king counts 6 and one on the far side of the board counts 0.
+/// The default weights are mostly midgame, where attacking the king matters most
+pub(crate) fn eval_king_tropism(board: &Board, acc: &mut impl EvalAccumulator) {
+    for side in [Side::White, Side::Black] {
+        let Some(enemy_king) = board.positions.get_piece_bb(side.flip(), Piece::King).lsb() else {
+            continue;
+        };
+        let distances = &SQUARE_DISTANCE[enemy_king as usize];
+
+        for (i, piece) in TROPISM_PIECES.into_iter().enumerate() {
+            let closeness: i32 = board
+                .positions
+                .get_piece_bb(side, piece)
+                .iter_bits()
+                .map(|sq| 7 - distances[sq] as i32)
+                .sum();
+            if closeness > 0 {
+                acc.add_feature(KING_TROPISM_START + i, side, closeness);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::evaluate;
+    use crate::tuning::params::TunableParams;
+
+    #[test]
+    fn test_distance_table_matches_square_distance() {
+        for (a, row) in SQUARE_DISTANCE.iter().enumerate() {
+            for (b, &dist) in row.iter().enumerate() {
+                assert_eq!(dist as i32, Square::distance(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_queen_near_king_raises_eval() {
+        let far = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/Q5K1 w - - 0 1");
+        let near = Board::from_fen("6k1/5ppp/8/7Q/8/8/5PPP/6K1 w - - 0 1");
+
+        let mut params = TunableParams::zeros();
+        params.king_tropism = TunableParams::default().king_tropism;
+        let eval = |board: &Board| evaluate(board, &params).taper(board.game_phase());
+        assert!(
+            eval(&near) > eval(&far),
+            "near: {}, far: {}",
+            eval(&near),
+            eval(&far)
+        );
+
+        // Same from Black's side, the eval is relative to the side to move
+        let far = Board::from_fen("q5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1");
+        let near = Board::from_fen("6k1/5ppp/8/8/7q/8/5PPP/6K1 b - - 0 1");
+        assert!(
+            eval(&near) > eval(&far),
+            "near: {}, far: {}",
+            eval(&near),
+            eval(&far)
+        );
+    }
+}
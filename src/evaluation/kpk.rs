@@ -0,0 +1,262 @@
+//! King and Pawn vs King bitbase
+//!
+//! Exact win/draw verdicts for every KPvK position, computed once through retrograde
+//! analysis on first use. Positions are normalized so the pawn belongs to White and
+//! sits on files a-d, which leaves `2 * 64 * 64 * 4 * 6` positions, one bit each.
+
+use std::sync::LazyLock;
+
+use crate::prelude::*;
+
+const NUM_POSITIONS: usize = 2 * 64 * 64 * 4 * 6;
+
+static KPK_BITBASE: LazyLock<Box<[u64]>> = LazyLock::new(generate);
+
+/// Win/Draw/Loss from the side to move's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// Probes the bitbase for a position where White has a king and a pawn, Black has a lone king.
+/// Callers with the pawn on Black's side should flip the squares vertically (`sq ^ 56`) and
+/// swap `stm` before probing.
+pub fn kpk_probe(white_king: usize, black_king: usize, pawn: usize, stm: Side) -> Wdl {
+    debug_assert!(
+        (8..56).contains(&pawn),
+        "Pawn can't be on the first or last rank"
+    );
+
+    // Mirror onto files a-d
+    let mirror = if pawn % 8 > 3 { 7 } else { 0 };
+    let idx = index(stm, white_king ^ mirror, black_king ^ mirror, pawn ^ mirror);
+
+    let white_wins = KPK_BITBASE[idx / 64] & (1 << (idx % 64)) != 0;
+    match (white_wins, stm) {
+        (false, _) => Wdl::Draw,
+        (true, Side::White) => Wdl::Win,
+        (true, Side::Black) => Wdl::Loss,
+    }
+}
+
+/// Pawn must be on files a-d and ranks 2-7
+#[inline]
+fn index(stm: Side, white_king: usize, black_king: usize, pawn: usize) -> usize {
+    let file = pawn % 8;
+    let rank = pawn / 8;
+    stm.index() | (black_king << 1) | (white_king << 7) | (file << 13) | ((6 - rank) << 15)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Invalid,
+    Unknown,
+    Draw,
+    Win,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct KpkPosition {
+    stm: Side,
+    white_king: usize,
+    black_king: usize,
+    pawn: usize,
+    verdict: Verdict,
+}
+
+impl KpkPosition {
+    fn new(stm: Side, white_king: usize, black_king: usize, pawn: usize) -> Self {
+        let mut pos = Self {
+            stm,
+            white_king,
+            black_king,
+            pawn,
+            verdict: Verdict::Unknown,
+        };
+        pos.verdict = pos.initial_verdict();
+        pos
+    }
+
+    fn initial_verdict(&self) -> Verdict {
+        let pawn_attacks = MOVE_TABLES.get_pawn_attacks(self.pawn, Side::White);
+        let kings_distance = Square::distance(self.white_king, self.black_king);
+
+        if kings_distance <= 1
+            || self.white_king == self.pawn
+            || self.black_king == self.pawn
+            || (self.stm == Side::White && pawn_attacks.0 & (1 << self.black_king) != 0)
+        {
+            return Verdict::Invalid;
+        }
+
+        let promo_sq = self.pawn + 8;
+        if self.stm == Side::White {
+            // Pawn promotes and the new queen can't be taken
+            if self.pawn / 8 == 6
+                && self.white_king != promo_sq
+                && self.black_king != promo_sq
+                && (Square::distance(self.black_king, promo_sq) > 1
+                    || Square::distance(self.white_king, promo_sq) <= 1)
+            {
+                return Verdict::Win;
+            }
+        } else {
+            let stalemate = self
+                .black_king_moves()
+                .all(|sq| self.is_attacked_by_white(sq));
+            let takes_pawn = Square::distance(self.black_king, self.pawn) == 1
+                && Square::distance(self.white_king, self.pawn) > 1;
+            if stalemate || takes_pawn {
+                return Verdict::Draw;
+            }
+        }
+
+        Verdict::Unknown
+    }
+
+    fn is_attacked_by_white(&self, sq: usize) -> bool {
+        Square::distance(self.white_king, sq) <= 1
+            || MOVE_TABLES.get_pawn_attacks(self.pawn, Side::White).0 & (1 << sq) != 0
+    }
+
+    fn black_king_moves(&self) -> impl Iterator<Item = usize> + '_ {
+        MOVE_TABLES.king_moves[self.black_king].iter_bits()
+    }
+
+    /// Resolves an unknown position from its successors.
+    /// White needs one winning move, Black needs one drawing move
+    fn classify(&self, db: &[KpkPosition]) -> Verdict {
+        let (good, bad) = match self.stm {
+            Side::White => (Verdict::Win, Verdict::Draw),
+            Side::Black => (Verdict::Draw, Verdict::Win),
+        };
+
+        // Illegal successors (king next to king, into a pawn attack, etc) are skipped
+        let mut all_bad = true;
+        let mut visit = |verdict: Verdict| {
+            if verdict == good {
+                return true;
+            }
+            if verdict == Verdict::Unknown {
+                all_bad = false;
+            }
+            false
+        };
+
+        match self.stm {
+            Side::White => {
+                for sq in MOVE_TABLES.king_moves[self.white_king].iter_bits() {
+                    if visit(db[index(Side::Black, sq, self.black_king, self.pawn)].verdict) {
+                        return good;
+                    }
+                }
+
+                // Promotions from the 7th rank are covered by the initial verdict
+                let push = self.pawn + 8;
+                if self.pawn / 8 < 6 && push != self.white_king && push != self.black_king {
+                    if visit(db[index(Side::Black, self.white_king, self.black_king, push)].verdict)
+                    {
+                        return good;
+                    }
+
+                    let double_push = push + 8;
+                    if self.pawn / 8 == 1
+                        && double_push != self.white_king
+                        && double_push != self.black_king
+                        && visit(
+                            db[index(Side::Black, self.white_king, self.black_king, double_push)]
+                                .verdict,
+                        )
+                    {
+                        return good;
+                    }
+                }
+            }
+            Side::Black => {
+                for sq in self.black_king_moves() {
+                    if visit(db[index(Side::White, self.white_king, sq, self.pawn)].verdict) {
+                        return good;
+                    }
+                }
+            }
+        }
+
+        if all_bad { bad } else { Verdict::Unknown }
+    }
+}
+
+/// Retrograde analysis over all KPK positions. Whatever is still unknown once
+/// nothing changes can't be forced into a win, so it's a draw
+fn generate() -> Box<[u64]> {
+    let mut db = Vec::with_capacity(NUM_POSITIONS);
+    for idx in 0..NUM_POSITIONS {
+        let stm = if idx & 1 == 0 {
+            Side::White
+        } else {
+            Side::Black
+        };
+        let black_king = (idx >> 1) & 63;
+        let white_king = (idx >> 7) & 63;
+        let file = (idx >> 13) & 3;
+        let rank = 6 - (idx >> 15);
+        db.push(KpkPosition::new(
+            stm,
+            white_king,
+            black_king,
+            rank * 8 + file,
+        ));
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for idx in 0..NUM_POSITIONS {
+            if db[idx].verdict == Verdict::Unknown {
+                let verdict = db[idx].classify(&db);
+                if verdict != Verdict::Unknown {
+                    db[idx].verdict = verdict;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut bits = vec![0u64; NUM_POSITIONS / 64].into_boxed_slice();
+    for (idx, pos) in db.iter().enumerate() {
+        if pos.verdict == Verdict::Win {
+            bits[idx / 64] |= 1 << (idx % 64);
+        }
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn probe(white_king: &str, black_king: &str, pawn: &str, stm: Side) -> Wdl {
+        let sq = |s| Square::from_str(s).unwrap().index();
+        kpk_probe(sq(white_king), sq(black_king), sq(pawn), stm)
+    }
+
+    #[test]
+    fn test_kpk_textbook_positions() {
+        // King on a key square in front of the pawn
+        assert_eq!(probe("e6", "e8", "e5", Side::Black), Wdl::Loss);
+        assert_eq!(probe("d6", "d8", "e4", Side::White), Wdl::Win);
+        // Pawn on the 7th with Black stalemated
+        assert_eq!(probe("e6", "e8", "e7", Side::Black), Wdl::Draw);
+        // Lone king blockades the pawn
+        assert_eq!(probe("e2", "e5", "e3", Side::White), Wdl::Draw);
+        // Lone king outside the square of the pawn
+        assert_eq!(probe("a1", "a5", "h4", Side::White), Wdl::Win);
+        // Rook pawn with the lone king in the corner is always a draw
+        assert_eq!(probe("a6", "a8", "a5", Side::White), Wdl::Draw);
+        assert_eq!(probe("h5", "h8", "h6", Side::Black), Wdl::Draw);
+        // Black to move takes the undefended pawn
+        assert_eq!(probe("a1", "d5", "d4", Side::Black), Wdl::Draw);
+    }
+}
@@ -8,6 +8,33 @@ use crate::{
 };
 
 pub(crate) fn eval_material(board: &Board, acc: &mut impl EvalAccumulator) {
+    if let Some(incremental) = acc.incremental_score() {
+        acc.add_fixed_score(incremental.material, Side::White);
+    } else {
+        eval_piece_counts(board, acc);
+    }
+
+    // Bishop Pair
+    if board
+        .positions
+        .get_piece_bb(Side::White, Piece::Bishop)
+        .pop_count()
+        >= 2
+    {
+        acc.add_feature(BISHOP_PAIR_BONUS, Side::White, 1);
+    }
+
+    if board
+        .positions
+        .get_piece_bb(Side::Black, Piece::Bishop)
+        .pop_count()
+        >= 2
+    {
+        acc.add_feature(BISHOP_PAIR_BONUS, Side::Black, 1);
+    }
+}
+
+fn eval_piece_counts(board: &Board, acc: &mut impl EvalAccumulator) {
     let pieces = [
         (Piece::Pawn, MATERIAL_PAWN),
         (Piece::Knight, MATERIAL_KNIGHT),
@@ -27,23 +54,34 @@ pub(crate) fn eval_material(board: &Board, acc: &mut impl EvalAccumulator) {
             acc.add_feature(param_idx, Side::Black, black_count as i32);
         }
     }
+}
 
-    // Bishop Pair
-    if board
-        .positions
-        .get_piece_bb(Side::White, Piece::Bishop)
-        .pop_count()
-        >= 2
-    {
-        acc.add_feature(BISHOP_PAIR_BONUS, Side::White, 1);
+/// Baseline eval: the tapered material balance from the side to move's point of view,
+/// using the fixed `Piece::score` values. Ignores the params and every positional term,
+/// for testing search on its own, see `SearchConfig::material_only_eval`
+pub fn evaluate_material(board: &Board) -> i32 {
+    let score = board.material_balance().taper(board.game_phase());
+    if board.stm == Side::White {
+        score
+    } else {
+        -score
     }
+}
 
-    if board
-        .positions
-        .get_piece_bb(Side::Black, Piece::Bishop)
-        .pop_count()
-        >= 2
-    {
-        acc.add_feature(BISHOP_PAIR_BONUS, Side::Black, 1);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_material() {
+        assert_eq!(evaluate_material(&Board::new()), 0);
+        assert_eq!(evaluate_material(&Board::from_fen(KIWIPETE)), 0);
+
+        // White is a queen up, worse for Black to move
+        let fen = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let white_up = evaluate_material(&Board::from_fen(fen));
+        assert!(white_up > 900, "{white_up}");
+        let fen = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1";
+        assert_eq!(evaluate_material(&Board::from_fen(fen)), -white_up);
     }
 }
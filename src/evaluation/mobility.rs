@@ -1,4 +1,20 @@
-use crate::{evaluation::accumulator::EvalAccumulator, prelude::*};
+use crate::{
+    evaluation::accumulator::EvalAccumulator,
+    prelude::*,
+    tuning::params::{BISHOP_MAX, KNIGHT_MAX, QUEEN_MAX, ROOK_MAX},
+};
+
+/// Largest mobility count that has its own entry in the piece's mobility table.
+/// Also the most squares the piece can ever move to, so clamping to it is only a safeguard
+pub(crate) const fn max_mobility(piece: Piece) -> usize {
+    match piece {
+        Piece::Knight => KNIGHT_MAX - 1,
+        Piece::Bishop => BISHOP_MAX - 1,
+        Piece::Rook => ROOK_MAX - 1,
+        Piece::Queen => QUEEN_MAX - 1,
+        Piece::Pawn | Piece::King => 0,
+    }
+}
 
 pub(crate) fn eval_mobility(board: &Board, acc: &mut impl EvalAccumulator) {
     let mut buffer = MoveBuffer::new();
@@ -16,7 +32,7 @@ pub(crate) fn eval_mobility(board: &Board, acc: &mut impl EvalAccumulator) {
     for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
         let bb = board.positions.get_piece_bb(Side::White, piece);
         for sq in bb.iter_bits() {
-            let count = move_counts[sq];
+            let count = (move_counts[sq] as usize).min(max_mobility(piece));
             acc.add_mobility(piece, Side::White, count as i32);
         }
     }
@@ -35,8 +51,58 @@ pub(crate) fn eval_mobility(board: &Board, acc: &mut impl EvalAccumulator) {
     for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
         let bb = board.positions.get_piece_bb(Side::Black, piece);
         for sq in bb.iter_bits() {
-            let count = move_counts[sq];
+            let count = (move_counts[sq] as usize).min(max_mobility(piece));
             acc.add_mobility(piece, Side::Black, count as i32);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tuning::{params::TunableParams, trace::EvalTrace};
+
+    #[test]
+    fn test_mobility_fits_tables_in_random_positions() {
+        let params = TunableParams::default();
+        let mut rng = Prng::init(0x5EED_u64);
+        let mut max_seen = [0usize; NUM_PIECES];
+
+        for start in [START_FEN, KIWIPETE, "4k3/8/8/3Q4/8/8/8/4K3 w - - 0 1"] {
+            for _ in 0..20 {
+                let mut board = Board::from_fen(start);
+                for _ in 0..80 {
+                    let mut pseudo = MoveBuffer::new();
+                    let mut counts = [0usize; NUM_SQUARES];
+                    for side in [Side::White, Side::Black] {
+                        board.generate_pseudo_legal_moves(&mut pseudo, Some(side));
+                    }
+                    for m in &pseudo {
+                        counts[m.from_sq().index()] += 1;
+                    }
+                    for (piece, side) in Piece::all() {
+                        for sq in board.positions.get_piece_bb(side, piece).iter_bits() {
+                            max_seen[piece.index()] = max_seen[piece.index()].max(counts[sq]);
+                        }
+                    }
+                    // Neither must index out of bounds
+                    board.evaluate_position(&params);
+                    crate::evaluation::trace(&board, &mut EvalTrace::default());
+
+                    let mut legal = MoveBuffer::new();
+                    board.generate_legal_moves(&mut legal, false);
+                    if legal.is_empty() {
+                        break;
+                    }
+                    let mv = legal.as_slice()[(rng.rand() % legal.len() as u64) as usize];
+                    board.make_move(mv).unwrap();
+                }
+            }
+        }
+
+        for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+            let seen = max_seen[piece.index()];
+            assert!(seen <= max_mobility(piece), "{piece:?}: {seen}");
+        }
+    }
+}
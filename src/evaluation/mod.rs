@@ -1,3 +1,12 @@
+//! Static evaluation
+//!
+//! # Sign conventions
+//!
+//! Every eval term and `Board::material_balance` is White relative: positive means White
+//! is better, terms add their bonus for the side that owns the feature and the
+//! accumulators subtract it for Black. Only the final `evaluate*` functions flip the sign
+//! so the result is relative to the side to move, which is what negamax search expects.
+
 use crate::{
     evaluation::accumulator::{ScoreAccumulator, TraceAccumulator},
     prelude::*,
@@ -10,36 +19,106 @@ use crate::{
 pub mod score;
 
 pub mod accumulator;
+pub mod endgame;
+pub mod king_activity;
 pub mod king_safety;
+pub mod king_tropism;
+pub mod kpk;
 pub mod material;
 pub mod mobility;
+pub mod pawn_hash;
 pub mod pawn_structure;
 pub mod position;
+pub mod pst;
+pub(crate) mod simd;
+pub mod threats;
+pub mod trapped;
 
+use king_activity::eval_king_activity;
 use king_safety::eval_king_safety;
+use king_tropism::eval_king_tropism;
 use material::eval_material;
 use mobility::eval_mobility;
-use pawn_structure::eval_pawn_structure;
+use pawn_hash::PawnHashTable;
+use pawn_structure::{eval_backward_pawns, eval_passed_pawn_support, eval_pawn_structure};
 use position::eval_position;
+use pst::IncrementalScore;
+use threats::eval_threats;
+use trapped::eval_trapped_pieces;
 
 // Generic driver function
 fn eval_all(board: &Board, acc: &mut impl accumulator::EvalAccumulator) {
+    eval_pawn_structure(board, acc);
+    eval_pieces(board, acc);
+}
+
+/// Everything but the pawn-only terms that the pawn hash table caches
+fn eval_pieces(board: &Board, acc: &mut impl accumulator::EvalAccumulator) {
     eval_material(board, acc);
     eval_position(board, acc);
-    eval_pawn_structure(board, acc);
+    eval_backward_pawns(board, acc);
+    eval_passed_pawn_support(board, acc);
     eval_mobility(board, acc);
     eval_king_safety(board, acc);
+    eval_king_activity(board, acc);
+    eval_king_tropism(board, acc);
+    eval_threats(board, acc);
+    eval_trapped_pieces(board, acc);
 
     acc.add_feature(TEMPO_BONUS, board.stm, 1);
 }
 
 pub fn evaluate(board: &Board, params: &TunableParams) -> Score {
+    evaluate_with(board, params, None, None)
+}
+
+/// Same as `evaluate`, but takes the PST and material terms from `incremental` instead of
+/// scanning every piece. `incremental` must have been valued with `params`
+pub fn evaluate_incremental(
+    board: &Board,
+    params: &TunableParams,
+    incremental: IncrementalScore,
+) -> Score {
+    evaluate_with(board, params, Some(incremental), None)
+}
+
+/// Same as `evaluate`/`evaluate_incremental`, but the pawn structure score comes from `pawn_table`
+pub fn evaluate_cached(
+    board: &Board,
+    params: &TunableParams,
+    incremental: Option<IncrementalScore>,
+    pawn_table: &mut PawnHashTable,
+) -> Score {
+    evaluate_with(board, params, incremental, Some(pawn_table))
+}
+
+fn evaluate_with(
+    board: &Board,
+    params: &TunableParams,
+    incremental: Option<IncrementalScore>,
+    pawn_table: Option<&mut PawnHashTable>,
+) -> Score {
+    if let Some(score) = endgame::probe(board) {
+        return if board.stm == Side::White {
+            score
+        } else {
+            -score
+        };
+    }
+
     let mut acc = ScoreAccumulator {
-        params,
-        score: Score::default(),
+        incremental,
+        ..ScoreAccumulator::new(params)
     };
 
-    eval_all(board, &mut acc);
+    match pawn_table {
+        Some(table) => {
+            acc.score += table.probe(board, params);
+            eval_pieces(board, &mut acc);
+        }
+        None => eval_all(board, &mut acc),
+    }
+    acc.score.eg = acc.score.eg * endgame::scale_factor(board) / endgame::SCALE_NORMAL;
 
     if board.stm == Side::White {
         acc.score
@@ -116,4 +195,27 @@ mod tests {
         assert_eq!(score_eval.mg, trace_score_mg, "MG Scores do not match!");
         assert_eq!(score_eval.eg, trace_score_eg, "EG Scores do not match!");
     }
+
+    #[test]
+    fn test_tempo_shrinks_towards_endgame() {
+        let params = TunableParams::default();
+        assert!(params.tempo_bonus.mg > params.tempo_bonus.eg);
+        let no_tempo = TunableParams {
+            tempo_bonus: Score::default(),
+            ..params.clone()
+        };
+
+        let tempo_at = |fen: &str| {
+            let board = Board::from_fen(fen);
+            let diff = evaluate(&board, &params) - evaluate(&board, &no_tempo);
+            assert_eq!(diff, params.tempo_bonus, "{fen}");
+            diff.taper(board.game_phase())
+        };
+
+        let opening = tempo_at(START_FEN);
+        let middlegame = tempo_at(KIWIPETE);
+        let endgame = tempo_at("4k3/pp3ppp/8/8/8/8/PP3PPP/R3K3 w - - 0 1");
+        assert!(opening >= middlegame, "{opening} vs {middlegame}");
+        assert!(middlegame > endgame, "{middlegame} vs {endgame}");
+    }
 }
@@ -0,0 +1,110 @@
+//! Pawn hash table
+//!
+//! Pawn structure only changes on pawn moves, so its score is cached by a Zobrist key
+//! of the pawns alone. Entries hold the White relative `eval_pawn_structure` score.
+
+use crate::{
+    evaluation::{accumulator::ScoreAccumulator, pawn_structure::eval_pawn_structure},
+    prelude::*,
+    tuning::params::TunableParams,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PawnEntry {
+    key: u64,
+    score: Score,
+}
+
+/// Cached pawn structure scores. Entries are only valid for the `TunableParams`
+/// they were computed with, so the table must be cleared when those change
+#[derive(Debug, Clone)]
+pub struct PawnHashTable {
+    entries: Vec<PawnEntry>,
+    hits: u64,
+    probes: u64,
+}
+
+impl PawnHashTable {
+    pub fn new(size_kb: usize) -> Self {
+        let num_entries = (size_kb * 1024 / std::mem::size_of::<PawnEntry>())
+            .max(1)
+            .next_power_of_two();
+        Self {
+            entries: vec![PawnEntry::default(); num_entries],
+            hits: 0,
+            probes: 0,
+        }
+    }
+
+    /// Returns the White relative pawn structure score, computing and storing it on a miss.
+    // NOTE: An empty slot has key 0, which is also the key of a board without pawns.
+    // That's still correct since the pawn structure score of no pawns is 0.
+    pub fn probe(&mut self, board: &Board, params: &TunableParams) -> Score {
+        let key = board.pawn_hash;
+        let idx = key as usize & (self.entries.len() - 1);
+        self.probes += 1;
+
+        let entry = &mut self.entries[idx];
+        if entry.key == key {
+            self.hits += 1;
+            return entry.score;
+        }
+
+        let mut acc = ScoreAccumulator::new(params);
+        eval_pawn_structure(board, &mut acc);
+        *entry = PawnEntry {
+            key,
+            score: acc.score,
+        };
+        acc.score
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(PawnEntry::default());
+        self.hits = 0;
+        self.probes = 0;
+    }
+
+    /// Returns (hits, probes) since the last clear
+    pub fn hit_rate(&self) -> (u64, u64) {
+        (self.hits, self.probes)
+    }
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::{evaluate, evaluate_cached};
+
+    #[test]
+    fn test_cached_pawn_eval_matches_fresh() {
+        let params = TunableParams::default();
+        let mut table = PawnHashTable::new(64);
+
+        // Same pawns, pieces moved around (including one blocking a pawn)
+        let fens = [
+            "r1bqkbnr/pp1ppppp/2n5/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            "r1bqkb1r/pp1ppppp/2n2n2/2p5/4P3/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 4 4",
+            "r2qkb1r/pp1ppppp/2n2n2/2p5/4P1b1/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 5 4",
+            "r2qk2r/pp1ppppp/2n2n2/2p5/1b2P1b1/2NB1N2/PPPP1PPP/R1BQK2R w KQkq - 7 5",
+            "r2qk2r/pp1ppppp/2n5/2p1n3/1b2P1b1/2NB1N2/PPPP1PPP/R1BQK2R w KQkq - 8 6",
+        ];
+
+        for fen in fens {
+            let board = Board::from_fen(fen);
+            let fresh = evaluate(&board, &params);
+            let cached = evaluate_cached(&board, &params, None, &mut table);
+            assert_eq!(fresh, cached, "{fen}");
+        }
+
+        let (hits, probes) = table.hit_rate();
+        assert_eq!(probes, fens.len() as u64);
+        assert_eq!(hits, fens.len() as u64 - 1);
+    }
+}
@@ -1,22 +1,87 @@
 use crate::{
+    board::bitboard_ops::{east, forward, pawn_attacks_bb, west},
     evaluation::accumulator::EvalAccumulator,
     prelude::*,
     tuning::params::{
-        BACKWARD_PENALTY, CONNECTED_BONUS, DOUBLED_PENALTY, ISOLATED_PENALTY, PASSED_PAWN_START,
+        BACKWARD_PENALTY, CONNECTED_BONUS, DOUBLED_PENALTY, ISOLATED_PENALTY, PASSED_PAWN_BLOCKADE,
+        PASSED_PAWN_KING_SUPPORT, PASSED_PAWN_START, PHALANX_BONUS,
     },
 };
 
+/// Pawn structure terms that only depend on pawn placement, so they can be cached by pawn hash
 pub(crate) fn eval_pawn_structure(board: &Board, acc: &mut impl EvalAccumulator) {
     for side in [Side::White, Side::Black] {
         eval_side_pawns(board, side, acc);
     }
 }
 
+/// Backward pawns look at every piece blocking the pawn, not just pawns,
+/// so they're kept out of `eval_pawn_structure` and the pawn hash
+pub(crate) fn eval_backward_pawns(board: &Board, acc: &mut impl EvalAccumulator) {
+    let occupied = board.positions.get_occupied_bb();
+    for side in [Side::White, Side::Black] {
+        let side_idx = side.index();
+        let friendly_pawns = board.positions.get_piece_bb(side, Piece::Pawn);
+
+        for sq_idx in friendly_pawns.iter_bits() {
+            // Backward Pawn: Blocked in front AND cannot be supported by friendly pawns
+            let is_blocked_in_front =
+                (occupied & PAWN_TABLES.pawn_front_square_masks[side_idx][sq_idx]).any();
+            let has_backward_support =
+                (friendly_pawns & &PAWN_TABLES.pawn_backward_support_masks[side_idx][sq_idx]).any();
+
+            if is_blocked_in_front && !has_backward_support {
+                acc.add_feature(BACKWARD_PENALTY, side, 1);
+            }
+        }
+    }
+}
+
+/// Passed pawns with an enemy piece on the square in front, and passed pawns whose king
+/// stands next to that square, ready to escort them. Both depend on pieces other than pawns,
+/// so they're kept out of `eval_pawn_structure` and the pawn hash
+pub(crate) fn eval_passed_pawn_support(board: &Board, acc: &mut impl EvalAccumulator) {
+    for side in [Side::White, Side::Black] {
+        let passers = passed_pawns(board, side);
+        if passers.is_empty() {
+            continue;
+        }
+        let enemies = *board.positions.get_side_bb(side.flip());
+        let stop_squares = forward(passers, side);
+
+        let blockaded = (stop_squares & enemies).pop_count() as i32;
+        if blockaded > 0 {
+            acc.add_feature(PASSED_PAWN_BLOCKADE, side, blockaded);
+        }
+
+        let Some(king_sq) = board.positions.get_piece_bb(side, Piece::King).lsb() else {
+            continue;
+        };
+        let supported =
+            (stop_squares & MOVE_TABLES.king_moves[king_sq as usize]).pop_count() as i32;
+        if supported > 0 {
+            acc.add_feature(PASSED_PAWN_KING_SUPPORT, side, supported);
+        }
+    }
+}
+
+/// Pawns of `side` with no enemy pawn in front of them on their own or an adjacent file
+pub(crate) fn passed_pawns(board: &Board, side: Side) -> BitBoard {
+    let pawns = *board.positions.get_piece_bb(side, Piece::Pawn);
+    let enemy_pawns = *board.positions.get_piece_bb(side.flip(), Piece::Pawn);
+    let mut passers = BitBoard(0);
+    for sq in pawns.iter_bits() {
+        if (enemy_pawns & PAWN_TABLES.passed_pawn_blocking_masks[side.index()][sq]).is_empty() {
+            passers.set(sq);
+        }
+    }
+    passers
+}
+
 fn eval_side_pawns(board: &Board, side: Side, acc: &mut impl EvalAccumulator) {
     let side_idx = side.index();
     let friendly_pawns = board.positions.get_piece_bb(side, Piece::Pawn);
     let opponent_pawns = board.positions.get_piece_bb(side.flip(), Piece::Pawn);
-    let occupied = board.positions.get_occupied_bb();
 
     for sq_idx in friendly_pawns.iter_bits() {
         let file = sq_idx % 8;
@@ -44,22 +109,100 @@ fn eval_side_pawns(board: &Board, side: Side, acc: &mut impl EvalAccumulator) {
             // e.g. Rank 7 passed pawn uses index PASSED_PAWN_START + 7
             acc.add_feature(PASSED_PAWN_START + relative_rank, side, 1);
         }
+    }
 
-        // Backward Pawn: Blocked in front AND cannot be supported by friendly pawns
-        let is_blocked_in_front =
-            (occupied & PAWN_TABLES.pawn_front_square_masks[side_idx][sq_idx]).any();
-        let has_backward_support =
-            (friendly_pawns & &PAWN_TABLES.pawn_backward_support_masks[side_idx][sq_idx]).any();
+    eval_pawn_chains(*friendly_pawns, side, acc);
+}
 
-        if is_blocked_in_front && !has_backward_support {
-            acc.add_feature(BACKWARD_PENALTY, side, 1);
-        }
+/// Connected pawns are defended by a friendly pawn, phalanx pawns have a friendly pawn
+/// beside them on the same rank. A pawn can be both and is counted once for each
+fn eval_pawn_chains(pawns: BitBoard, side: Side, acc: &mut impl EvalAccumulator) {
+    let defended = pawn_attacks_bb(pawns, side);
+    let connected = (pawns & defended).pop_count() as i32;
+    if connected > 0 {
+        acc.add_feature(CONNECTED_BONUS, side, connected);
+    }
 
-        // Connected Bonus: Friendly Pawns on adjacent files/ranks
-        let connected_neighbors = friendly_pawns & &PAWN_TABLES.connected_pawn_masks[sq_idx];
-        let count = connected_neighbors.pop_count() as i32;
-        if count > 0 {
-            acc.add_feature(CONNECTED_BONUS, side, count);
-        }
+    let beside = east(pawns) | west(pawns);
+    let phalanx = (pawns & beside).pop_count() as i32;
+    if phalanx > 0 {
+        acc.add_feature(PHALANX_BONUS, side, phalanx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::accumulator::ScoreAccumulator;
+    use crate::tuning::params::TunableParams;
+
+    fn pawn_score(board: &Board, params: &TunableParams) -> Score {
+        let mut acc = ScoreAccumulator::new(params);
+        eval_pawn_structure(board, &mut acc);
+        acc.score
+    }
+
+    #[test]
+    fn test_connected_duo_beats_isolated_pawns() {
+        let params = TunableParams::default();
+        let phase = Phase(128);
+        let isolated = pawn_score(&Board::from_fen("4k3/8/8/8/P6P/8/8/4K3 w - - 0 1"), &params);
+        let connected = pawn_score(
+            &Board::from_fen("4k3/8/8/8/3P4/4P3/8/4K3 w - - 0 1"),
+            &params,
+        );
+        let phalanx = pawn_score(
+            &Board::from_fen("4k3/8/8/8/3PP3/8/8/4K3 w - - 0 1"),
+            &params,
+        );
+
+        assert!(connected.taper(phase) > isolated.taper(phase));
+        assert!(phalanx.taper(phase) > isolated.taper(phase));
+    }
+
+    #[test]
+    fn test_pawn_chain_counts() {
+        let mut params = TunableParams::zeros();
+        params.connected_bonus = Score::new(1, 0);
+        params.phalanx_bonus = Score::new(0, 1);
+
+        // c3 defends d4, d4 and e4 stand side by side
+        let white = Board::from_fen("4k3/8/8/8/3PP3/2P5/8/4K3 w - - 0 1");
+        assert_eq!(pawn_score(&white, &params), Score::new(1, 2));
+
+        // Pawns on the edge files don't wrap around
+        let edges = Board::from_fen("4k3/8/8/7P/P7/8/8/4K3 w - - 0 1");
+        assert_eq!(pawn_score(&edges, &params), Score::default());
+
+        let black = Board::from_fen("4k3/2p5/3pp3/8/8/8/8/4K3 b - - 0 1");
+        assert_eq!(pawn_score(&black, &params), Score::new(-1, -2));
+    }
+
+    #[test]
+    fn test_king_supported_passer_beats_unsupported() {
+        let mut params = TunableParams::zeros();
+        let defaults = TunableParams::default();
+        params.passed_pawn_blockade = defaults.passed_pawn_blockade;
+        params.passed_pawn_king_support = defaults.passed_pawn_king_support;
+        let score = |fen: &str| {
+            let board = Board::from_fen(fen);
+            let mut acc = ScoreAccumulator::new(&params);
+            eval_passed_pawn_support(&board, &mut acc);
+            acc.score.taper(board.game_phase())
+        };
+
+        // The white king next to d5, the square in front of the passer
+        let supported = score("8/7p/1k6/8/2KP4/8/7P/8 w - - 0 1");
+        let unsupported = score("8/7p/1k6/8/3P4/8/7P/K7 w - - 0 1");
+        assert!(supported > unsupported, "{supported} vs {unsupported}");
+        assert!(supported > 0);
+
+        // A knight on d5 blockades the passer
+        let blockaded = score("8/7p/1k6/3n4/3P4/8/7P/K7 w - - 0 1");
+        assert!(blockaded < unsupported, "{blockaded} vs {unsupported}");
+
+        // Same for Black
+        let supported = score("8/7p/8/2kp4/8/1K6/7P/8 b - - 0 1");
+        assert!(supported < 0, "{supported}");
     }
 }
@@ -1,13 +1,21 @@
 use crate::{
+    board::bitboard_ops::{adjacent_files, file_fill, pawn_attacks_bb},
     evaluation::accumulator::EvalAccumulator,
     prelude::*,
-    tuning::params::{KNIGHT_OUTPOST_BONUS, ROOK_OPEN_FILE_BONUS, ROOK_SEMI_FILE_BONUS},
+    tuning::params::{
+        CONNECTED_ROOKS_BONUS, FILE_BATTERY_BONUS, KNIGHT_OUTPOST_BONUS, QUEEN_ON_SEVENTH,
+        ROOK_ON_SEVENTH, ROOK_OPEN_FILE_BONUS, ROOK_SEMI_FILE_BONUS,
+    },
 };
 
 const WHITE_OUTPOST_MASK: BitBoard = BitBoard(0x00007E7E7E000000);
 const BLACK_OUTPOST_MASK: BitBoard = BitBoard(0x0000007E7E7E0000);
 
 pub(crate) fn eval_position(board: &Board, acc: &mut impl EvalAccumulator) {
+    if let Some(incremental) = acc.incremental_score() {
+        // Already White relative
+        acc.add_fixed_score(incremental.pst, Side::White);
+    }
     for side in [Side::White, Side::Black] {
         eval_side_position(board, side, acc);
     }
@@ -21,10 +29,10 @@ fn eval_side_position(board: &Board, side: Side, acc: &mut impl EvalAccumulator)
     let opp_idx = opponent.index();
 
     // Piece Square Tables
-    for piece in Piece::all_pieces() {
-        let bb = board.positions.get_piece_bb(side, piece);
-        for sq in bb.iter_bits() {
-            acc.add_pst(piece, side, sq);
+    if acc.incremental_score().is_none() {
+        for piece in Piece::all_pieces() {
+            let bb = board.positions.get_piece_bb(side, piece);
+            acc.add_pst_bb(piece, side, *bb);
         }
     }
 
@@ -67,4 +75,153 @@ fn eval_side_position(board: &Board, side: Side, acc: &mut impl EvalAccumulator)
             }
         }
     }
+
+    // Connected Rooks, defending each other along a rank or file with nothing in between
+    let occupied = board.positions.get_occupied_bb();
+    let mut connected = 0;
+    for (i, from) in rooks.iter_bits().enumerate() {
+        for to in rooks.iter_bits().skip(i + 1) {
+            let aligned = from % 8 == to % 8 || from / 8 == to / 8;
+            if aligned && (MOVE_TABLES.get_ray_between(from, to) & occupied).is_empty() {
+                connected += 1;
+            }
+        }
+    }
+    if connected > 0 {
+        acc.add_feature(CONNECTED_ROOKS_BONUS, side, connected);
+    }
+
+    // Rooks and queens on the 7th rank attack pawns that haven't moved yet, and are worth
+    // twice as much while they also cut the enemy king off on its back rank
+    let (seventh, back_rank) = match side {
+        Side::White => (RANK_MASKS[6], RANK_MASKS[7]),
+        Side::Black => (RANK_MASKS[1], RANK_MASKS[0]),
+    };
+    let enemy_king = board.positions.get_piece_bb(opponent, Piece::King);
+    let scale = if (*enemy_king & BitBoard(back_rank)).any() {
+        2
+    } else {
+        1
+    };
+    for (piece, feature) in [
+        (Piece::Rook, ROOK_ON_SEVENTH),
+        (Piece::Queen, QUEEN_ON_SEVENTH),
+    ] {
+        let on_seventh = *board.positions.get_piece_bb(side, piece) & BitBoard(seventh);
+        if on_seventh.any() {
+            acc.add_feature(feature, side, on_seventh.pop_count() as i32 * scale);
+        }
+    }
+
+    // Batteries, a rook stacked on a rook or queen on a file without friendly pawns, aimed
+    // at the enemy king (on or next to the file) or at an enemy pawn no pawn defends
+    let queens = board.positions.get_piece_bb(side, Piece::Queen);
+    let targets = adjacent_files(*enemy_king)
+        | file_fill(*enemy_king)
+        | (*opponent_pawns & !pawn_attacks_bb(*opponent_pawns, opponent));
+    let batteries = (0..8)
+        .filter(|&file| {
+            let file_mask = BitBoard(FILE_MASKS[file]);
+            (file_mask & *friendly_pawns).is_empty()
+                && (file_mask & targets).any()
+                && has_battery(*rooks & file_mask, (*rooks | *queens) & file_mask, occupied)
+        })
+        .count() as i32;
+    if batteries > 0 {
+        acc.add_feature(FILE_BATTERY_BONUS, side, batteries);
+    }
+}
+
+/// Whether a rook in `rooks` and another piece in `heavy`, all on one file, are next to
+/// each other with nothing in between
+fn has_battery(rooks: BitBoard, heavy: BitBoard, occupied: BitBoard) -> bool {
+    rooks.iter_bits().any(|rook| {
+        heavy.iter_bits().any(|other| {
+            other != rook && (MOVE_TABLES.get_ray_between(rook, other) & occupied).is_empty()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::evaluate;
+    use crate::tuning::params::TunableParams;
+
+    #[test]
+    fn test_doubled_rooks_on_seventh() {
+        let mut params = TunableParams::zeros();
+        params.rook_on_seventh = TunableParams::default().rook_on_seventh;
+        let eval = |fen: &str| {
+            let board = Board::from_fen(fen);
+            evaluate(&board, &params).taper(board.game_phase())
+        };
+        let phase = Board::from_fen("6k1/RR3ppp/8/8/8/8/5PPP/6K1 w - - 0 1").game_phase();
+        let bonus = |count| (params.rook_on_seventh * count).taper(phase);
+
+        // Both rooks on the 7th, with the king stuck on the back rank
+        assert_eq!(eval("6k1/RR3ppp/8/8/8/8/5PPP/6K1 w - - 0 1"), bonus(4));
+        // Half as much once the king has left the back rank
+        assert_eq!(eval("8/RR3ppk/8/8/8/8/5PPP/6K1 w - - 0 1"), bonus(2));
+        // Nothing on the 6th
+        assert_eq!(eval("6k1/5ppp/RR6/8/8/8/5PPP/6K1 w - - 0 1"), 0);
+        // Same for Black, from Black's point of view
+        assert_eq!(eval("6k1/5ppp/8/8/8/8/rr3PPP/6K1 b - - 0 1"), bonus(4));
+    }
+
+    #[test]
+    fn test_connected_rooks() {
+        let mut params = TunableParams::zeros();
+        params.connected_rooks_bonus = TunableParams::default().connected_rooks_bonus;
+        let eval = |fen: &str| {
+            let board = Board::from_fen(fen);
+            evaluate(&board, &params).taper(board.game_phase())
+        };
+        let bonus = |fen: &str| {
+            params
+                .connected_rooks_bonus
+                .taper(Board::from_fen(fen).game_phase())
+        };
+
+        // Doubled on the open d file
+        let doubled = "6k1/5ppp/8/8/8/3R4/3R1PPP/6K1 w - - 0 1";
+        assert_eq!(eval(doubled), bonus(doubled));
+        // Same rooks with a knight in between
+        assert_eq!(eval("6k1/5ppp/8/8/3R4/3N4/3R1PPP/6K1 w - - 0 1"), 0);
+        // Diagonal neighbours don't defend each other
+        assert_eq!(eval("6k1/5ppp/8/8/8/4R3/3R1PPP/6K1 w - - 0 1"), 0);
+        // Connected along the back rank, for Black from Black's point of view
+        let back_rank = "r4rk1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1";
+        assert_eq!(eval(back_rank), bonus(back_rank));
+    }
+
+    #[test]
+    fn test_file_battery() {
+        let zeros = TunableParams::zeros();
+        let mut params = TunableParams::zeros();
+        params.file_battery_bonus = TunableParams::default().file_battery_bonus;
+        // King safety adds fixed scores on top of the params, so only look at the difference
+        let battery = |fen: &str| {
+            let board = Board::from_fen(fen);
+            (evaluate(&board, &params) - evaluate(&board, &zeros)).taper(board.game_phase())
+        };
+        let bonus = |fen: &str| {
+            params
+                .file_battery_bonus
+                .taper(Board::from_fen(fen).game_phase())
+        };
+
+        // Queen behind the rook on the open d file, aimed at the king on d8
+        let fen = "3k4/pppn1ppp/8/8/8/8/PPPR1PPP/3QK3 w - - 0 1";
+        assert_eq!(battery(fen), bonus(fen));
+        // Nothing to aim at with the king tucked away on a8
+        assert_eq!(battery("k7/ppp2ppp/8/8/8/8/PPPR1PPP/3QK3 w - - 0 1"), 0);
+        // A knight between queen and rook breaks the battery
+        assert_eq!(battery("3k4/pppn1ppp/8/8/8/3R4/PPPN1PPP/3QK3 w - - 0 1"), 0);
+        // Two queens aren't a battery
+        assert_eq!(battery("3k4/pppn1ppp/8/8/8/8/PPPQ1PPP/3QK3 w - - 0 1"), 0);
+        // Doubled rooks aimed at an undefended pawn, for Black from Black's point of view
+        let fen = "3r2k1/3r1ppp/8/8/8/3P4/5PPP/6K1 b - - 0 1";
+        assert_eq!(battery(fen), bonus(fen));
+    }
 }
@@ -0,0 +1,108 @@
+//! Incrementally maintained Piece-Square Table and material scores
+//!
+//! The search keeps an [`IncrementalScore`] for every position along the line it is
+//! searching, updated from the `MoveInfo` of each move it makes, so the leaf evaluation
+//! doesn't need to scan every piece. The scores are valued with the params passed in, so
+//! they always agree with an eval using the same params.
+
+use crate::{prelude::*, tuning::params::TunableParams};
+
+/// White relative PST and material scores of a position
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IncrementalScore {
+    pub pst: Score,
+    pub material: Score,
+}
+
+impl IncrementalScore {
+    /// Full recompute from the pieces on the board
+    pub fn compute(board: &Board, params: &TunableParams) -> Self {
+        Self {
+            pst: compute_pst(board, params),
+            material: compute_material(board, params),
+        }
+    }
+
+    /// Scores after the move described by `info`, given the scores before it
+    pub fn after_move(mut self, info: &MoveInfo, params: &TunableParams) -> Self {
+        let (us, them) = (info.stm, info.stm.flip());
+        let (from, to) = (info.from.index(), info.to.index());
+
+        if let Some(captured) = info.captured_piece {
+            let captured_sq = match (info.is_en_passant, us) {
+                (true, Side::White) => to - 8,
+                (true, Side::Black) => to + 8,
+                (false, _) => to,
+            };
+            self.pst -= pst_value(params, captured, them, captured_sq);
+            self.material -= material_value(params, captured, them);
+        }
+
+        self.pst -= pst_value(params, info.piece_moved, us, from);
+        match info.promotion {
+            Some(promo) => {
+                self.pst += pst_value(params, promo, us, to);
+                self.material += material_value(params, promo, us);
+                self.material -= material_value(params, Piece::Pawn, us);
+            }
+            None => self.pst += pst_value(params, info.piece_moved, us, to),
+        }
+
+        if info.is_castling {
+            // Same rook squares as `Board::make_move`
+            let back_rank = from / 8 * 8;
+            let (rook_from, rook_to) = if to > from {
+                (back_rank + 7, back_rank + 5)
+            } else {
+                (back_rank, back_rank + 3)
+            };
+            self.pst -= pst_value(params, Piece::Rook, us, rook_from);
+            self.pst += pst_value(params, Piece::Rook, us, rook_to);
+        }
+        self
+    }
+}
+
+/// White relative PST value of `piece` belonging to `side` on `sq`
+#[inline(always)]
+pub fn pst_value(params: &TunableParams, piece: Piece, side: Side, sq: usize) -> Score {
+    let value = params.psts[piece.index() * 64 + side.relative_square(sq)];
+    match side {
+        Side::White => value,
+        Side::Black => -value,
+    }
+}
+
+/// White relative material value of `piece` belonging to `side`. Kings are worth nothing
+#[inline(always)]
+pub fn material_value(params: &TunableParams, piece: Piece, side: Side) -> Score {
+    let value = match piece {
+        Piece::King => Score::default(),
+        _ => params.material[piece.index()],
+    };
+    match side {
+        Side::White => value,
+        Side::Black => -value,
+    }
+}
+
+/// Full material recompute from the pieces on the board
+pub fn compute_material(board: &Board, params: &TunableParams) -> Score {
+    let mut score = Score::default();
+    for (piece, side) in Piece::all() {
+        let count = board.positions.get_piece_bb(side, piece).pop_count() as i32;
+        score += material_value(params, piece, side) * count;
+    }
+    score
+}
+
+/// Full PST recompute from the pieces on the board
+pub fn compute_pst(board: &Board, params: &TunableParams) -> Score {
+    let mut score = Score::default();
+    for (piece, side) in Piece::all() {
+        for sq in board.positions.get_piece_bb(side, piece).iter_bits() {
+            score += pst_value(params, piece, side, sq);
+        }
+    }
+    score
+}
@@ -73,6 +73,10 @@ impl Tunable for Score {
         *idx += 2;
         Score::new(mg, eg)
     }
+
+    fn to_rust_source(&self) -> String {
+        format!("Score::new({}, {})", self.mg, self.eg)
+    }
 }
 
 impl<const N: usize> Tunable for [Score; N] {
@@ -85,6 +89,15 @@ impl<const N: usize> Tunable for [Score; N] {
     fn read_from_vector(vec: &[f64], idx: &mut usize) -> Self {
         std::array::from_fn(|_| Score::read_from_vector(vec, idx))
     }
+
+    fn to_rust_source(&self) -> String {
+        let mut src = String::from("[\n");
+        for score in self {
+            src.push_str(&format!("        {},\n", score.to_rust_source()));
+        }
+        src.push_str("    ]");
+        src
+    }
 }
 
 impl Add for Score {
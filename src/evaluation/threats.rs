@@ -0,0 +1,104 @@
+use crate::{
+    evaluation::accumulator::EvalAccumulator,
+    prelude::*,
+    tuning::params::{THREAT_BY_MINOR, THREAT_BY_PAWN, THREAT_BY_ROOK},
+};
+
+pub(crate) fn eval_threats(board: &Board, acc: &mut impl EvalAccumulator) {
+    for side in [Side::White, Side::Black] {
+        eval_side_threats(board, side, acc);
+    }
+}
+
+/// Rewards `side` for attacking enemy pieces worth more than the attacker.
+/// Each attacked piece is counted once per attacker class, so a knight forking
+/// two rooks counts as two minor threats.
+fn eval_side_threats(board: &Board, side: Side, acc: &mut impl EvalAccumulator) {
+    let opponent = side.flip();
+    let occupied = board.positions.get_occupied_bb();
+
+    let enemy_knights = *board.positions.get_piece_bb(opponent, Piece::Knight);
+    let enemy_bishops = *board.positions.get_piece_bb(opponent, Piece::Bishop);
+    let enemy_rooks = *board.positions.get_piece_bb(opponent, Piece::Rook);
+    let enemy_queens = *board.positions.get_piece_bb(opponent, Piece::Queen);
+
+    let enemy_minors = enemy_knights | enemy_bishops;
+    let enemy_majors = enemy_rooks | enemy_queens;
+
+    // Pawns attacking any minor or major piece
+    let mut pawn_attacks = BitBoard(0);
+    for sq in board.positions.get_piece_bb(side, Piece::Pawn).iter_bits() {
+        pawn_attacks |= MOVE_TABLES.get_pawn_attacks(sq, side);
+    }
+    let pawn_threats = (pawn_attacks & (enemy_minors | enemy_majors)).pop_count() as i32;
+    if pawn_threats > 0 {
+        acc.add_feature(THREAT_BY_PAWN, side, pawn_threats);
+    }
+
+    // Knights and Bishops attacking rooks or queens
+    let mut minor_attacks = BitBoard(0);
+    for sq in board
+        .positions
+        .get_piece_bb(side, Piece::Knight)
+        .iter_bits()
+    {
+        minor_attacks |= MOVE_TABLES.knight_moves[sq];
+    }
+    for sq in board
+        .positions
+        .get_piece_bb(side, Piece::Bishop)
+        .iter_bits()
+    {
+        minor_attacks |= MOVE_TABLES.get_bishop_attacks_bb(sq, occupied);
+    }
+    let minor_threats = (minor_attacks & enemy_majors).pop_count() as i32;
+    if minor_threats > 0 {
+        acc.add_feature(THREAT_BY_MINOR, side, minor_threats);
+    }
+
+    // Rooks attacking queens
+    let mut rook_attacks = BitBoard(0);
+    for sq in board.positions.get_piece_bb(side, Piece::Rook).iter_bits() {
+        rook_attacks |= MOVE_TABLES.get_rook_attacks_bb(sq, occupied);
+    }
+    let rook_threats = (rook_attacks & enemy_queens).pop_count() as i32;
+    if rook_threats > 0 {
+        acc.add_feature(THREAT_BY_ROOK, side, rook_threats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::accumulator::ScoreAccumulator;
+    use crate::tuning::params::TunableParams;
+
+    fn threat_score(board: &Board, params: &TunableParams) -> Score {
+        let mut acc = ScoreAccumulator::new(params);
+        eval_threats(board, &mut acc);
+        acc.score
+    }
+
+    #[test]
+    fn test_knight_fork_on_rooks() {
+        // White knight on d5 attacks both black rooks on c7 and e7
+        let board = Board::from_fen("4k3/2r1r3/8/3N4/8/8/8/4K3 w - - 0 1");
+        let mut params = TunableParams::zeros();
+        params.threat_by_minor = Score::new(10, 5);
+
+        let score = threat_score(&board, &params);
+        assert_eq!(score, Score::new(20, 10));
+    }
+
+    #[test]
+    fn test_threats_are_symmetric() {
+        let white = Board::from_fen("4k3/2r1r3/8/3N4/8/8/8/4K3 w - - 0 1");
+        let black = Board::from_fen("4k3/8/8/8/3n4/8/2R1R3/4K3 b - - 0 1");
+        let params = TunableParams::default();
+
+        assert_eq!(
+            threat_score(&white, &params),
+            -threat_score(&black, &params)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    board::bitboard_ops::pawn_attacks_bb,
+    evaluation::accumulator::EvalAccumulator,
+    prelude::*,
+    tuning::params::{TRAPPED_BISHOP_PENALTY, TRAPPED_KNIGHT_PENALTY, TRAPPED_ROOK_PENALTY},
+};
+
+const RIM: BitBoard = BitBoard(FILE_MASKS[0] | FILE_MASKS[7] | RANK_MASKS[0] | RANK_MASKS[7]);
+
+/// A rook boxed in by its own king has at most this many squares to go to
+const TRAPPED_ROOK_MOBILITY: u32 = 3;
+
+pub(crate) fn eval_trapped_pieces(board: &Board, acc: &mut impl EvalAccumulator) {
+    for side in [Side::White, Side::Black] {
+        eval_side_trapped(board, side, acc);
+    }
+}
+
+/// Penalises `side` for pieces with no safe square to go to. A square is safe if it is not
+/// occupied by a friendly piece and not attacked by an enemy pawn.
+fn eval_side_trapped(board: &Board, side: Side, acc: &mut impl EvalAccumulator) {
+    let opponent = side.flip();
+    let occupied = board.positions.get_occupied_bb();
+    let friendly = *board.positions.get_side_bb(side);
+    let enemy_pawns = *board.positions.get_piece_bb(opponent, Piece::Pawn);
+    let unsafe_squares = friendly | pawn_attacks_bb(enemy_pawns, opponent);
+    let back_rank = match side {
+        Side::White => BitBoard(RANK_MASKS[0]),
+        Side::Black => BitBoard(RANK_MASKS[7]),
+    };
+
+    // Knights on the rim with nowhere to go
+    let knights = *board.positions.get_piece_bb(side, Piece::Knight) & RIM;
+    let trapped_knights = knights
+        .iter_bits()
+        .filter(|&sq| (MOVE_TABLES.knight_moves[sq] & !unsafe_squares).is_empty())
+        .count() as i32;
+    if trapped_knights > 0 {
+        acc.add_feature(TRAPPED_KNIGHT_PENALTY, side, trapped_knights);
+    }
+
+    // Developed bishops with nowhere to go, or stuck behind an enemy pawn in the corner.
+    // Undeveloped bishops are left to the PSTs
+    let trapped_bishops = board
+        .positions
+        .get_piece_bb(side, Piece::Bishop)
+        .iter_bits()
+        .filter(|&sq| {
+            let safe = MOVE_TABLES.get_bishop_attacks_bb(sq, occupied) & !unsafe_squares;
+            (safe.is_empty() && !back_rank.contains_square(sq))
+                || is_corner_trap(sq, side, enemy_pawns)
+        })
+        .count() as i32;
+    if trapped_bishops > 0 {
+        acc.add_feature(TRAPPED_BISHOP_PENALTY, side, trapped_bishops);
+    }
+
+    // Rooks stuck in the corner behind a king that can no longer castle that way
+    let Some(king_sq) = board.positions.get_piece_bb(side, Piece::King).lsb() else {
+        return;
+    };
+    let king_sq = king_sq as usize;
+    if !back_rank.contains_square(king_sq) {
+        return;
+    }
+    let king_file = king_sq % 8;
+    let rooks = *board.positions.get_piece_bb(side, Piece::Rook) & back_rank;
+    let trapped_rooks = rooks
+        .iter_bits()
+        .filter(|&sq| {
+            let kingside = sq % 8 > king_file;
+            let boxed_in = if kingside {
+                king_file >= 4
+            } else {
+                king_file <= 3
+            };
+            boxed_in
+                && !board.castling_rights.can_castle(side, kingside)
+                && (MOVE_TABLES.get_rook_attacks_bb(sq, occupied) & !friendly).pop_count()
+                    <= TRAPPED_ROOK_MOBILITY
+        })
+        .count() as i32;
+    if trapped_rooks > 0 {
+        acc.add_feature(TRAPPED_ROOK_PENALTY, side, trapped_rooks);
+    }
+}
+
+/// The classic Bxa7 trap: a bishop on a7/h7 (a2/h2 for Black) cut off by a pawn on b6/g6
+fn is_corner_trap(sq: usize, side: Side, enemy_pawns: BitBoard) -> bool {
+    const A7: usize = 48;
+    const B6: usize = 41;
+    const H7: usize = 55;
+    const G6: usize = 46;
+    // Flip Black's squares so the pattern is only written once
+    match side.relative_square(sq) {
+        A7 => enemy_pawns.contains_square(side.relative_square(B6)),
+        H7 => enemy_pawns.contains_square(side.relative_square(G6)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::accumulator::ScoreAccumulator;
+    use crate::tuning::params::TunableParams;
+
+    fn trapped_score(fen: &str) -> Score {
+        let mut params = TunableParams::zeros();
+        params.trapped_knight_penalty = Score::new(-1, 0);
+        params.trapped_bishop_penalty = Score::new(-10, 0);
+        params.trapped_rook_penalty = Score::new(-100, 0);
+        let mut acc = ScoreAccumulator::new(&params);
+        eval_trapped_pieces(&Board::from_fen(fen), &mut acc);
+        acc.score
+    }
+
+    #[test]
+    fn test_bishop_with_no_safe_squares_is_trapped() {
+        // Hemmed in by its own pawns on b2 and b4
+        assert_eq!(trapped_score("4k3/8/8/8/1P6/B7/1P6/4K3 w - - 0 1").mg, -10);
+        // Same bishop with one pawn gone can leave via b4
+        assert_eq!(trapped_score("4k3/8/8/8/8/B7/1P6/4K3 w - - 0 1").mg, 0);
+        // Bxa7 with ...b6 shutting it in
+        assert_eq!(trapped_score("4k3/B1p5/1p6/8/8/8/8/4K3 w - - 0 1").mg, -10);
+        // Same for Black, from White's point of view
+        assert_eq!(trapped_score("4k3/8/8/8/8/1P6/b1P5/4K3 b - - 0 1").mg, 10);
+        // Undeveloped bishops are not trapped
+        assert_eq!(trapped_score(START_FEN).mg, 0);
+    }
+
+    #[test]
+    fn test_trapped_knight_and_rook() {
+        // Every square the a1 knight can reach is covered by a black pawn
+        assert_eq!(trapped_score("4k3/8/8/8/2p5/1p6/2p5/N3K3 w - - 0 1").mg, -1);
+        // The rook is boxed in by the king on f1, which can't castle anymore
+        assert_eq!(trapped_score("4k3/8/8/8/8/8/6PP/5K1R w - - 0 1").mg, -100);
+        // Not while the king can still castle
+        assert_eq!(trapped_score("4k3/8/8/8/8/8/6PP/4K2R w K - 0 1").mg, 0);
+    }
+}
@@ -9,6 +9,7 @@
 
 pub mod board;
 pub mod comms;
+pub mod error;
 pub mod evaluation;
 pub mod moves;
 pub mod precomputed;
@@ -16,6 +17,8 @@ pub mod prelude;
 pub mod search;
 pub mod tuning;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub mod ansi_colors {
     // Reset
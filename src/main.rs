@@ -30,21 +30,40 @@ fn main() -> miette::Result<()> {
         Some(cmd) => match cmd {
             Commands::Play { fen, depth } => {
                 trace!("Starting game with fen: {:?}, depth: {:?}", fen, depth);
-                game_loop(fen.unwrap(), depth.unwrap(), params)?;
+                let board = start_position(fen.as_deref())?;
+                game_loop(board, depth.unwrap(), params)?;
             }
             Commands::Perft { fen, depth, divide } => {
                 trace!(
                     "Running perft with fen: {:?}, depth: {:?}, divide: {:?}",
                     fen, depth, divide
                 );
-                let mut board = Board::from_fen(&fen.unwrap());
+                let mut board = start_position(fen.as_deref())?;
                 println!("{board}");
                 if divide {
-                    perft_divide(&mut board, depth);
+                    perft_divide(&mut board, depth, true);
                 } else {
                     run_perft_suite(&mut board, depth);
                 }
             }
+            Commands::Analyze { depth, movetime } => {
+                trace!(
+                    "Analyzing stdin with depth: {:?}, movetime: {:?}",
+                    depth, movetime
+                );
+                let stdin = std::io::stdin();
+                analyze(
+                    stdin.lock(),
+                    &mut std::io::stdout(),
+                    depth,
+                    movetime,
+                    params,
+                )?;
+            }
+            Commands::Sts { file, movetime } => {
+                trace!("Running STS from {:?} with movetime: {}", file, movetime);
+                run_sts(&file, movetime, params, &mut std::io::stdout())?;
+            }
             Commands::Headless { protocol } => {
                 trace!("Running headless with protocol: {:?}", protocol);
                 uci::play(params)?;
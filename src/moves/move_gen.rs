@@ -65,6 +65,140 @@ pub fn generate_legal_moves<T: MoveGenType>(board: &Board, moves: &mut MoveBuffe
     gen_legal_sliding_moves::<T>(board, Piece::Queen, &attack_data, moves);
 }
 
+/// Number of legal moves for the side to move, same as the length of
+/// `generate_legal_moves::<AllMoves>` but without building the moves
+pub fn count_legal_moves(board: &Board) -> usize {
+    let side = board.stm;
+    let attack_data = calculate_attack_data(board, side);
+    let friendly_pieces = *board.positions.get_side_bb(side);
+    let enemy_pieces = *board.positions.get_side_bb(side.flip());
+
+    let opp_attack_map = attack_data::calculate_opp_attack_map(board, side);
+    let king_targets = MOVE_TABLES.king_moves[attack_data.king_sq] & !friendly_pieces;
+    let mut count = (king_targets & !opp_attack_map).pop_count() as usize;
+    if attack_data.double_check {
+        return count;
+    }
+    if !attack_data.in_check {
+        count += legal_castles(board, opp_attack_map)
+            .into_iter()
+            .flatten()
+            .count();
+    }
+
+    let mut knights_bb =
+        *board.positions.get_piece_bb(side, Piece::Knight) & !attack_data.pin_ray_mask;
+    while knights_bb.any() {
+        let from_sq = knights_bb.pop_lsb() as usize;
+        let targets = MOVE_TABLES.knight_moves[from_sq] & !friendly_pieces;
+        count += (targets & attack_data.check_ray_mask).pop_count() as usize;
+    }
+
+    for piece in [Piece::Bishop, Piece::Rook, Piece::Queen] {
+        let mut piece_bb = *board.positions.get_piece_bb(side, piece);
+        while piece_bb.any() {
+            let from_sq = piece_bb.pop_lsb() as usize;
+            let mut move_mask = attack_data.check_ray_mask;
+            if attack_data.pin_ray_mask.contains_square(from_sq) {
+                move_mask &= MOVE_TABLES.get_ray(
+                    attack_data.king_sq,
+                    Direction::get_dir(attack_data.king_sq, from_sq),
+                );
+            }
+            let attacks = match piece {
+                Piece::Bishop => {
+                    MOVE_TABLES.get_bishop_moves(from_sq, friendly_pieces, enemy_pieces)
+                }
+                Piece::Rook => MOVE_TABLES.get_rook_moves(from_sq, friendly_pieces, enemy_pieces),
+                _ => MOVE_TABLES.get_queen_moves(from_sq, friendly_pieces, enemy_pieces),
+            };
+            count += (attacks & move_mask).pop_count() as usize;
+        }
+    }
+
+    count + count_legal_pawn_moves(board, &attack_data)
+}
+
+fn count_legal_pawn_moves(board: &Board, attack_data: &AttackData) -> usize {
+    let side = board.stm;
+    let enemy_pieces = *board.positions.get_side_bb(side.flip());
+    let all_pieces = enemy_pieces | *board.positions.get_side_bb(side);
+    let (push_dir, start_rank, promo_rank) = match side {
+        Side::White => (Direction::NORTH, 1, 7),
+        Side::Black => (Direction::SOUTH, 6, 0),
+    };
+    // Every promotion is 4 moves
+    let moves_to = |to_sq: usize| if to_sq / 8 == promo_rank { 4 } else { 1 };
+
+    let mut count = 0;
+    let mut pawns_bb = *board.positions.get_piece_bb(side, Piece::Pawn);
+    while pawns_bb.any() {
+        let from_sq = pawns_bb.pop_lsb() as usize;
+        let pin_mask = pawn_pin_mask(attack_data, from_sq);
+
+        let one_step = (from_sq as i8 + push_dir) as usize;
+        if pin_mask.contains_square(one_step) && !all_pieces.contains_square(one_step) {
+            if attack_data.check_ray_mask.contains_square(one_step) {
+                count += moves_to(one_step);
+            }
+            let two_steps = (from_sq as i8 + 2 * push_dir.value()) as usize;
+            if from_sq / 8 == start_rank
+                && !all_pieces.contains_square(two_steps)
+                && attack_data.check_ray_mask.contains_square(two_steps)
+            {
+                count += 1;
+            }
+        }
+
+        let attacks = MOVE_TABLES.get_pawn_attacks(from_sq, side);
+        let mut capture_targets = attacks & enemy_pieces & attack_data.check_ray_mask;
+        while capture_targets.any() {
+            let to_sq = capture_targets.pop_lsb() as usize;
+            if pin_mask.contains_square(to_sq) {
+                count += moves_to(to_sq);
+            }
+        }
+
+        if let Some(ep_sq) = board.enpassant_square
+            && attacks.contains_square(ep_sq.index())
+            && pin_mask.contains_square(ep_sq.index())
+            && is_legal_en_passant(board, attack_data, from_sq, ep_sq.index())
+        {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Same as `generate_legal_moves::<AllMoves>`, but the buffer comes back ordered by a cheap
+/// static score: captures by MVV-LVA, then promotions, then quiet moves. For light callers
+/// that don't need the search's `MovePicker`
+pub fn generate_legal_moves_sorted(board: &Board, moves: &mut MoveBuffer) {
+    generate_legal_moves::<AllMoves>(board, moves);
+    moves
+        .as_mut_slice()
+        .sort_unstable_by_key(|&mv| std::cmp::Reverse(static_order_score(board, mv)));
+}
+
+/// Captures always outrank quiet moves, promotions add the promoted piece's value
+fn static_order_score(board: &Board, mv: Move) -> i32 {
+    let mut score = 0;
+    if mv.is_capture() {
+        let victim = if mv.is_enpassant() {
+            Piece::Pawn
+        } else {
+            board.get_piece_at(mv.to_sq()).unwrap_or(Piece::Pawn)
+        };
+        let attacker = board.get_piece_at(mv.from_sq()).unwrap_or(Piece::Pawn);
+        // Offset keeps even KxP above every quiet promotion
+        score += 100_000 + victim.victim_score() * 10 - attacker.index() as i32;
+    }
+    if let Some(promo) = mv.promoted_piece() {
+        score += promo.victim_score();
+    }
+    score
+}
+
 pub fn get_attackers_to(board: &Board, square: Square, side: Side, occupied: BitBoard) -> BitBoard {
     let sq_idx = square.index();
     let opponent = side.flip();
@@ -131,62 +265,57 @@ fn gen_legal_king_moves<T: MoveGenType>(
     // Castling
     // Castling is never a capture / forcing, so can be skipped
     if !T::CAPTURES_ONLY && !T::FORCING_ONLY && !attack_data.in_check {
-        let all_pieces =
-            board.positions.get_side_bb(Side::White) | board.positions.get_side_bb(Side::Black);
-        match side {
-            Side::White => {
-                // Kingside
-                if board
-                    .castling_rights
-                    .allows(CastlingRights(CastlingRights::WHITE_00))
-                    && !all_pieces.contains_square(5)
-                    && !all_pieces.contains_square(6)
-                    && !opp_attack_map.contains_square(5)
-                    && !opp_attack_map.contains_square(6)
-                {
-                    moves.push(Move::new(4, 6, Move::KING_CASTLE));
-                }
-                // Queenside
-                if board
-                    .castling_rights
-                    .allows(CastlingRights(CastlingRights::WHITE_000))
-                    && !all_pieces.contains_square(1)
-                    && !all_pieces.contains_square(2)
-                    && !all_pieces.contains_square(3)
-                    && !opp_attack_map.contains_square(2)
-                    && !opp_attack_map.contains_square(3)
-                {
-                    moves.push(Move::new(4, 2, Move::QUEEN_CASTLE));
-                }
-            }
-            Side::Black => {
-                if board
-                    .castling_rights
-                    .allows(CastlingRights(CastlingRights::BLACK_00))
-                    && !all_pieces.contains_square(61)
-                    && !all_pieces.contains_square(62)
-                    && !opp_attack_map.contains_square(61)
-                    && !opp_attack_map.contains_square(62)
-                {
-                    moves.push(Move::new(60, 62, Move::KING_CASTLE));
-                }
-                // Queenside
-                if board
-                    .castling_rights
-                    .allows(CastlingRights(CastlingRights::BLACK_000))
-                    && !all_pieces.contains_square(57)
-                    && !all_pieces.contains_square(58)
-                    && !all_pieces.contains_square(59)
-                    && !opp_attack_map.contains_square(58)
-                    && !opp_attack_map.contains_square(59)
-                {
-                    moves.push(Move::new(60, 58, Move::QUEEN_CASTLE));
-                }
-            }
-        }
+        legal_castles(board, opp_attack_map)
+            .into_iter()
+            .flatten()
+            .for_each(|mv| moves.push(mv));
     }
 }
 
+/// Adds the castling moves of the side to move that are legal: the king is not in check,
+/// and neither passes through nor lands on an attacked square
+pub fn generate_legal_castling(board: &Board, moves: &mut MoveBuffer) {
+    if board.is_in_check(board.stm) {
+        return;
+    }
+    let opp_attack_map = attack_data::calculate_opp_attack_map(board, board.stm);
+    legal_castles(board, opp_attack_map)
+        .into_iter()
+        .flatten()
+        .for_each(|mv| moves.push(mv));
+}
+
+/// Kingside and queenside castling moves of the side to move, if they're legal.
+/// Doesn't check whether the king is in check
+fn legal_castles(board: &Board, opp_attack_map: BitBoard) -> [Option<Move>; 2] {
+    let all_pieces =
+        board.positions.get_side_bb(Side::White) | board.positions.get_side_bb(Side::Black);
+    let (king_side, queen_side, rank) = match board.stm {
+        Side::White => (CastlingRights::WHITE_00, CastlingRights::WHITE_000, 0),
+        Side::Black => (CastlingRights::BLACK_00, CastlingRights::BLACK_000, 56),
+    };
+    let free = |squares: &[usize]| {
+        squares
+            .iter()
+            .all(|&sq| !all_pieces.contains_square(rank + sq))
+    };
+    let safe = |squares: &[usize]| {
+        squares
+            .iter()
+            .all(|&sq| !opp_attack_map.contains_square(rank + sq))
+    };
+
+    let kingside =
+        (board.castling_rights.allows(CastlingRights(king_side)) && free(&[5, 6]) && safe(&[5, 6]))
+            .then(|| Move::new(rank as u8 + 4, rank as u8 + 6, Move::KING_CASTLE));
+    let queenside = (board.castling_rights.allows(CastlingRights(queen_side))
+        && free(&[1, 2, 3])
+        && safe(&[2, 3]))
+    .then(|| Move::new(rank as u8 + 4, rank as u8 + 2, Move::QUEEN_CASTLE));
+
+    [kingside, queenside]
+}
+
 fn gen_legal_sliding_moves<T: MoveGenType>(
     board: &Board,
     piece: Piece,
@@ -326,12 +455,7 @@ fn gen_legal_pawn_moves<T: MoveGenType>(
     while pawns_bb.any() {
         let from_sq = pawns_bb.pop_lsb();
         let from_sq_u = from_sq as usize;
-        let is_pinned = attack_data.pin_ray_mask.contains_square(from_sq_u);
-        let pin_dir = if is_pinned {
-            Some(Direction::get_dir(attack_data.king_sq, from_sq_u))
-        } else {
-            None
-        };
+        let pin_mask = pawn_pin_mask(attack_data, from_sq_u);
 
         // Pushes
         if !T::FORCING_ONLY {
@@ -340,34 +464,30 @@ fn gen_legal_pawn_moves<T: MoveGenType>(
             } else {
                 Direction::SOUTH
             };
-            if pin_dir.is_none() || pin_dir == Some(push_dir) {
-                let one_step = from_sq_u as i8 + push_dir;
-                if !all_pieces.contains_square(one_step as usize) {
-                    if attack_data
-                        .check_ray_mask
-                        .contains_square(one_step as usize)
-                    {
-                        if (one_step as usize) / 8 == promo_rank {
-                            add_promo_moves(from_sq as u8, one_step as u8, false, moves);
-                        } else {
-                            moves.push(Move::new(from_sq as u8, one_step as u8, Move::QUIET));
-                        }
+            let one_step = from_sq_u as i8 + push_dir;
+            if pin_mask.contains_square(one_step as usize)
+                && !all_pieces.contains_square(one_step as usize)
+            {
+                if attack_data
+                    .check_ray_mask
+                    .contains_square(one_step as usize)
+                {
+                    if (one_step as usize) / 8 == promo_rank {
+                        add_promo_moves(from_sq as u8, one_step as u8, false, moves);
+                    } else {
+                        moves.push(Move::new(from_sq as u8, one_step as u8, Move::QUIET));
                     }
-                    // Double push
-                    let start_rank = if side == Side::White { 1 } else { 6 };
-                    if from_sq_u / 8 == start_rank {
-                        let two_steps = from_sq_u as i8 + 2 * push_dir.value();
-                        if !all_pieces.contains_square(two_steps as usize)
-                            && attack_data
-                                .check_ray_mask
-                                .contains_square(two_steps as usize)
-                        {
-                            moves.push(Move::new(
-                                from_sq as u8,
-                                two_steps as u8,
-                                Move::DOUBLE_PAWN,
-                            ));
-                        }
+                }
+                // Double push
+                let start_rank = if side == Side::White { 1 } else { 6 };
+                if from_sq_u / 8 == start_rank {
+                    let two_steps = from_sq_u as i8 + 2 * push_dir.value();
+                    if !all_pieces.contains_square(two_steps as usize)
+                        && attack_data
+                            .check_ray_mask
+                            .contains_square(two_steps as usize)
+                    {
+                        moves.push(Move::new(from_sq as u8, two_steps as u8, Move::DOUBLE_PAWN));
                     }
                 }
             }
@@ -379,22 +499,21 @@ fn gen_legal_pawn_moves<T: MoveGenType>(
             } else {
                 Direction::SOUTH
             };
-            if pin_dir.is_none() || pin_dir == Some(push_dir) {
-                let one_step = (from_sq as i8 + push_dir) as usize;
-                if !all_pieces.contains_square(one_step)
-                    && attack_data.check_ray_mask.contains_square(one_step)
-                {
-                    // Only consider promotions, as they are the only pushes that can be forcing.
-                    if one_step / 8 == promo_rank {
-                        if T::CAPTURES_ONLY {
-                            // in CapturesOnly, we want all promos for material change
-                            add_promo_moves(from_sq as u8, one_step as u8, false, moves);
-                        }
-                        // Queen promo is most likey to result in checks
-                        let promo_move = Move::new(from_sq as u8, one_step as u8, Move::PROMO_Q);
-                        if is_move_a_check(board, promo_move, opponent_king_sq) {
-                            add_promo_moves(from_sq as u8, one_step as u8, false, moves);
-                        }
+            let one_step = (from_sq as i8 + push_dir) as usize;
+            if pin_mask.contains_square(one_step)
+                && !all_pieces.contains_square(one_step)
+                && attack_data.check_ray_mask.contains_square(one_step)
+            {
+                // Only consider promotions, as they are the only pushes that can be forcing.
+                if one_step / 8 == promo_rank {
+                    if T::CAPTURES_ONLY {
+                        // in CapturesOnly, we want all promos for material change
+                        add_promo_moves(from_sq as u8, one_step as u8, false, moves);
+                    }
+                    // Queen promo is most likey to result in checks
+                    let promo_move = Move::new(from_sq as u8, one_step as u8, Move::PROMO_Q);
+                    if is_move_a_check(board, promo_move, opponent_king_sq) {
+                        add_promo_moves(from_sq as u8, one_step as u8, false, moves);
                     }
                 }
             }
@@ -405,8 +524,7 @@ fn gen_legal_pawn_moves<T: MoveGenType>(
         while capture_targets.any() {
             let to_sq = capture_targets.pop_lsb();
             let to_sq_u = to_sq as usize;
-            let capture_dir = Direction::get_dir(from_sq_u, to_sq_u);
-            if (pin_dir.is_none() || pin_dir == Some(capture_dir))
+            if pin_mask.contains_square(to_sq_u)
                 && attack_data.check_ray_mask.contains_square(to_sq_u)
             {
                 if to_sq_u / 8 == promo_rank {
@@ -420,41 +538,59 @@ fn gen_legal_pawn_moves<T: MoveGenType>(
         // En Passant
         if let Some(ep_sq) = board.enpassant_square
             && (attacks & BitBoard(1 << ep_sq.index())).any()
+            && pin_mask.contains_square(ep_sq.index())
+            && is_legal_en_passant(board, attack_data, from_sq_u, ep_sq.index())
         {
-            let ep_dir = Direction::get_dir(from_sq_u, ep_sq.index());
-            if pin_dir.is_none() || pin_dir == Some(ep_dir) {
-                // En passant check is complex: need to see if removing both pawns opens a check.
-                let captured_pawn_sq = if side == Side::White {
-                    ep_sq.index() - 8
-                } else {
-                    ep_sq.index() + 8
-                };
-                let occupied_after_ep =
-                    (all_pieces & !BitBoard(1 << from_sq_u) & !BitBoard(1 << captured_pawn_sq))
-                        | BitBoard(1 << ep_sq.index());
-                let king_sq = attack_data.king_sq;
-                let rooks_queens = board.positions.get_ortho_sliders_bb(side.flip());
-                let bishops_queens = board.positions.get_diag_sliders_bb(side.flip());
-
-                let rook_attacks =
-                    MOVE_TABLES.get_rook_moves(king_sq, BitBoard(0), occupied_after_ep);
-                let bishop_attacks =
-                    MOVE_TABLES.get_bishop_moves(king_sq, BitBoard(0), occupied_after_ep);
-
-                if (rook_attacks & rooks_queens).is_empty()
-                    && (bishop_attacks & bishops_queens).is_empty()
-                {
-                    moves.push(Move::new(
-                        from_sq as u8,
-                        ep_sq.index() as u8,
-                        Move::EN_PASSANT,
-                    ));
-                }
-            }
+            moves.push(Move::new(
+                from_sq as u8,
+                ep_sq.index() as u8,
+                Move::EN_PASSANT,
+            ));
         }
     }
 }
 
+/// Squares a pawn on `from_sq` may move to without leaving its king in check through a pin.
+/// A pinned pawn stays on the line through its king, moving either towards or away from it
+fn pawn_pin_mask(attack_data: &AttackData, from_sq: usize) -> BitBoard {
+    if attack_data.pin_ray_mask.contains_square(from_sq) {
+        MOVE_TABLES.get_ray(
+            attack_data.king_sq,
+            Direction::get_dir(attack_data.king_sq, from_sq),
+        )
+    } else {
+        BitBoard(!0u64)
+    }
+}
+
+/// En passant check is complex: need to see if removing both pawns opens a check
+fn is_legal_en_passant(
+    board: &Board,
+    attack_data: &AttackData,
+    from_sq: usize,
+    ep_sq: usize,
+) -> bool {
+    let side = board.stm;
+    let all_pieces =
+        *board.positions.get_side_bb(Side::White) | *board.positions.get_side_bb(Side::Black);
+    let captured_pawn_sq = if side == Side::White {
+        ep_sq - 8
+    } else {
+        ep_sq + 8
+    };
+    let occupied_after_ep =
+        (all_pieces & !BitBoard(1 << from_sq) & !BitBoard(1 << captured_pawn_sq))
+            | BitBoard(1 << ep_sq);
+    let king_sq = attack_data.king_sq;
+    let rooks_queens = board.positions.get_ortho_sliders_bb(side.flip());
+    let bishops_queens = board.positions.get_diag_sliders_bb(side.flip());
+
+    let rook_attacks = MOVE_TABLES.get_rook_moves(king_sq, BitBoard(0), occupied_after_ep);
+    let bishop_attacks = MOVE_TABLES.get_bishop_moves(king_sq, BitBoard(0), occupied_after_ep);
+
+    (rook_attacks & rooks_queens).is_empty() && (bishop_attacks & bishops_queens).is_empty()
+}
+
 fn add_promo_moves(from: u8, to: u8, is_capture: bool, moves: &mut MoveBuffer) {
     if is_capture {
         moves.push(Move::new(from, to, Move::PROMO_QC));
@@ -469,9 +605,20 @@ fn add_promo_moves(from: u8, to: u8, is_capture: bool, moves: &mut MoveBuffer) {
     }
 }
 
+/// Whether the legal move `mv` puts the opponent in check
+pub fn gives_check(board: &Board, mv: Move) -> bool {
+    let opponent_king_sq = board
+        .positions
+        .get_piece_bb(board.stm.flip(), Piece::King)
+        .lsb()
+        .unwrap_or_default() as usize;
+    is_move_a_check(board, mv, opponent_king_sq)
+}
+
 /// Determines if a given pseudo-legal move delivers a check to the opponent.
-/// Also handles discovered checks
+/// Also handles discovered checks, promotions, en passant and the rook's check when castling
 fn is_move_a_check(board: &Board, mv: Move, opponent_king_sq: usize) -> bool {
+    let side = board.stm;
     let from = mv.from_sq().index();
     let to = mv.to_sq().index();
     let piece = match board.get_piece_at(mv.from_sq()) {
@@ -479,73 +626,64 @@ fn is_move_a_check(board: &Board, mv: Move, opponent_king_sq: usize) -> bool {
         None => return false,
     };
 
-    // Direct check from piece that is moved
-    let piece_attacks_from_to_sq = get_piece_attacks(board, board.stm, piece, to);
-    if piece_attacks_from_to_sq.contains_square(opponent_king_sq) {
+    // Squares our pieces leave, and the occupancy once the move is made
+    let mut vacated = BitBoard(1 << from);
+    let mut occupied = (board.positions.get_occupied_bb() & !vacated) | BitBoard(1 << to);
+    let (checker, checker_sq) = if mv.is_castling() {
+        // The king can't give check, only the rook can
+        let (rook_from, rook_to) = if to > from {
+            (to + 1, to - 1)
+        } else {
+            (to - 2, to + 1)
+        };
+        vacated |= BitBoard(1 << rook_from);
+        occupied = (occupied & !BitBoard(1 << rook_from)) | BitBoard(1 << rook_to);
+        (Piece::Rook, rook_to)
+    } else if mv.is_enpassant() {
+        let captured_pawn_sq = if side == Side::White { to - 8 } else { to + 8 };
+        occupied &= !BitBoard(1 << captured_pawn_sq);
+        (piece, to)
+    } else {
+        (mv.promoted_piece().unwrap_or(piece), to)
+    };
+
+    // Direct check from the piece that is moved
+    if attacks_with_occupancy(side, checker, checker_sq, occupied).contains_square(opponent_king_sq)
+    {
         return true;
     }
 
-    // Discovered check: see if moving this piece from 'from' square open up
-    // an avenue of attack by other pieces.
-    // NOTE: The moving piece is not a pinned piece, it is a friendly, so it cannot be pinned
-    // Pinned pieces are those that block an enemy's attack
+    // Discovered check: moving away from a line through the enemy king can open it up for
+    // one of our sliders. En passant also removes the captured pawn from its square
+    // get_dir can return a (0), so the ray check handles the case where they aren't aligned
     let king_and_from_ray =
         MOVE_TABLES.get_ray(opponent_king_sq, Direction::get_dir(opponent_king_sq, from));
-    // get_dir can return a (0), so this is to handle the case where the pieces are not
-    // aligned
-    if king_and_from_ray.contains_square(from) {
-        let occupied = board.positions.get_occupied_bb() & !BitBoard(1 << from); // Remove 'from' sq
-
-        let rooks_queens = board.positions.get_ortho_sliders_bb(board.stm);
-        let bishops_queens = board.positions.get_diag_sliders_bb(board.stm);
-
-        let magic_rook = MOVE_TABLES.get_rook_attacks_bb(opponent_king_sq, occupied);
-        if (magic_rook & rooks_queens).any() {
-            return true;
-        }
-        let magic_bishop = MOVE_TABLES.get_bishop_attacks_bb(opponent_king_sq, occupied);
-        if (magic_bishop & bishops_queens).any() {
-            return true;
-        }
+    if !king_and_from_ray.contains_square(from) && !mv.is_enpassant() && !mv.is_castling() {
+        return false;
     }
 
-    if mv.is_enpassant() {
-        let captured_pawn_sq = if board.stm == Side::White {
-            to - 8
-        } else {
-            to + 8
-        };
-        let occupied = (board.positions.get_occupied_bb()
-            & !BitBoard(1 << from)
-            & !BitBoard(1 << captured_pawn_sq))
-            | BitBoard(1 << to);
-
-        let rooks_queens = board.positions.get_ortho_sliders_bb(board.stm);
-        let bishops_queens = board.positions.get_diag_sliders_bb(board.stm);
-
-        let magic_rook = MOVE_TABLES.get_rook_attacks_bb(opponent_king_sq, occupied);
-        if (magic_rook & rooks_queens).any() {
-            return true;
-        }
+    // Sliders that moved were already covered by the direct check
+    let rooks_queens = board.positions.get_ortho_sliders_bb(side) & !vacated;
+    let bishops_queens = board.positions.get_diag_sliders_bb(side) & !vacated;
+    (MOVE_TABLES.get_rook_attacks_bb(opponent_king_sq, occupied) & rooks_queens).any()
+        || (MOVE_TABLES.get_bishop_attacks_bb(opponent_king_sq, occupied) & bishops_queens).any()
+}
 
-        let magic_bishop = MOVE_TABLES.get_bishop_attacks_bb(opponent_king_sq, occupied);
-        if (magic_bishop & bishops_queens).any() {
-            return true;
-        }
-    }
-    false
+pub(crate) fn get_piece_attacks(board: &Board, side: Side, piece: Piece, from: usize) -> BitBoard {
+    attacks_with_occupancy(side, piece, from, board.positions.get_occupied_bb())
 }
 
-fn get_piece_attacks(board: &Board, side: Side, piece: Piece, from: usize) -> BitBoard {
+/// Squares `piece` attacks from `from`, with sliders blocked by `occupied`
+fn attacks_with_occupancy(side: Side, piece: Piece, from: usize, occupied: BitBoard) -> BitBoard {
     match piece {
         Piece::Pawn => MOVE_TABLES.get_pawn_attacks(from, side),
         Piece::Knight => MOVE_TABLES.knight_moves[from],
         Piece::King => MOVE_TABLES.king_moves[from],
-        Piece::Bishop => MOVE_TABLES.get_bishop_attacks_bb(from, board.positions.get_occupied_bb()),
-        Piece::Rook => MOVE_TABLES.get_rook_attacks_bb(from, board.positions.get_occupied_bb()),
+        Piece::Bishop => MOVE_TABLES.get_bishop_attacks_bb(from, occupied),
+        Piece::Rook => MOVE_TABLES.get_rook_attacks_bb(from, occupied),
         Piece::Queen => {
-            MOVE_TABLES.get_rook_attacks_bb(from, board.positions.get_occupied_bb())
-                | MOVE_TABLES.get_bishop_attacks_bb(from, board.positions.get_occupied_bb())
+            MOVE_TABLES.get_rook_attacks_bb(from, occupied)
+                | MOVE_TABLES.get_bishop_attacks_bb(from, occupied)
         }
     }
 }
@@ -785,6 +923,10 @@ fn gen_pawn_moves_with_ep(
 }
 
 /// Generate pseudo-legal king moves with castling support.
+///
+/// Castling only requires the rights and empty squares between king and rook here, it
+/// may still start in, pass through or land on an attacked square.
+/// Use `generate_legal_castling` for castling moves that are safe to play
 fn gen_king_moves_with_castling(
     state: &BoardState,
     side: Side,
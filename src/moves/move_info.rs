@@ -14,10 +14,12 @@ pub struct MoveInfo {
     pub promotion: Option<Piece>,
     pub is_castling: bool,
     pub is_en_passant: bool,
+    pub check_given: bool,                // only set by `Board::play_move`
     pub castle_rights: CastlingRights,    // prev
     pub enpassant_square: Option<Square>, // prev
     pub halfmove_clock: u8,               // prev
     pub zobrist_hash: u64,                // prev
+    pub pawn_hash: u64,                   // prev
                                           // pub material: [Score; 2],             // prev
 }
 
@@ -59,6 +61,9 @@ impl Move {
     pub const TO_MASK: u16 = 0x0FC0;
     pub const FROM_MASK: u16 = 0x003F;
 
+    /// "No move", written as `0000` in UCI. Encodes a quiet a1a1, which is never legal
+    pub const NULL: Move = Move(0);
+
     pub const fn new(from: u8, to: u8, flags: u16) -> Self {
         Self((from as u16) | ((to as u16) << 6) | flags)
     }
@@ -113,6 +118,12 @@ impl Move {
         self.flags() == Self::QUIET
     }
 
+    /// Returns true if this is `Move::NULL`
+    #[inline(always)]
+    pub const fn is_null(&self) -> bool {
+        self.0 == Self::NULL.0
+    }
+
     /// Returns true if this move is a castling
     #[inline]
     pub const fn is_castling(&self) -> bool {
@@ -152,6 +163,9 @@ impl Move {
 
     /// Utility: returns a 'e2e4', 'e7e8q' etc
     pub fn uci(&self) -> String {
+        if self.is_null() {
+            return "0000".to_string();
+        }
         let from = Square::new(self.from_idx().into()).unwrap();
         let to = Square::new(self.to_idx().into()).unwrap_or_default();
         let uci_str = match self.promoted_piece_char() {
@@ -234,6 +248,165 @@ impl Move {
         ))
     }
 
+    /// Creates a Move from Long Algebraic Notation (LAN) given a board state.
+    ///
+    /// LAN always spells out both squares, with an optional piece letter and either
+    /// `-` or `x` between them. The squares are handed to `from_uci`, and the piece
+    /// letter and separator are then checked against the board.
+    ///
+    /// # Arguments
+    /// * `board` - The board state from which the move is made.
+    /// * `lan` - The LAN string (e.g., "e2-e4", "Ng1-f3", "Bb5xc6+", "e7-e8=Q", "O-O").
+    pub fn from_lan(board: &Board, lan: &str) -> miette::Result<Move> {
+        let clean_lan = lan.trim_end_matches(['+', '#']);
+
+        // Castling is written the same way as in SAN
+        if clean_lan == "O-O" || clean_lan == "O-O-O" {
+            return Move::from_san(board, clean_lan);
+        }
+
+        let (piece, rest) = match clean_lan.chars().next() {
+            Some('K') => (Piece::King, &clean_lan[1..]),
+            Some('Q') => (Piece::Queen, &clean_lan[1..]),
+            Some('R') => (Piece::Rook, &clean_lan[1..]),
+            Some('B') => (Piece::Bishop, &clean_lan[1..]),
+            Some('N') => (Piece::Knight, &clean_lan[1..]),
+            _ => (Piece::Pawn, clean_lan),
+        };
+
+        if rest.len() < 5 || !rest.is_char_boundary(2) || !rest.is_char_boundary(3) {
+            miette::bail!("Invalid LAN move format: '{}'", lan);
+        }
+        let (from, separator, rest) = (&rest[0..2], &rest[2..3], &rest[3..]);
+        let is_capture = match separator {
+            "-" => false,
+            "x" => true,
+            _ => miette::bail!("Expected '-' or 'x' between squares in LAN move '{}'", lan),
+        };
+        // Promotion may be written as `e7-e8=Q` or `e7-e8Q`
+        let uci = format!("{from}{}", rest.replace('=', ""));
+
+        let m = Move::from_uci(board, &uci).into_diagnostic()?;
+
+        let moved = board
+            .get_piece_at(m.from_sq())
+            .context(format!("No piece on {} in LAN move '{}'", from, lan))?;
+        if moved != piece {
+            miette::bail!(
+                "LAN move '{}' names a {} but {} holds a {}",
+                lan,
+                piece,
+                from,
+                moved
+            );
+        }
+        if m.is_capture() != is_capture {
+            miette::bail!(
+                "LAN move '{}' uses '{}' but the move is {}a capture",
+                lan,
+                separator,
+                if m.is_capture() { "" } else { "not " }
+            );
+        }
+        Ok(m)
+    }
+
+    /// Formats the move in Long Algebraic Notation (e.g. "Ng1-f3", "e4xd5", "e7-e8=Q").
+    /// `board` must be the position before the move is made.
+    pub fn to_lan(&self, board: &Board) -> String {
+        if self.is_castling() {
+            return if self.to_sq().col() > self.from_sq().col() {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            };
+        }
+
+        let piece = match board.get_piece_at(self.from_sq()) {
+            Some(Piece::Pawn) | None => String::new(),
+            Some(p) => Piece::PIECE_CHARS[Side::White.index()][p.index()].to_string(),
+        };
+        let separator = if self.is_capture() { 'x' } else { '-' };
+        let promo = match self.promoted_piece() {
+            Some(p) => format!("={}", Piece::PIECE_CHARS[Side::White.index()][p.index()]),
+            None => String::new(),
+        };
+        format!(
+            "{piece}{}{separator}{}{promo}",
+            Move::square_to_coord(self.from_idx()),
+            Move::square_to_coord(self.to_idx())
+        )
+    }
+
+    /// Formats the move in Standard Algebraic Notation (e.g. "Nf3", "exd5", "Rad1", "e8=Q+").
+    /// `board` must be the position before the move is made.
+    ///
+    /// Disambiguation only considers *legal* moves, so a second piece that could reach
+    /// the target square but is pinned doesn't add a file or rank, as PGN requires.
+    pub fn to_san(&self, board: &Board) -> String {
+        let mut san = if self.is_castling() {
+            if self.to_sq().col() > self.from_sq().col() {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            }
+        } else {
+            let piece = board.get_piece_at(self.from_sq()).unwrap_or_default();
+            let from = Move::square_to_coord(self.from_idx());
+            let (from_file, from_rank) = from.split_at(1);
+            let mut san = String::new();
+
+            if piece == Piece::Pawn {
+                if self.is_capture() {
+                    san.push_str(from_file);
+                }
+            } else {
+                san.push(Piece::PIECE_CHARS[Side::White.index()][piece.index()]);
+
+                let mut legal_moves = MoveBuffer::new();
+                board.generate_legal_moves(&mut legal_moves, false);
+                let rivals: Vec<Square> = legal_moves
+                    .iter()
+                    .filter(|m| {
+                        m.to_idx() == self.to_idx()
+                            && m.from_idx() != self.from_idx()
+                            && board.get_piece_at(m.from_sq()) == Some(piece)
+                    })
+                    .map(|m| m.from_sq())
+                    .collect();
+
+                if !rivals.is_empty() {
+                    let from_sq = self.from_sq();
+                    if rivals.iter().all(|sq| sq.col() != from_sq.col()) {
+                        san.push_str(from_file);
+                    } else if rivals.iter().all(|sq| sq.row() != from_sq.row()) {
+                        san.push_str(from_rank);
+                    } else {
+                        san.push_str(&from);
+                    }
+                }
+            }
+
+            if self.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&Move::square_to_coord(self.to_idx()));
+            if let Some(promo) = self.promoted_piece() {
+                san.push('=');
+                san.push(Piece::PIECE_CHARS[Side::White.index()][promo.index()]);
+            }
+            san
+        };
+
+        let mut after = *board;
+        if after.make_move(*self).is_ok() && after.is_in_check(after.stm) {
+            let mut replies = MoveBuffer::new();
+            after.generate_legal_moves(&mut replies, false);
+            san.push(if replies.is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+
     /// Creates a Move from Universal Chess Interface (UCI) notation.
     ///
     /// This is the most efficient implementation. It constructs the move with the
@@ -244,32 +417,42 @@ impl Move {
     /// # Arguments
     /// * `board` - The board state from which the move is made.
     /// * `uci` - The UCI string (e.g., "e2e4", "g1f3", "a7a8q").
-    pub fn from_uci(board: &Board, uci: &str) -> miette::Result<Move> {
-        if uci.len() < 4 || uci.len() > 5 {
-            miette::bail!("Invalid UCI move format: '{}'", uci);
+    pub fn from_uci(board: &Board, uci: &str) -> Result<Move, EschecError> {
+        let parse_error = |reason: String| EschecError::ParseError {
+            input: uci.to_string(),
+            reason,
+        };
+        let illegal = |reason: String| EschecError::IllegalMove {
+            mv: uci.to_string(),
+            reason,
+        };
+
+        if uci.len() < 4 || uci.len() > 5 || !uci.is_ascii() {
+            return Err(parse_error("Invalid UCI move format".to_string()));
         }
 
-        let from = Square::from_str(&uci[0..2])?;
-        let to = Square::from_str(&uci[2..4])?;
-        let promo_char = uci.chars().nth(4);
+        let square = |s: &str| Square::from_str(s).map_err(|e| parse_error(e.to_string()));
+        let from = square(&uci[0..2])?;
+        let to = square(&uci[2..4])?;
+        let promo_piece = match uci.chars().nth(4).map(|c| c.to_ascii_lowercase()) {
+            Some('q') => Some(Piece::Queen),
+            Some('r') => Some(Piece::Rook),
+            Some('b') => Some(Piece::Bishop),
+            Some('n') => Some(Piece::Knight),
+            None => None,
+            Some(c) => return Err(parse_error(format!("Invalid promotion piece '{c}'"))),
+        };
+
+        let piece = board
+            .get_piece_at(from)
+            .ok_or_else(|| illegal(format!("No piece at the 'from' square '{from}'")))?;
 
-        let piece = board.get_piece_at(from).context(format!(
-            "No piece at the 'from' square '{}' in UCI move '{}'",
-            from, uci
-        ))?;
+        let promo_rank = if board.stm == Side::White { 7 } else { 0 };
+        let is_promoting = piece == Piece::Pawn && to.row() == promo_rank;
 
         let mut flags = Move::QUIET;
-        if piece == Piece::Pawn {
-            let promo_rank = if board.stm == Side::White { 7 } else { 0 };
-            if to.row() == promo_rank {
-                let promotion_piece = match promo_char {
-                    Some('q') => Piece::Queen,
-                    Some('r') => Piece::Rook,
-                    Some('b') => Piece::Bishop,
-                    Some('n') => Piece::Knight,
-                    None => miette::bail!("Promotion move '{}' is missing a promotion piece", uci),
-                    _ => miette::bail!("Invalid promotion piece in UCI move '{}'", uci),
-                };
+        match (is_promoting, promo_piece) {
+            (true, Some(promotion_piece)) => {
                 let is_capture = board.get_piece_at(to).is_some();
                 flags = match (promotion_piece, is_capture) {
                     (Piece::Queen, true) => Move::PROMO_QC,
@@ -282,12 +465,18 @@ impl Move {
                     (Piece::Knight, false) => Move::PROMO_N,
                     _ => unreachable!(),
                 };
-            } else if promo_char.is_some_and(|c| ['q', 'r', 'b', 'n'].contains(&c)) {
-                miette::bail!(
-                    "Invalid promotion rank (not 2nd or 7th rank) in UCI move: {}",
-                    to.rank()
-                );
             }
+            (true, None) => {
+                return Err(illegal(
+                    "Promotion move is missing a promotion piece".to_string(),
+                ));
+            }
+            (false, Some(_)) => {
+                return Err(illegal(format!(
+                    "Promotion piece given for non-promoting move ({piece} from {from} to {to})"
+                )));
+            }
+            (false, None) => {}
         }
         if flags == Move::QUIET {
             if piece == Piece::King {
@@ -326,9 +515,8 @@ impl Move {
             }
         }
 
-        Err(miette::miette!(
-            "The move '{}' is not legal in the current position.",
-            uci
+        Err(illegal(
+            "The move is not legal in the current position".to_string(),
         ))
     }
 }
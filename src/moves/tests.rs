@@ -74,6 +74,37 @@ fn test_from_uci_illegal_move_leaves_king_in_check() {
     assert!(result.is_err(), "Should not allow moving a pinned piece");
 }
 
+#[test]
+fn test_from_uci_uppercase_promotion() {
+    let board = Board::from_fen("rnbq1bnr/pppkPppp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQ - 1 5");
+    let mov = Move::from_uci(&board, "e7d8Q").unwrap();
+    assert_eq!(mov.flags(), Move::PROMO_QC);
+
+    let mov = Move::from_uci(&board, "e7f8N").unwrap();
+    assert_eq!(mov.flags(), Move::PROMO_NC);
+    assert_eq!(mov.uci(), "e7f8n");
+}
+
+#[test]
+fn test_from_uci_promo_char_on_non_promoting_move() {
+    let board = Board::new();
+    let err = Move::from_uci(&board, "g1f3q").unwrap_err();
+    assert!(err.to_string().contains("non-promoting"), "{err}");
+
+    let err = Move::from_uci(&board, "e2e4q").unwrap_err();
+    assert!(err.to_string().contains("non-promoting"), "{err}");
+}
+
+#[test]
+fn test_from_uci_promotion_missing_promo_char() {
+    let board = Board::from_fen("rnbq1bnr/pppkPppp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQ - 1 5");
+    let err = Move::from_uci(&board, "e7d8").unwrap_err();
+    assert!(
+        err.to_string().contains("missing a promotion piece"),
+        "{err}"
+    );
+}
+
 #[test]
 fn test_from_uci_invalid_format() {
     let board = Board::new();
@@ -82,6 +113,71 @@ fn test_from_uci_invalid_format() {
     assert!(Move::from_uci(&board, "e2e4q").is_err()); // Valid length but not a promotion
 }
 
+#[test]
+fn test_null_move() {
+    assert_eq!(Move::NULL.uci(), "0000");
+    assert!(Move::NULL.is_null());
+    assert_eq!(Move::default(), Move::NULL);
+
+    // a1a1 never shows up among legal moves
+    for fen in [START_FEN, KIWIPETE] {
+        let mut moves = MoveBuffer::new();
+        Board::from_fen(fen).generate_legal_moves(&mut moves, false);
+        assert!(moves.iter().all(|m| !m.is_null()));
+    }
+    assert!(!Move::new(0, 8, Move::QUIET).is_null());
+    assert!(!Move::new(0, 0, Move::CAPTURE).is_null());
+}
+
+fn assert_lan_round_trip(fen: &str, lan: &str, uci: &str) {
+    let board = Board::from_fen(fen);
+    let mov = Move::from_lan(&board, lan).unwrap();
+    assert_eq!(mov.uci(), uci);
+    assert_eq!(mov.to_lan(&board), lan);
+}
+
+#[test]
+fn test_lan_round_trip_quiet_moves() {
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_lan_round_trip(fen, "e2-e4", "e2e4");
+    assert_lan_round_trip(fen, "Ng1-f3", "g1f3");
+}
+
+#[test]
+fn test_lan_round_trip_captures() {
+    let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
+    assert_lan_round_trip(fen, "e4xd5", "e4d5");
+    let fen = "rnbqkbnr/pp1p1ppp/8/2pPp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3";
+    assert_lan_round_trip(fen, "d5xe6", "d5e6");
+    let fen = "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 2 3";
+    assert_lan_round_trip(fen, "Bb5xc6", "b5c6");
+}
+
+#[test]
+fn test_lan_round_trip_castling() {
+    let fen = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1";
+    assert_lan_round_trip(fen, "O-O", "e1g1");
+    assert_lan_round_trip(fen, "O-O-O", "e1c1");
+}
+
+#[test]
+fn test_lan_round_trip_promotions() {
+    let fen = "rnbq1bnr/pppkPppp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQ - 1 5";
+    assert_lan_round_trip(fen, "e7xd8=Q", "e7d8q");
+    assert_lan_round_trip(fen, "e7xf8=N", "e7f8n");
+
+    let board = Board::from_fen(fen);
+    assert_eq!(Move::from_lan(&board, "e7xd8Q").unwrap().uci(), "e7d8q");
+}
+
+#[test]
+fn test_from_lan_rejects_mismatches() {
+    let board = Board::new();
+    assert!(Move::from_lan(&board, "Bg1-f3").is_err()); // Wrong piece letter
+    assert!(Move::from_lan(&board, "e2xe4").is_err()); // Not a capture
+    assert!(Move::from_lan(&board, "e2e4").is_err()); // Missing separator
+}
+
 #[test]
 fn test_from_san_simple_pawn_move() {
     let board = Board::new();
@@ -146,6 +242,70 @@ fn test_from_san_disambiguation_rank() {
     assert_eq!(mov2.uci(), "a8a5");
 }
 
+#[test]
+fn test_to_san_basic() {
+    let board = Board::new();
+    assert_eq!(Move::from_uci(&board, "e2e4").unwrap().to_san(&board), "e4");
+    assert_eq!(
+        Move::from_uci(&board, "g1f3").unwrap().to_san(&board),
+        "Nf3"
+    );
+
+    let board = Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(
+        Move::from_uci(&board, "e4d5").unwrap().to_san(&board),
+        "exd5"
+    );
+
+    let board = Board::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1");
+    assert_eq!(
+        Move::from_uci(&board, "e1c1").unwrap().to_san(&board),
+        "O-O-O"
+    );
+
+    // Promotion with capture and mate
+    let board = Board::from_fen("rnbq1bnr/pppkPppp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQ - 1 5");
+    assert_eq!(
+        Move::from_uci(&board, "e7d8q").unwrap().to_san(&board),
+        "exd8=Q+"
+    );
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    assert_eq!(
+        Move::from_uci(&board, "a1a8").unwrap().to_san(&board),
+        "Ra8#"
+    );
+}
+
+#[test]
+fn test_san_disambiguation_ignores_pinned_pieces() {
+    // Both rooks can reach d2, but the e2 rook is pinned to the king
+    let board = Board::from_fen("4r2k/8/8/8/8/8/R3R3/4K3 w - - 0 1");
+    let mov = Move::from_uci(&board, "a2d2").unwrap();
+    assert_eq!(mov.to_san(&board), "Rd2");
+    assert_eq!(Move::from_san(&board, "Rd2").unwrap(), mov);
+
+    // Same with the pin removed needs the file
+    let board = Board::from_fen("7k/8/8/8/8/8/R3R3/4K3 w - - 0 1");
+    let mov = Move::from_uci(&board, "a2d2").unwrap();
+    assert_eq!(mov.to_san(&board), "Rad2");
+    assert!(Move::from_san(&board, "Rd2").is_err());
+
+    // Both knights can reach e4, but the g3 knight is pinned by the bishop
+    let board = Board::from_fen("k7/8/8/4b3/8/2N3N1/7K/8 w - - 0 1");
+    let mov = Move::from_uci(&board, "c3e4").unwrap();
+    assert_eq!(mov.to_san(&board), "Ne4");
+    assert_eq!(Move::from_san(&board, "Ne4").unwrap(), mov);
+
+    let board = Board::from_fen("k7/8/8/8/8/2N3N1/7K/8 w - - 0 1");
+    let mov = Move::from_uci(&board, "c3e4").unwrap();
+    assert_eq!(mov.to_san(&board), "Nce4");
+
+    // Same file, so the rank is used instead
+    let board = Board::from_fen("k7/8/8/8/3R4/8/3R4/7K w - - 0 1");
+    let mov = Move::from_uci(&board, "d2d3").unwrap();
+    assert_eq!(mov.to_san(&board), "R2d3");
+}
+
 #[test]
 fn test_from_san_illegal_move() {
     let board = Board::new();
@@ -317,8 +477,9 @@ fn test_make_unmake_symmetry(fen: &str) {
         assert_eq!(
             board,
             original_board,
-            "Board state was not restored after unmaking move {} on FEN {fen}",
-            mov.uci()
+            "Board state was not restored after unmaking move {} on FEN {fen}\n{}",
+            mov.uci(),
+            board.diff(&original_board)
         );
     }
 }
@@ -383,6 +544,8 @@ fn get_stockfish_legal_moves(fen: &str) -> Vec<String> {
 fn assert_moves_match_stockfish(fen: &str) {
     // 1. Generate and sort moves with our engine
     let board = Board::from_fen(fen);
+    // Cheap secondary check that doesn't need Stockfish
+    board.assert_legal_moves_match_reference();
     let mut legal_moves = MoveBuffer::new();
     board.generate_legal_moves(&mut legal_moves, false);
     let mut our_moves: Vec<String> = legal_moves.into_iter().map(|m| m.uci()).collect();
@@ -398,6 +561,89 @@ fn assert_moves_match_stockfish(fen: &str) {
     );
 }
 
+#[test]
+fn test_legal_moves_match_reference() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppppp1pp/8/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1",
+        "r3k2r/pp1ppppp/8/2b5/8/8/PPP1PPPP/R3K2R w KQkq - 0 1",
+        "rnb1kbnr/pppp1ppp/8/8/3r4/3B4/PPP1PPPP/RN1QK1NR w KQkq - 0 5",
+        // En passant capture that would expose the king along the rank
+        "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+    ];
+    for fen in fens {
+        let board = Board::from_fen(fen);
+        board.assert_legal_moves_match_reference();
+
+        // One ply deeper to cover more positions
+        let mut moves = MoveBuffer::new();
+        board.generate_legal_moves(&mut moves, false);
+        for m in moves {
+            let mut child = board;
+            child.make_move(m).unwrap();
+            child.assert_legal_moves_match_reference();
+        }
+    }
+}
+
+/// En passant positions where a pin decides legality. Expected moves are Stockfish's `go perft 1`
+#[test]
+fn test_en_passant_pin_edge_cases() {
+    let cases: [(&str, &[&str]); 5] = [
+        // Both pawns leave the rank, exposing the king to the rook
+        (
+            "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+            &["a5a4", "a5a6", "a5b4", "a5b5", "a5b6", "e5e6"],
+        ),
+        (
+            "7K/8/8/8/R2Pp2k/8/8/8 b - d3 0 1",
+            &["e4e3", "h4g3", "h4g4", "h4g5", "h4h3", "h4h5"],
+        ),
+        // Capturer is pinned on a diagonal and captures towards its king, staying on the pin
+        (
+            "k7/6K1/8/4Pp2/8/2b5/8/8 w - f6 0 1",
+            &[
+                "e5f6", "g7f6", "g7f7", "g7f8", "g7g6", "g7g8", "g7h6", "g7h7", "g7h8",
+            ],
+        ),
+        // Pinned on the file with its king in front, the push stays between king and rook
+        (
+            "k7/8/4K3/8/4P3/8/8/4r3 w - - 0 1",
+            &[
+                "e4e5", "e6d5", "e6d6", "e6d7", "e6e5", "e6e7", "e6f5", "e6f6", "e6f7",
+            ],
+        ),
+        // Capturing en passant removes the pawn giving check
+        (
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            &[
+                "c5b4", "c5b5", "c5b6", "c5c4", "c5c6", "c5d4", "c5d5", "c5d6", "e4d3",
+            ],
+        ),
+    ];
+
+    for (fen, expected) in cases {
+        let board = Board::from_fen(fen);
+        board.assert_legal_moves_match_reference();
+
+        let mut moves = MoveBuffer::new();
+        board.generate_legal_moves(&mut moves, false);
+        let mut ours: Vec<String> = moves.into_iter().map(|m| m.uci()).collect();
+        ours.sort_unstable();
+        assert_eq!(ours, expected, "FEN: {fen}");
+        assert_eq!(
+            move_gen::count_legal_moves(&board),
+            expected.len(),
+            "FEN: {fen}"
+        );
+    }
+}
+
 #[test]
 fn test_make_unmake_startpos() {
     test_make_unmake_symmetry("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
@@ -522,3 +768,68 @@ fn test_stalemate_position() {
 fn test_checkmate_position() {
     assert_moves_match_stockfish("5rk1/p4ppp/8/1p1p4/3P3q/1P2r3/P5PP/2R2Q1K b - - 1 27");
 }
+
+#[test]
+fn test_sorted_generation_puts_captures_first() {
+    for fen in [
+        KIWIPETE,
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ] {
+        let board = Board::from_fen(fen);
+        let mut sorted = MoveBuffer::new();
+        move_gen::generate_legal_moves_sorted(&board, &mut sorted);
+
+        // Same moves as the default generator
+        let mut unsorted = MoveBuffer::new();
+        board.generate_legal_moves(&mut unsorted, false);
+        let mut sorted_copy = sorted.clone();
+        unsorted.as_mut_slice().sort_unstable_by_key(|m| m.uci());
+        sorted_copy.as_mut_slice().sort_unstable_by_key(|m| m.uci());
+        assert_eq!(unsorted.as_slice(), sorted_copy.as_slice(), "{fen}");
+
+        let first_quiet = sorted
+            .iter()
+            .position(|m| !m.is_capture() && !m.is_promotion())
+            .unwrap();
+        assert!(
+            sorted.iter().take(first_quiet).any(|m| m.is_capture()),
+            "{fen}: {:?}",
+            sorted.iter().map(|m| m.uci()).collect::<Vec<_>>()
+        );
+        assert!(
+            sorted
+                .iter()
+                .skip(first_quiet)
+                .all(|m| !m.is_capture() && !m.is_promotion()),
+            "{fen}: {:?}",
+            sorted.iter().map(|m| m.uci()).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn test_legal_castling_rejects_attacked_transit_square() {
+    let castles = |moves: &MoveBuffer| -> Vec<String> {
+        moves
+            .iter()
+            .filter(|m| m.is_castling())
+            .map(|m| m.uci())
+            .collect()
+    };
+
+    // The rook on f8 covers f1, which the king has to pass through
+    let board = Board::from_fen("4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    let mut pseudo = MoveBuffer::new();
+    board.generate_pseudo_legal_moves(&mut pseudo, None);
+    assert_eq!(castles(&pseudo), ["e1g1", "e1c1"]);
+
+    let mut legal = MoveBuffer::new();
+    move_gen::generate_legal_castling(&board, &mut legal);
+    assert_eq!(castles(&legal), ["e1c1"]);
+
+    // No castling out of check
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K1rR w KQ - 0 1");
+    let mut legal = MoveBuffer::new();
+    move_gen::generate_legal_castling(&board, &mut legal);
+    assert!(legal.is_empty());
+}
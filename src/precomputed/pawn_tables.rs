@@ -27,11 +27,6 @@ pub struct PawnTables {
     /// *Index: \[Side\]\[pawn_square\] -> BitBoard of 2 squares*
     pub pawn_backward_support_masks: [[BitBoard; 64]; 2],
 
-    /// For connected pawns: contains twon squares on adjacent files on the same rank
-    ///
-    /// *Index: \[pawn_square\] -> BitBoard of 2 neighbours*
-    pub connected_pawn_masks: [BitBoard; 64],
-
     /// For King Safety evaluation: the full 4x3 (or smaller near edges)
     /// zone of squares around the king, including two ranks "in front".
     /// This is the area enemy pieces attack.
@@ -52,7 +47,6 @@ impl Default for PawnTables {
             king_shield_zone_masks: [[BitBoard(0); 64]; 2],
             pawn_front_square_masks: [[BitBoard(0); 64]; 2],
             pawn_backward_support_masks: [[BitBoard(0); 64]; 2],
-            connected_pawn_masks: [BitBoard(0); 64],
             king_attack_zone_masks: [[BitBoard(0); 64]; 2],
         }
     }
@@ -66,7 +60,6 @@ impl PawnTables {
             king_shield_zone_masks: [[BitBoard(0); 64]; 2],
             pawn_front_square_masks: [[BitBoard(0); 64]; 2],
             pawn_backward_support_masks: [[BitBoard(0); 64]; 2],
-            connected_pawn_masks: [BitBoard(0); 64],
             king_attack_zone_masks: [[BitBoard(0); 64]; 2],
         };
         tables.init_adjacent_file_masks();
@@ -186,14 +179,6 @@ impl PawnTables {
             }
             self.pawn_backward_support_masks[Side::Black.index()][sq_idx] = south_support_mask;
 
-            let mut neighbour_mask = BitBoard(0);
-            if file > 0 {
-                neighbour_mask.set(rank * 8 + (file - 1));
-            }
-            if file < 7 {
-                neighbour_mask.set(rank * 8 + (file + 1));
-            }
-            self.connected_pawn_masks[sq_idx] = neighbour_mask;
             sq_idx += 1;
         }
     }
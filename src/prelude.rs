@@ -9,6 +9,7 @@ pub use crate::board::{
 };
 pub use crate::comms::*;
 pub use crate::consts::*;
+pub use crate::error::EschecError;
 pub use crate::evaluation::{
     self, evaluate,
     score::{Phase, Score},
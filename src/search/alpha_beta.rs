@@ -3,31 +3,73 @@
 //! - Late Move Reduction
 //! - Null Move Pruning
 //! - Aspiration Windows
+//! - Fractional Extensions, bounded per branch
 
 use std::cmp::{max, min};
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::{Duration, Instant};
+use std::time::Duration;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
 
+// `std::time::Instant` panics on wasm32-unknown-unknown, this one reads `performance.now()`
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+use serde::Serialize;
 use tracing::trace_span;
 
+use crate::evaluation::{
+    evaluate_cached, material::evaluate_material, pawn_hash::PawnHashTable, pst::IncrementalScore,
+};
 use crate::moves::move_gen::{AllMoves, CapturesOnly, generate_legal_moves};
 use crate::prelude::*;
-use crate::search::move_ordering::{MainSearchPolicy, MoveScoringPolicy, sort_moves};
+use crate::search::move_ordering::{HistoryTable, MainSearchPolicy, MoveScoringPolicy, sort_moves};
 use crate::search::move_picker::MovePicker;
+use crate::search::tree_dump::SearchTree;
 use crate::search::tt::{ScoreTypes, TranspositionEntry, TranspositionTable};
 use crate::search::{SearchEngine, SearchResult, SearchStats, common::*};
 use crate::tuning::params::TunableParams;
 
 /// Consts
-const ASP_START_WINDOW: i32 = 48;
-const ASP_MAX_WINDOW: i32 = 4096;
 const HISTORY_SIZE: usize = 512;
-const DELTA_MARGIN: i32 = 700;
-const SEE_THRESHOLD: i32 = -100;
-const CONTEMPT_SCORE: i32 = 0;
-const SE_DEPTH: u16 = 8;
-const SE_MARGIN: i32 = 2;
+
+/// Failed aspiration windows before the failing side is opened all the way
+const ASP_MAX_TRIES: usize = 4;
+
+/// Extensions are counted in fractions of a ply
+pub const ONE_PLY: u16 = 4;
+const CHECK_EXT: u16 = ONE_PLY;
+const SINGULAR_EXT: u16 = ONE_PLY;
+const PAWN_PUSH_EXT: u16 = ONE_PLY / 2;
+const RECAPTURE_EXT: u16 = ONE_PLY / 2;
+/// Max extension applied to a single move
+const MAX_NODE_EXT: u16 = ONE_PLY;
+/// Max total extension along a single branch, from the root
+pub const EXT_BUDGET: u16 = 8 * ONE_PLY;
+
+/// Returned by the root search when the stop condition fired before an iteration
+/// (or aspiration attempt) finished. Scores below the root are unreliable once
+/// the search is stopped, so the whole iteration is discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchAborted;
+
+/// Best move and score of a fully searched root iteration
+type RootResult = Result<(Option<Move>, i32), SearchAborted>;
+
+/// Per-depth info emitted when `SearchConfig::info_format` is `InfoFormat::Json`
+#[derive(Debug, Serialize)]
+struct InfoLine {
+    depth: u16,
+    score: i32,
+    nodes: u64,
+    nps: u64,
+    /// "lowerbound" or "upperbound" if `score` comes from a failed aspiration window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bound: Option<&'static str>,
+    pv: Vec<String>,
+}
 
 /// Holds pv_node and curr ply
 #[derive(Clone, Copy)]
@@ -36,6 +78,12 @@ pub struct SearchContext {
     is_pv_node: bool,
     /// If set, this move will be skipped during move generation/looping
     excluded_move: Option<Move>,
+    /// Move that led to this node, `None` at the root and after a null move
+    prev_move: Option<Move>,
+    /// Total extension applied along this branch, in fractions of `ONE_PLY`
+    extension: u16,
+    /// Fractional extension that hasn't yet added up to a full ply
+    ext_carry: u16,
 }
 
 impl SearchContext {
@@ -44,6 +92,9 @@ impl SearchContext {
             ply: 0,
             is_pv_node: true,
             excluded_move: None,
+            prev_move: None,
+            extension: 0,
+            ext_carry: 0,
         }
     }
 
@@ -52,22 +103,36 @@ impl SearchContext {
             ply: self.ply + 1,
             is_pv_node: is_pv_child,
             excluded_move: None,
+            prev_move: None,
+            extension: self.extension,
+            ext_carry: self.ext_carry,
         }
     }
+
+    /// Applies `ext` (in fractions of `ONE_PLY`) to this context, capped by
+    /// the remaining `EXT_BUDGET`. Returns the number of whole plies to extend by
+    pub fn extend(&mut self, ext: u16) -> u16 {
+        let ext = ext.min(EXT_BUDGET - self.extension);
+        self.extension += ext;
+
+        let total = self.ext_carry + ext;
+        self.ext_carry = total % ONE_PLY;
+        total / ONE_PLY
+    }
 }
 
 /// Struct that holds relatively large tables
 #[derive(Debug)]
 pub struct SearchTables {
     killer_moves: [[Option<Move>; 2]; MAX_PLY],
-    history: [[i32; NUM_SQUARES]; NUM_SQUARES],
+    history: HistoryTable,
 }
 
 impl Default for SearchTables {
     fn default() -> Self {
         Self {
             killer_moves: [[None; 2]; MAX_PLY],
-            history: [[0; NUM_SQUARES]; NUM_SQUARES],
+            history: HistoryTable::default(),
         }
     }
 }
@@ -76,7 +141,7 @@ impl SearchTables {
     fn new() -> Self {
         Self {
             killer_moves: [[None; 2]; MAX_PLY],
-            history: [[0; NUM_SQUARES]; NUM_SQUARES],
+            history: HistoryTable::default(),
         }
     }
 
@@ -96,22 +161,23 @@ impl SearchTables {
         }
     }
 
-    /// Update `history` table.
-    /// Indexes as `history[mv.from][mv.to]`
+    /// Update `history` table, indexed as set by `SearchConfig::history_indexing`.
+    /// `board` is the position `mv` was played from.
     /// Scores are stored as `depth ^ 2`
-    fn update_history(&mut self, mv: Move, depth: u16) {
-        let from = mv.from_idx() as usize;
-        let to = mv.to_idx() as usize;
-        self.history[from][to] += depth as i32 * depth as i32;
+    fn update_history(&mut self, board: &Board, mv: Move, depth: u16) {
+        self.history.add(board, mv, depth as i32 * depth as i32);
+    }
+
+    /// Rewards a capture that caused a beta cutoff in the capture history.
+    /// `board` is the position `mv` was played from
+    fn update_capture_history(&mut self, board: &Board, mv: Move, depth: u16) {
+        self.history
+            .add_capture(board, mv, depth as i32 * depth as i32);
     }
 
     /// Decay's all elements in `history` by dividing it by 2
     fn decay_history(&mut self) {
-        for from in 0..64 {
-            for to in 0..64 {
-                self.history[from][to] /= 2;
-            }
-        }
+        self.history.decay();
     }
 }
 
@@ -180,14 +246,26 @@ pub struct AlphaBetaSearch {
     search_cycle: u8,
     /// Search params
     config: SearchConfig,
+    params: SearchParams,
     limits: SearchLimits,
+    /// Set when playing at reduced strength
+    strength: Option<StrengthLimit>,
+    /// Side to move at the root, contempt is applied from its point of view
+    root_stm: Side,
     /// External deps
     eval_params: TunableParams,
+    /// PST and material scores valued with `eval_params`, one per position along the
+    /// current line. Empty outside of `find_best_move`
+    incremental: Vec<IncrementalScore>,
     search_running: Option<Arc<AtomicBool>>,
+    /// Best move of the last completed depth, readable while searching
+    shared_best_move: Option<Arc<SharedBestMove>>,
     /// Move ordering & history
     search_tables: Box<SearchTables>,
     /// Transposition table
     tt: TranspositionTable,
+    /// Cached pawn structure scores, `None` if disabled
+    pawn_table: Option<PawnHashTable>,
     /// Repetition detection
     pub repetition_table: RepetitionTable,
     /// Status
@@ -195,6 +273,8 @@ pub struct AlphaBetaSearch {
     start_time: Instant,
     /// Debug/tuning
     stats: SearchStats,
+    /// Last root iteration's tree, if `SearchConfig::dump_tree` is set
+    search_tree: Option<SearchTree>,
 }
 
 impl Default for AlphaBetaSearch {
@@ -203,15 +283,22 @@ impl Default for AlphaBetaSearch {
             nodes_searched: Default::default(),
             search_cycle: Default::default(),
             config: Default::default(),
+            params: Default::default(),
             limits: Default::default(),
+            strength: None,
+            root_stm: Side::White,
             eval_params: TunableParams::default(),
+            incremental: Vec::with_capacity(MAX_PLY),
             search_running: Default::default(),
+            shared_best_move: None,
             search_tables: Default::default(),
             tt: Default::default(),
+            pawn_table: Some(PawnHashTable::default()),
             repetition_table: Default::default(),
             in_progress: Default::default(),
             start_time: Instant::now(),
             stats: SearchStats::default(),
+            search_tree: None,
         }
     }
 }
@@ -220,34 +307,48 @@ impl AlphaBetaSearch {
     pub fn new() -> Self {
         Self {
             config: SearchConfig::default(),
+            params: SearchParams::default(),
             limits: SearchLimits::default(),
+            strength: None,
+            root_stm: Side::White,
             nodes_searched: 0,
             search_cycle: 0,
             start_time: Instant::now(),
             in_progress: false,
             eval_params: TunableParams::default(),
+            incremental: Vec::with_capacity(MAX_PLY),
             tt: TranspositionTable::new(16),
+            pawn_table: Some(PawnHashTable::default()),
             search_tables: Box::new(SearchTables::new()),
             repetition_table: RepetitionTable::new(),
             search_running: None,
+            shared_best_move: None,
             stats: SearchStats::new(),
+            search_tree: None,
         }
     }
 
     pub fn with_eval(params: TunableParams) -> Self {
         Self {
             config: SearchConfig::default(),
+            params: SearchParams::default(),
             limits: SearchLimits::default(),
+            strength: None,
+            root_stm: Side::White,
             nodes_searched: 0,
             search_cycle: 0,
             start_time: Instant::now(),
             in_progress: false,
+            incremental: Vec::with_capacity(MAX_PLY),
             eval_params: params,
             tt: TranspositionTable::new(16),
+            pawn_table: Some(PawnHashTable::default()),
             search_tables: Box::new(SearchTables::new()),
             repetition_table: RepetitionTable::new(),
             search_running: None,
+            shared_best_move: None,
             stats: SearchStats::new(),
+            search_tree: None,
         }
     }
 
@@ -255,7 +356,20 @@ impl AlphaBetaSearch {
     /// enable ASP, disable NMP, etc.
     pub fn with_config(mut self, config: SearchConfig) -> miette::Result<Self> {
         if self.config.hash_size_mb != config.hash_size_mb {
-            self.tt.change_size(config.hash_size_mb)?;
+            if config.preserve_hash_on_resize {
+                self.tt.resize_preserving(config.hash_size_mb)?;
+            } else {
+                self.tt.change_size(config.hash_size_mb)?;
+            }
+        }
+        self.tt.set_replacement(config.tt_replacement);
+        self.tt.set_index_bits(config.tt_index_bits);
+        if self.search_tables.history.indexing() != config.history_indexing {
+            self.search_tables.history = HistoryTable::new(config.history_indexing);
+        }
+        if self.config.pawn_hash_size_kb != config.pawn_hash_size_kb {
+            self.pawn_table = (config.pawn_hash_size_kb > 0)
+                .then(|| PawnHashTable::new(config.pawn_hash_size_kb));
         }
         self.config = config;
         Ok(self)
@@ -266,15 +380,93 @@ impl AlphaBetaSearch {
         self.limits = limits;
         self
     }
+
+    /// Constructor to override pruning/reduction thresholds
+    pub fn with_params(mut self, params: SearchParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn get_search_params(&self) -> SearchParams {
+        self.params
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    /// Play at reduced strength, or at full strength if `None`
+    pub fn set_strength(&mut self, strength: Option<StrengthLimit>) {
+        self.strength = strength;
+    }
+
+    pub fn get_strength(&self) -> Option<StrengthLimit> {
+        self.strength
+    }
+
+    pub fn set_info_format(&mut self, format: InfoFormat) {
+        self.config.info_format = format;
+    }
+
+    /// Changes the TT size in place, keeping the stored entries if
+    /// `SearchConfig::preserve_hash_on_resize` is set
+    pub fn resize_hash(&mut self, size_mb: usize) -> miette::Result<()> {
+        if self.config.preserve_hash_on_resize {
+            self.tt.resize_preserving(size_mb)?;
+        } else {
+            self.tt.change_size(size_mb)?;
+        }
+        self.config.hash_size_mb = size_mb;
+        Ok(())
+    }
+
+    /// Writes the TT to `path`, see `TranspositionTable::save`
+    pub fn save_hash(&self, path: &Path) -> miette::Result<()> {
+        self.tt.save(path)
+    }
+
+    /// Fills the TT from a file written by `save_hash`. Returns false if the file was
+    /// from an incompatible version and got ignored
+    pub fn load_hash(&mut self, path: &Path) -> miette::Result<bool> {
+        self.tt.load(path)
+    }
+
+    /// Sets the positions played before the root, oldest first, for repetition detection.
+    /// `keys` are their `Board::repetition_key`s, the root itself is added by the search
+    pub fn set_game_history(&mut self, keys: impl IntoIterator<Item = u64>) {
+        self.repetition_table.clear();
+        for key in keys {
+            self.repetition_table.push(key);
+        }
+    }
+
+    pub fn set_preserve_hash_on_resize(&mut self, preserve: bool) {
+        self.config.preserve_hash_on_resize = preserve;
+    }
+
+    /// See `SearchConfig::set_analyse_mode`
+    pub fn set_analyse_mode(&mut self, enabled: bool) {
+        self.config.set_analyse_mode(enabled);
+    }
+
+    pub fn get_config(&self) -> SearchConfig {
+        self.config
+    }
+
+    /// Tree of the last root iteration, if `SearchConfig::dump_tree` is set
+    pub fn search_tree(&self) -> Option<&SearchTree> {
+        self.search_tree.as_ref()
+    }
 }
 
 impl SearchEngine for AlphaBetaSearch {
     type Output = AlphaBetaSearch;
 
-    fn init(self, search_running: Arc<AtomicBool>) -> Self::Output {
+    fn init(self, search_running: Arc<AtomicBool>, best_move: Arc<SharedBestMove>) -> Self::Output {
         trace!("AlphaBeta: Initialized");
         Self {
             search_running: Some(search_running),
+            shared_best_move: Some(best_move),
             ..self
         }
     }
@@ -297,6 +489,9 @@ impl SearchEngine for AlphaBetaSearch {
 
     fn clear(&mut self) {
         self.tt.clear();
+        if let Some(table) = &mut self.pawn_table {
+            table.clear();
+        }
         self.search_cycle = 0;
         self.repetition_table.clear();
         self.search_tables.clear();
@@ -312,7 +507,7 @@ impl SearchEngine for AlphaBetaSearch {
     fn get_stats(&mut self) -> SearchStats {
         self.stats.nodes_searched = self.nodes_searched;
         self.stats.time_elapsed = self.start_time.elapsed();
-        self.stats.hash_full = self.tt.hash_full();
+        self.stats.hash_full = self.tt.hash_full(self.search_cycle);
         self.stats.calculate_nps();
         self.stats
     }
@@ -351,8 +546,16 @@ impl AlphaBetaSearch {
 
         self.prepare_for_search();
         self.start_time = Instant::now();
-        self.repetition_table.push(board.hash);
+        self.root_stm = board.stm;
+        self.repetition_table.push(board.repetition_key());
+        self.incremental.clear();
+        self.incremental
+            .push(IncrementalScore::compute(board, &self.eval_params));
         self.search_cycle = self.search_cycle.wrapping_add(1);
+        self.search_tree = self.config.dump_tree.map(SearchTree::new);
+        if let Some(slot) = &self.shared_best_move {
+            slot.clear();
+        }
 
         let mut legal_moves = MoveBuffer::new();
         board.generate_legal_moves(&mut legal_moves, false);
@@ -368,6 +571,7 @@ impl AlphaBetaSearch {
                 STALEMATE_SCORE
             };
             self.repetition_table.pop();
+            self.incremental.clear();
             self.finish();
             return SearchResult {
                 best_move: None,
@@ -390,41 +594,76 @@ impl AlphaBetaSearch {
 
         // Prev score for Aspiration Windows
         let mut prev_score = 0;
+        // Scores of the last two completed iterations, oldest first
+        let mut prev_scores: [Option<i32>; 2] = [None; 2];
 
-        'id_loop: for depth in 1..=self.limits.max_depth.unwrap_or(MAX_PLY as u16) {
+        let mut max_depth = self.limits.max_depth.unwrap_or(MAX_PLY as u16);
+        if let Some(strength) = self.strength {
+            max_depth = max_depth.min(strength.depth_cap);
+        }
+
+        'id_loop: for depth in 1..=max_depth {
             if self.should_stop() {
                 break 'id_loop;
             }
 
             debug!("Iterative Deepening current depth: {depth}");
 
-            let (local_best_move, local_best_score) = self.root_search_with_aspiration(
+            // Swing between the last two iterations, if it's larger than the initial window
+            let oscillation = match prev_scores {
+                [Some(older), Some(last)] if self.config.asp_widen_on_oscillation => {
+                    let swing = (last - older).abs();
+                    (swing >= self.params.asp_start_window).then_some(swing)
+                }
+                _ => None,
+            };
+
+            let Ok((local_best_move, local_best_score)) = self.root_search_with_aspiration(
                 &mut root_board,
                 depth,
                 &mut legal_moves,
                 best_move,
                 prev_score,
-            );
-
-            if self.should_stop() {
+                oscillation,
+            ) else {
+                debug!("Discarding aborted iteration at depth {depth}");
                 break 'id_loop;
-            }
+            };
 
             completed_depth = depth;
             best_move = local_best_move;
             best_score = local_best_score;
             prev_score = best_score;
+            prev_scores = [prev_scores[1], Some(best_score)];
+
+            if let Some(slot) = &self.shared_best_move
+                && let Some(mv) = best_move
+            {
+                slot.publish(mv, best_score, depth);
+            }
 
             if std::hint::likely(self.config.emit_info) {
-                self.emit_info_string(depth, best_score, best_move);
+                let pv = self.principal_variation(board, best_move, depth);
+                self.emit_info_string(depth, best_score, ScoreTypes::Exact, &pv);
             }
         }
 
+        if let Some(strength) = self.strength {
+            best_move = self.maybe_blunder(board, strength, &legal_moves, best_move);
+        }
+
+        // Stopped before depth 1 completed, fall back to the static eval of the root
+        if completed_depth == 0 {
+            best_score = self.evaluate(board);
+        }
+
         self.repetition_table.pop();
+        self.incremental.clear();
         if std::hint::unlikely(self.config.collect_stats) {
             self.stats.depth_reached = completed_depth;
             self.get_stats().log_summary();
         }
+        let pv = self.principal_variation(board, best_move, completed_depth.max(1));
         self.finish();
         SearchResult {
             best_move,
@@ -432,16 +671,36 @@ impl AlphaBetaSearch {
             depth: completed_depth,
             nodes_searched: self.nodes_searched,
             time_taken: self.start_time.elapsed(),
-            pv: Some(vec![
-                best_move.expect("Already been initialized to first move"),
-            ]),
-            // NOTE: Figure out how to get this.
-            is_mate: false,
-            mate_in: None,
+            pv: Some(pv),
+            is_mate: best_score.abs() > MATE_THRESHOLD,
+            mate_in: mate_in(best_score),
         }
     }
 
+    /// `alpha_beta_node`, recording the node into `search_tree` if it's being dumped
+    #[inline(always)]
     fn alpha_beta(
+        &mut self,
+        board: &mut Board,
+        context: SearchContext,
+        depth: u16,
+        alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        match &mut self.search_tree {
+            Some(tree) if tree.records(context.ply) => {
+                tree.open(context.prev_move, depth, alpha, beta);
+                let score = self.alpha_beta_node(board, context, depth, alpha, beta);
+                if let Some(tree) = &mut self.search_tree {
+                    tree.close(score);
+                }
+                score
+            }
+            _ => self.alpha_beta_node(board, context, depth, alpha, beta),
+        }
+    }
+
+    fn alpha_beta_node(
         &mut self,
         board: &mut Board,
         context: SearchContext,
@@ -450,18 +709,19 @@ impl AlphaBetaSearch {
         mut beta: i32,
     ) -> i32 {
         if self.should_stop() {
-            // Neutral score because search was stopped
+            // Neutral score because search was stopped.
+            // Never used: the root discards any iteration that was aborted
             return 0;
         }
 
         // Necessary to prevent search extensions from explosion
         if context.ply >= MAX_PLY {
             // Treat this as leaf node
-            return board.evaluate_position(&self.eval_params);
+            return self.evaluate(board);
         }
 
         if depth == 0 {
-            return self.quiescence_search(board, context, alpha, beta);
+            return self.quiescence_search(board, context, 0, alpha, beta);
         }
 
         if alpha >= beta {
@@ -491,7 +751,7 @@ impl AlphaBetaSearch {
             // CONTEMPT Factor
             // If we are drawing, return contempt score
             // Nudge the engine away from accepting draws
-            return CONTEMPT_SCORE;
+            return self.draw_score(board);
         }
 
         let mut tt_move = None;
@@ -541,7 +801,7 @@ impl AlphaBetaSearch {
                             if self.config.collect_stats {
                                 self.stats.tt_cutoffs += 1;
                             }
-                            return beta;
+                            return if self.config.fail_soft { score } else { beta };
                         }
                     }
                     ScoreTypes::UpperBound => {
@@ -552,7 +812,7 @@ impl AlphaBetaSearch {
                             if self.config.collect_stats {
                                 self.stats.tt_cutoffs += 1;
                             }
-                            return alpha;
+                            return if self.config.fail_soft { score } else { alpha };
                         }
                     }
                 }
@@ -585,14 +845,13 @@ impl AlphaBetaSearch {
         if self.config.sing_ext
             && !context.is_pv_node
             && context.excluded_move.is_none()
-            && depth >= SE_DEPTH
+            && depth >= self.params.se_min_depth
             && tt_move.is_some()
             && tt_depth >= depth.saturating_sub(3)
             && tt_bound != ScoreTypes::UpperBound
             && tt_value.abs() < MATE_THRESHOLD
         {
-            // TODO: Add to tuning params
-            let margin = depth as i32 * SE_MARGIN;
+            let margin = depth as i32 * self.params.se_margin;
             let singular_beta = tt_value - margin;
 
             // Search if any other move can beat margin
@@ -613,7 +872,7 @@ impl AlphaBetaSearch {
             );
 
             if score < singular_beta {
-                singular_extension = 1;
+                singular_extension = SINGULAR_EXT;
 
                 // if self.config.collect_stats {
                 //     self.stats.singular_extensions += 1;
@@ -655,7 +914,7 @@ impl AlphaBetaSearch {
             .expect("Atleast one move should exist in the buffer");
 
         let mut picker = MovePicker::new(
-            &board,
+            board,
             legal_moves.as_mut_slice(),
             &self.search_tables.killer_moves[ply],
             tt_move,
@@ -670,12 +929,25 @@ impl AlphaBetaSearch {
                 continue;
             }
 
+            // SEE pruning: drop clearly losing captures once a move has been searched
+            if move_index > 0
+                && !context.is_pv_node
+                && self.should_see_prune(board, depth, mv, is_in_check)
+            {
+                if self.config.collect_stats {
+                    self.stats.main_see_pruning_cutoffs += 1;
+                    self.stats.pruned_nodes += 1;
+                }
+                continue;
+            }
+
             // TODO: Use Unmake_move instead
             // let mut board_copy = *board;
             // board_copy.make_move(mv).expect("Move is already legal");
             let move_info = board.make_move(mv).expect("Move is already legal");
 
-            self.repetition_table.push(board.hash);
+            self.repetition_table.push(board.repetition_key());
+            self.push_incremental(&move_info);
 
             let mut score: i32;
 
@@ -684,21 +956,27 @@ impl AlphaBetaSearch {
 
             // Starts as non-PV unless the parent is PV and this is the first move
             let mut child_context = context.new_child(child_is_pv);
+            child_context.prev_move = Some(mv);
 
             let move_gives_check = board.is_in_check(board.stm);
 
-            let in_check_ext = if is_in_check { 1 } else { 0 };
+            let in_check_ext = if is_in_check { CHECK_EXT } else { 0 };
 
-            let pawn_ext = if let Some(piece) = board.get_piece_at(mv.from_sq())
+            // Move has already been made, so the pawn is on `to_sq` and the mover is `!stm`
+            let pawn_ext = if let Some(piece) = board.get_piece_at(mv.to_sq())
                 && piece == Piece::Pawn
             {
                 let rank = mv.to_sq().row();
-                let is_threatening_promo = if board.stm == Side::White {
+                let is_threatening_promo = if board.stm == Side::Black {
                     rank == 6
                 } else {
                     rank == 1
                 };
-                if is_threatening_promo { 1 } else { 0 }
+                if is_threatening_promo {
+                    PAWN_PUSH_EXT
+                } else {
+                    0
+                }
             } else {
                 0
             };
@@ -709,9 +987,32 @@ impl AlphaBetaSearch {
                 0
             };
 
-            let extension = (in_check_ext + pawn_ext + se_ext).min(1);
+            // Recapturing on the square the opponent just captured on
+            let recapture_ext = if self.config.enable_recapture_ext
+                && mv.is_capture()
+                && context
+                    .prev_move
+                    .is_some_and(|prev| prev.is_capture() && prev.to_sq() == mv.to_sq())
+            {
+                if self.config.collect_stats {
+                    self.stats.recapture_extensions += 1;
+                }
+                RECAPTURE_EXT
+            } else {
+                0
+            };
 
-            let new_depth = depth + extension;
+            let extension = (in_check_ext + pawn_ext + se_ext + recapture_ext).min(MAX_NODE_EXT);
+            let ext_plies = child_context.extend(extension);
+
+            if self.config.collect_stats && extension > 0 {
+                self.stats.extended_moves += 1;
+                self.stats.extension_total += (child_context.extension - context.extension) as u64;
+                self.stats.max_branch_extension =
+                    self.stats.max_branch_extension.max(child_context.extension);
+            }
+
+            let new_depth = depth + ext_plies;
 
             if is_pv_node {
                 score = self.pv_search(board, child_context, new_depth, alpha, beta);
@@ -747,6 +1048,7 @@ impl AlphaBetaSearch {
             }
 
             self.repetition_table.pop();
+            self.incremental.pop();
             board
                 .unmake_move(&move_info)
                 .expect("Should be able to unmake moves");
@@ -772,16 +1074,25 @@ impl AlphaBetaSearch {
                 }
 
                 // This is beta-cutoff (Fail high)
-                // If move is quiet, this is a good candidate for killer moves
+                // If move is quiet, this is a good candidate for killer moves.
+                // Captures are remembered in the capture history instead
                 if !mv.is_capture() && ply < MAX_PLY {
                     self.search_tables.update_killers(ply, mv);
-                    self.search_tables.update_history(mv, depth);
+                    self.search_tables.update_history(board, mv, depth);
+                } else if mv.is_capture() {
+                    self.search_tables.update_capture_history(board, mv, depth);
                 }
 
+                // Fail-soft keeps the score that beat beta, it's a tighter lower bound
+                let cutoff_score = if self.config.fail_soft {
+                    best_score
+                } else {
+                    beta
+                };
                 let entry_to_store = TranspositionEntry::new(
                     current_hash,
                     best_move_this_node,
-                    adjust_score_from_ply(beta, ply),
+                    adjust_score_from_ply(cutoff_score, ply),
                     depth as u8,
                     ScoreTypes::LowerBound,
                     self.search_cycle,
@@ -789,7 +1100,7 @@ impl AlphaBetaSearch {
 
                 self.tt.store(entry_to_store);
 
-                return beta;
+                return cutoff_score;
             }
             move_index += 1;
         }
@@ -798,7 +1109,9 @@ impl AlphaBetaSearch {
             // If we excluded the only legal move, we effectively have no moves to search.
             // In a Singular Search, this means there are no alternatives.
             // We should return alpha (fail low), because we couldn't beat the singular beta.
-            if context.excluded_move.is_some() {
+            // Same if the search was stopped before any move got a score, the root throws
+            // away the aborted iteration anyway
+            if context.excluded_move.is_some() || self.should_stop() {
                 return alpha;
             }
             error!(
@@ -836,13 +1149,19 @@ impl AlphaBetaSearch {
         );
         self.tt.store(entry_to_store);
 
-        alpha
+        if self.config.fail_soft {
+            best_score
+        } else {
+            alpha
+        }
     }
 
+    /// `qs_depth` is the number of plies since entering quiescence search
     fn quiescence_search(
         &mut self,
         board: &mut Board,
         context: SearchContext,
+        qs_depth: u16,
         mut alpha: i32,
         beta: i32,
     ) -> i32 {
@@ -850,8 +1169,13 @@ impl AlphaBetaSearch {
             return 0;
         }
 
-        if context.ply > MAX_PLY {
-            return board.evaluate_position(&self.eval_params);
+        if self.config.collect_stats {
+            self.stats.qsearch_max_depth = self.stats.qsearch_max_depth.max(qs_depth);
+        }
+
+        // Bound pathological capture sequences, returning the stand-pat eval
+        if context.ply > MAX_PLY || qs_depth >= self.config.max_qsearch_depth {
+            return self.evaluate(board);
         }
 
         if self.should_stop() {
@@ -866,15 +1190,17 @@ impl AlphaBetaSearch {
             if self.config.collect_stats {
                 self.stats.draw_returns += 1;
             }
-            return CONTEMPT_SCORE;
+            return self.draw_score(board);
         }
 
         let is_in_check = board.is_in_check(board.stm);
 
         let stand_pat_score;
+        // Only returned with `SearchConfig::fail_soft`
+        let mut best_score = i32::MIN + 1;
 
         if !is_in_check {
-            stand_pat_score = board.evaluate_position(&self.eval_params);
+            stand_pat_score = self.evaluate(board);
 
             if stand_pat_score >= beta {
                 if self.config.collect_stats {
@@ -882,9 +1208,14 @@ impl AlphaBetaSearch {
                     self.stats.pruned_nodes += 1;
                 }
                 // Fail high
-                return beta;
+                return if self.config.fail_soft {
+                    stand_pat_score
+                } else {
+                    beta
+                };
             }
-            alpha = max(alpha, stand_pat_score)
+            alpha = max(alpha, stand_pat_score);
+            best_score = stand_pat_score;
         } else {
             stand_pat_score = i32::MIN;
         }
@@ -912,6 +1243,12 @@ impl AlphaBetaSearch {
         }
 
         let mut picker = MovePicker::new_qsearch(board, legal_moves.as_mut_slice());
+        // Evasions are never cut, a node in check needs all of them to detect mate
+        let mut moves_left = if is_in_check {
+            None
+        } else {
+            self.config.max_qsearch_captures
+        };
 
         while let Some(mv) = picker.next_best() {
             if !is_in_check {
@@ -929,9 +1266,9 @@ impl AlphaBetaSearch {
                     if stand_pat_score
                         + captured_piece_value
                         + if mv.is_promotion() {
-                            DELTA_MARGIN + 200
+                            self.params.delta_margin + 200
                         } else {
-                            DELTA_MARGIN
+                            self.params.delta_margin
                         }
                         < alpha
                     {
@@ -943,7 +1280,7 @@ impl AlphaBetaSearch {
                     }
                 }
                 // SEE pruning
-                if board.static_exchange_evaluation(mv) < SEE_THRESHOLD {
+                if board.static_exchange_evaluation(mv) < self.params.qs_see_threshold {
                     if self.config.collect_stats {
                         self.stats.see_pruning_cutoffs += 1;
                         self.stats.pruned_nodes += 1;
@@ -951,7 +1288,14 @@ impl AlphaBetaSearch {
                     continue;
                 }
             }
-            let move_info = board.make_move(mv).ok().expect("Move should be legal");
+            // The picker hands out the best moves by SEE first
+            if let Some(left) = &mut moves_left {
+                if *left == 0 {
+                    break;
+                }
+                *left -= 1;
+            }
+            let move_info = board.make_move(mv).expect("Move should be legal");
             // let mut board_copy = *board;
             // if let Err(e) = board_copy.make_move(mv) {
             //     error!(
@@ -961,12 +1305,14 @@ impl AlphaBetaSearch {
             //     );
             //     continue;
             // }
-            self.repetition_table.push(board.hash);
+            self.repetition_table.push(board.repetition_key());
+            self.push_incremental(&move_info);
 
             let child_context = context.new_child(context.is_pv_node);
-            let score = -self.quiescence_search(board, child_context, -beta, -alpha);
+            let score = -self.quiescence_search(board, child_context, qs_depth + 1, -beta, -alpha);
 
             self.repetition_table.pop();
+            self.incremental.pop();
             board
                 .unmake_move(&move_info)
                 .expect("Should be able to unmake move");
@@ -977,11 +1323,17 @@ impl AlphaBetaSearch {
                     self.stats.beta_cutoffs_qs += 1;
                     self.stats.pruned_nodes += 1;
                 }
-                return beta;
+                return if self.config.fail_soft { score } else { beta };
             }
+            best_score = max(best_score, score);
             alpha = max(alpha, score);
         }
-        alpha
+
+        if self.config.fail_soft && best_score != i32::MIN + 1 {
+            best_score
+        } else {
+            alpha
+        }
     }
 }
 
@@ -1019,25 +1371,119 @@ impl AlphaBetaSearch {
         false
     }
 
-    fn emit_info_string(&self, depth: u16, score: i32, best_move: Option<Move>) {
-        let nps =
-            (self.nodes_searched * 1000) / self.start_time.elapsed().as_millis().max(1) as u64;
-        let best_move_uci = best_move.map(|m| m.uci()).unwrap_or_default();
+    /// With `strength.blunder_permille` chance, swaps `best_move` for another random legal move.
+    /// Deterministic for a given position
+    fn maybe_blunder(
+        &self,
+        board: &Board,
+        strength: StrengthLimit,
+        legal_moves: &MoveBuffer,
+        best_move: Option<Move>,
+    ) -> Option<Move> {
+        if legal_moves.len() < 2 {
+            return best_move;
+        }
 
-        let msg = format!(
-            "info depth {} score cp {} nodes {} nps {} pv {}",
-            depth, score, self.nodes_searched, nps, best_move_uci
+        let mut prng = Prng::init(StrengthLimit::SEED ^ board.hash);
+        if prng.rand() % 1000 >= strength.blunder_permille as u64 {
+            return best_move;
+        }
+
+        let others: Vec<Move> = legal_moves
+            .iter()
+            .copied()
+            .filter(|&mv| Some(mv) != best_move)
+            .collect();
+        let blunder = others[prng.rand() as usize % others.len()];
+        debug!(
+            "Limited strength (elo {}): playing {} instead of {:?}",
+            strength.elo,
+            blunder.uci(),
+            best_move.map(|m| m.uci())
         );
+        Some(blunder)
+    }
+
+    /// `best_move`, followed by the TT moves of the positions it leads to if
+    /// `SearchConfig::full_pv` is set. At most `depth` moves, stops at a missing or
+    /// illegal TT move and at the first repeated position
+    fn principal_variation(&self, board: &Board, best_move: Option<Move>, depth: u16) -> Vec<Move> {
+        let Some(mut mv) = best_move else {
+            return Vec::new();
+        };
+        let mut pv = vec![mv];
+        if !self.config.full_pv {
+            return pv;
+        }
+
+        let mut board = *board;
+        let mut seen = vec![board.hash];
+        while pv.len() < depth as usize {
+            if board.make_move(mv).is_err() || seen.contains(&board.hash) {
+                break;
+            }
+            seen.push(board.hash);
+
+            let Some(entry) = self.tt.probe(board.hash) else {
+                break;
+            };
+            mv = entry.get_best_move();
+            let mut legal_moves = MoveBuffer::new();
+            board.generate_legal_moves(&mut legal_moves, false);
+            if !legal_moves.contains(&mv) {
+                break;
+            }
+            pv.push(mv);
+        }
+        pv
+    }
+
+    /// `bound` is `ScoreTypes::Exact` for completed iterations. Failed aspiration windows
+    /// report a `LowerBound` (fail high) or `UpperBound` (fail low) score
+    fn emit_info_string(&mut self, depth: u16, score: i32, bound: ScoreTypes, pv: &[Move]) {
+        let msg = self.format_info(depth, score, bound, pv);
         println!("{msg}");
         debug!(msg);
     }
 
+    fn format_info(&self, depth: u16, score: i32, bound: ScoreTypes, pv: &[Move]) -> String {
+        let nps =
+            (self.nodes_searched * 1000) / self.start_time.elapsed().as_millis().max(1) as u64;
+        let pv: Vec<String> = pv.iter().map(|m| m.uci()).collect();
+        let bound = match bound {
+            ScoreTypes::Exact => None,
+            ScoreTypes::LowerBound => Some("lowerbound"),
+            ScoreTypes::UpperBound => Some("upperbound"),
+        };
+
+        match self.config.info_format {
+            InfoFormat::Uci => format!(
+                "info depth {} score cp {}{} nodes {} nps {} pv {}",
+                depth,
+                score,
+                bound.map(|b| format!(" {b}")).unwrap_or_default(),
+                self.nodes_searched,
+                nps,
+                pv.join(" ")
+            ),
+            InfoFormat::Json => {
+                let line = InfoLine {
+                    depth,
+                    score,
+                    nodes: self.nodes_searched,
+                    nps,
+                    bound,
+                    pv,
+                };
+                serde_json::to_string(&line).expect("InfoLine only holds plain values")
+            }
+        }
+    }
+
+    /// The shared `search_running` flag is armed by its owner, so an early stop isn't overwritten
     #[inline]
     fn start(&mut self) {
         self.in_progress = true;
-        if let Some(flag) = &self.search_running {
-            flag.store(true, Ordering::Relaxed);
-        }
     }
 
     fn finish(&mut self) {
@@ -1047,9 +1493,58 @@ impl AlphaBetaSearch {
         }
     }
 
+    /// Static eval, using the incremental PST and material scores and the pawn hash table
+    /// when possible
+    #[inline]
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        if self.config.material_only_eval {
+            return evaluate_material(board);
+        }
+        let incremental = self.incremental.last().copied();
+        debug_assert!(
+            incremental.is_none_or(|s| s == IncrementalScore::compute(board, &self.eval_params)),
+            "Incremental scores out of step with {:?}",
+            board.to_fen()
+        );
+        match (&mut self.pawn_table, incremental) {
+            (Some(table), _) => evaluate_cached(board, &self.eval_params, incremental, table)
+                .taper(board.game_phase()),
+            (None, Some(incremental)) => {
+                board.evaluate_position_incremental(&self.eval_params, incremental)
+            }
+            (None, None) => board.evaluate_position(&self.eval_params),
+        }
+    }
+
+    /// Pushes the PST and material scores of the position `move_info` was just made in.
+    /// Does nothing when not searching from `find_best_move`, so evals scan the board then
+    #[inline(always)]
+    fn push_incremental(&mut self, move_info: &MoveInfo) {
+        if let Some(&last) = self.incremental.last() {
+            self.incremental
+                .push(last.after_move(move_info, &self.eval_params));
+        }
+    }
+
+    /// Score of a drawn position from the side to move's point of view.
+    /// Negative for the root side when contempt is set, so it avoids draws
+    #[inline]
+    fn draw_score(&self, board: &Board) -> i32 {
+        let contempt = self.params.effective_contempt(board.game_phase());
+        if board.stm == self.root_stm {
+            -contempt
+        } else {
+            contempt
+        }
+    }
+
     #[inline]
     fn is_draw(&self, board: &Board) -> bool {
-        board.halfmove_clock >= 100 || self.repetition_table.count_repetitions(board.hash) >= 2
+        board.is_fifty_move_draw()
+            || self
+                .repetition_table
+                .count_repetitions(board.repetition_key())
+                >= 2
     }
 
     fn prepare_for_search(&mut self) {
@@ -1081,14 +1576,15 @@ impl AlphaBetaSearch {
         legal_moves: &mut MoveBuffer,
         prev_best_move: Option<Move>,
         prev_score: i32,
-    ) -> (Option<Move>, i32) {
+        oscillation: Option<i32>,
+    ) -> RootResult {
         self.sort_moves::<MainSearchPolicy>(board, legal_moves, prev_best_move, depth as usize);
 
         let use_asp = self.config.enable_asp
             && depth > 1
-            && prev_score.abs() < MATE_THRESHOLD - ASP_MAX_WINDOW;
+            && prev_score.abs() < MATE_THRESHOLD - self.params.asp_max_window;
 
-        let mut window = ASP_START_WINDOW;
+        let mut window = self.params.asp_start_window;
 
         let mut alpha_base = if use_asp {
             prev_score.saturating_sub(window)
@@ -1106,20 +1602,22 @@ impl AlphaBetaSearch {
         let mut tries: usize = 0;
         loop {
             if self.should_stop() {
-                return (legal_moves.first().copied(), i32::MIN + 1);
+                return Err(SearchAborted);
             }
             trace!("ASP window: ({alpha_base}, {beta_base})");
 
             let (best_move, best_score) =
-                self.root_search_attempt(board, depth, alpha_base, beta_base, legal_moves);
+                self.root_search_attempt(board, depth, alpha_base, beta_base, legal_moves)?;
 
             if !use_asp {
-                return (best_move, best_score);
+                return Ok((best_move, best_score));
             }
 
-            if tries >= 4 {
-                debug!("Tried ASP 4 times, No-doy");
-                return (best_move, best_score);
+            if std::hint::likely(self.config.emit_info)
+                && let Some(bound) = window_bound(best_score, alpha_base, beta_base)
+            {
+                let pv = self.principal_variation(board, best_move, depth);
+                self.emit_info_string(depth, best_score, bound, &pv);
             }
 
             // - Asymmetric widening: Increase/decrease based on fail high/low
@@ -1129,24 +1627,54 @@ impl AlphaBetaSearch {
                     self.stats.asp_fail_low += 1;
                     self.stats.asp_research += 1;
                 }
+                window = self.widen_window(window, tries == 0, oscillation);
                 tries += 1;
-                window = window.saturating_mul(2).min(ASP_MAX_WINDOW);
-                alpha_base = prev_score.saturating_sub(window);
+                // The window is capped, so a far lower score (eg. a mate) needs a full window
+                alpha_base = if tries >= ASP_MAX_TRIES {
+                    debug!("Tried ASP {ASP_MAX_TRIES} times, opening alpha");
+                    i32::MIN + 1
+                } else {
+                    prev_score.saturating_sub(window)
+                };
             } else if best_score >= beta_base {
                 // Fail High
                 if self.config.collect_stats {
                     self.stats.asp_fail_high += 1;
                     self.stats.asp_research += 1;
                 }
+                window = self.widen_window(window, tries == 0, oscillation);
                 tries += 1;
-                window = window.saturating_mul(2).min(ASP_MAX_WINDOW);
-                beta_base = prev_score.saturating_add(window);
+                beta_base = if tries >= ASP_MAX_TRIES {
+                    debug!("Tried ASP {ASP_MAX_TRIES} times, opening beta");
+                    i32::MAX
+                } else {
+                    prev_score.saturating_add(window)
+                };
             } else {
-                return (best_move, best_score);
+                return Ok((best_move, best_score));
             }
         }
     }
 
+    /// Doubles the aspiration `window` after a fail.
+    /// If the last two iterations oscillated by more than that, the first fail widens
+    /// straight to the size of the swing, skipping the re-searches in between
+    fn widen_window(&mut self, window: i32, first_fail: bool, oscillation: Option<i32>) -> i32 {
+        let mut widened = window.saturating_mul(2);
+        if first_fail
+            && let Some(swing) = oscillation
+            && swing > widened
+        {
+            widened = swing;
+            if self.config.collect_stats {
+                self.stats.asp_oscillation_widen += 1;
+            }
+        }
+        widened.min(self.params.asp_max_window)
+    }
+
+    /// Searches every root move once in the (`alpha_base`, `beta_base`) window.
+    /// Returns `Err(SearchAborted)` if the search was stopped before all moves were searched
     fn root_search_attempt(
         &mut self,
         board: &mut Board,
@@ -1154,24 +1682,27 @@ impl AlphaBetaSearch {
         alpha_base: i32,
         beta_base: i32,
         legal_moves: &MoveBuffer,
-    ) -> (Option<Move>, i32) {
+    ) -> RootResult {
         let mut alpha = alpha_base;
         let beta = beta_base;
 
         let mut local_best_move: Option<Move> = legal_moves.first().copied();
         let mut local_best_score: i32 = i32::MIN + 1;
+        if let Some(tree) = &mut self.search_tree {
+            tree.reset(depth, alpha_base, beta_base);
+        }
 
         for &mv in legal_moves {
             if self.should_stop() {
-                break;
+                return Err(SearchAborted);
             }
 
-            // let make_info =  board.make_move(mv).ok().expect("Move should be legal");
             let make_info = match board.make_move(mv) {
                 Ok(mi) => mi,
                 Err(e) => panic!(
-                    "{e:?}\n{}",
-                    format!("Curr board: {:?}, Curr move: {}", board.to_fen(), mv.uci())
+                    "{e:?}\nCurr board: {:?}, Curr move: {}",
+                    board.to_fen(),
+                    mv.uci()
                 ),
             };
             // let mut board_copy = *board;
@@ -1184,23 +1715,22 @@ impl AlphaBetaSearch {
             //     continue;
             // }
 
-            self.repetition_table.push(board.hash);
+            self.repetition_table.push(board.repetition_key());
+            self.push_incremental(&make_info);
 
-            let root_child_context = SearchContext {
-                ply: 1,
-                is_pv_node: true,
-                excluded_move: None,
-            };
+            let mut root_child_context = SearchContext::root().new_child(true);
+            root_child_context.prev_move = Some(mv);
 
             let score = -self.alpha_beta(board, root_child_context, depth - 1, -beta, -alpha);
 
             self.repetition_table.pop();
+            self.incremental.pop();
             board
                 .unmake_move(&make_info)
                 .expect("Should be able to unmake move");
 
             if self.should_stop() {
-                break;
+                return Err(SearchAborted);
             }
 
             if score > local_best_score {
@@ -1215,7 +1745,16 @@ impl AlphaBetaSearch {
             }
         }
 
-        (local_best_move, local_best_score)
+        // Nothing was scored, never hand the sentinel to iterative deepening
+        if local_best_score == i32::MIN + 1 {
+            return Err(SearchAborted);
+        }
+
+        if let Some(tree) = &mut self.search_tree {
+            tree.close(local_best_score);
+        }
+
+        Ok((local_best_move, local_best_score))
     }
 
     /// Zero-window search for non-PV moves, with re-search on fail-high
@@ -1250,7 +1789,7 @@ impl AlphaBetaSearch {
             return None;
         }
 
-        if depth < 5
+        if depth < self.params.nmp_min_depth
             || context.ply == 0
             || board.is_in_check(board.stm)
             || !has_non_pawn_material(board)
@@ -1258,7 +1797,11 @@ impl AlphaBetaSearch {
             return None;
         }
 
-        let null_reduction = if depth >= 6 { 4 } else { 2 };
+        let null_reduction = if depth >= self.params.nmp_deep_depth {
+            self.params.nmp_deep_reduction
+        } else {
+            self.params.nmp_reduction
+        };
         let null_depth = depth.saturating_sub(null_reduction);
 
         if self.config.collect_stats {
@@ -1276,7 +1819,12 @@ impl AlphaBetaSearch {
                 self.stats.null_move_cutoffs += 1;
                 self.stats.pruned_nodes += 1;
             }
-            Some(beta)
+            // Unproven mate scores from a null move search aren't trusted
+            if self.config.fail_soft && score < MATE_THRESHOLD {
+                Some(score)
+            } else {
+                Some(beta)
+            }
         } else {
             None
         }
@@ -1285,7 +1833,8 @@ impl AlphaBetaSearch {
     /// Late Move Reduction
     #[inline]
     fn lmr_reduction(&self, depth: u16, move_index: usize) -> u16 {
-        let base = 0.20 + ((depth as f32).ln() * (move_index as f32).ln()) / 3.35;
+        let base = self.params.lmr_base
+            + ((depth as f32).ln() * (move_index as f32).ln()) / self.params.lmr_divisor;
         (base as u16).min(depth - 1)
     }
 
@@ -1296,7 +1845,7 @@ impl AlphaBetaSearch {
         hint: Option<Move>,
         depth: usize,
     ) {
-        let seed = board.hash.wrapping_add(depth as u64);
+        let seed = (!self.config.deterministic).then(|| board.hash.wrapping_add(depth as u64));
         sort_moves::<P>(
             board,
             legal_moves.as_mut_slice(),
@@ -1318,11 +1867,741 @@ impl AlphaBetaSearch {
         gives_check: bool,
     ) -> bool {
         self.config.enable_lmr
-            && depth >= 3
-            && move_index >= 3
+            && depth >= self.params.lmr_min_depth
+            && move_index >= self.params.lmr_min_move_index
             && !mv.is_capture()
             && !mv.is_promotion()
             && !in_check
             && !gives_check
     }
+
+    /// A capture at a shallow depth whose exchange loses more than `see_prune_margin` per ply
+    fn should_see_prune(&self, board: &Board, depth: u16, mv: Move, in_check: bool) -> bool {
+        self.config.enable_main_see_pruning
+            && depth <= self.params.see_prune_max_depth
+            && mv.is_capture()
+            && !in_check
+            && !board.see_ge(mv, -self.params.see_prune_margin * depth as i32)
+    }
+}
+
+/// The bound to report for a root score that fell outside the aspiration window, or `None`
+/// if it is exact
+fn window_bound(score: i32, alpha: i32, beta: i32) -> Option<ScoreTypes> {
+    if score >= beta {
+        Some(ScoreTypes::LowerBound)
+    } else if score <= alpha {
+        Some(ScoreTypes::UpperBound)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lmr_min_move_index_override() {
+        let quiet = Move::new(12, 28, Move::QUIET);
+        let default = AlphaBetaSearch::new();
+        assert!(!default.should_reduce(6, 2, quiet, false, false));
+        assert!(default.should_reduce(6, 3, quiet, false, false));
+
+        let params = SearchParams {
+            lmr_min_move_index: 6,
+            ..Default::default()
+        };
+        let search = AlphaBetaSearch::new().with_params(params);
+        assert!(!search.should_reduce(6, 3, quiet, false, false));
+        assert!(!search.should_reduce(6, 5, quiet, false, false));
+        assert!(search.should_reduce(6, 6, quiet, false, false));
+    }
+
+    #[test]
+    fn test_main_see_pruning() {
+        // Qxa5 loses the queen to bxa5, exd4 wins a pawn
+        let board = Board::from_fen("4k3/8/1p6/r7/3p4/4P3/8/Q3K3 w - - 0 1");
+        let losing = Move::from_uci(&board, "a1a5").unwrap();
+        let winning = Move::from_uci(&board, "e3d4").unwrap();
+
+        let default = AlphaBetaSearch::new();
+        assert!(!default.should_see_prune(&board, 1, losing, false));
+
+        let conf = SearchConfig {
+            emit_info: false,
+            enable_main_see_pruning: true,
+            ..Default::default()
+        };
+        let search = AlphaBetaSearch::new().with_config(conf).unwrap();
+        assert!(search.should_see_prune(&board, 1, losing, false));
+        assert!(search.should_see_prune(&board, 3, losing, false));
+        assert!(!search.should_see_prune(&board, 4, losing, false));
+        assert!(!search.should_see_prune(&board, 1, losing, true));
+        assert!(!search.should_see_prune(&board, 1, winning, false));
+
+        let stats = |enable_main_see_pruning| {
+            let conf = SearchConfig {
+                emit_info: false,
+                enable_main_see_pruning,
+                ..Default::default()
+            };
+            let mut search = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(5));
+            search.find_best_move(&board);
+            search.get_stats()
+        };
+        assert!(stats(true).main_see_pruning_cutoffs > 0);
+        assert_eq!(stats(false).main_see_pruning_cutoffs, 0);
+    }
+
+    #[test]
+    fn test_dump_tree() {
+        let board = Board::new();
+        let conf = SearchConfig {
+            emit_info: false,
+            enable_asp: false,
+            dump_tree: Some(2),
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(2));
+        let result = search.find_best_move(&board);
+
+        let tree = search.search_tree().expect("Tree should be recorded");
+        let root = tree.root().unwrap();
+        assert_eq!(root.depth, 2);
+        assert_eq!(root.children.len(), 20);
+        assert_eq!(root.score, result.score);
+
+        let mut root_moves: Vec<Move> = root
+            .children
+            .iter()
+            .map(|&idx| tree.nodes()[idx].mv.unwrap())
+            .collect();
+        root_moves.sort();
+        root_moves.dedup();
+        assert_eq!(root_moves.len(), 20);
+        assert!(tree.to_string().lines().count() > 20);
+
+        // Off by default
+        let mut search = AlphaBetaSearch::new().with_limits(SearchLimits::depth(2));
+        search.find_best_move(&board);
+        assert!(search.search_tree().is_none());
+    }
+
+    #[test]
+    fn test_deterministic_ordering() {
+        // Nothing to tell the quiet start position moves apart, so only the tie break
+        // decides their order
+        let board = Board::new();
+        let order = |deterministic, reversed: bool| {
+            let conf = SearchConfig {
+                emit_info: false,
+                deterministic,
+                ..Default::default()
+            };
+            let search = AlphaBetaSearch::new().with_config(conf).unwrap();
+            let mut moves = MoveBuffer::new();
+            board.generate_legal_moves(&mut moves, false);
+            if reversed {
+                moves.as_mut_slice().reverse();
+            }
+            search.sort_moves::<MainSearchPolicy>(&board, &mut moves, None, 1);
+            moves.iter().copied().collect::<Vec<_>>()
+        };
+
+        // Ties are broken by move encoding, whatever order the moves were generated in
+        let deterministic = order(true, false);
+        assert_eq!(deterministic, order(true, true));
+        assert!(deterministic.is_sorted());
+
+        // The random tie break depends on the generation order
+        assert_ne!(order(false, false), order(false, true));
+    }
+
+    #[test]
+    fn test_aborted_search_has_no_sentinel_score() {
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        for nodes in [1, 2, 10] {
+            let board = Board::from_fen(KIWIPETE);
+            let mut search = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::nodes(nodes));
+            let result = search.find_best_move(&board);
+
+            assert_eq!(
+                result.depth, 0,
+                "Node limit {nodes} shouldn't finish depth 1"
+            );
+            assert!(result.best_move.is_some());
+            assert!(
+                result.score.abs() < MATE_THRESHOLD,
+                "Node limit {nodes} leaked score {}",
+                result.score
+            );
+        }
+    }
+
+    #[test]
+    fn test_shared_best_move_mid_search() {
+        let board = Board::from_fen(KIWIPETE);
+        let running = Arc::new(AtomicBool::new(true));
+        let slot = Arc::new(SharedBestMove::new());
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::infinite())
+            .init(running.clone(), slot.clone());
+        let handle = std::thread::spawn(move || search.find_best_move(&board));
+
+        let start = Instant::now();
+        let (mv, _, depth) = loop {
+            if let Some(best) = slot.load() {
+                break best;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Nothing published"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        let mut legal = MoveBuffer::new();
+        board.generate_legal_moves(&mut legal, false);
+        assert!(depth >= 1);
+        assert!(legal.contains(&mv), "{}", mv.uci());
+        assert!(!handle.is_finished(), "Infinite search stopped on its own");
+
+        running.store(false, Ordering::Release);
+        let result = handle.join().unwrap();
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_json_info_format() {
+        let conf = SearchConfig {
+            emit_info: false,
+            info_format: InfoFormat::Json,
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(4));
+        let result = search.find_best_move(&Board::new());
+
+        let pv = result.pv.clone().unwrap();
+        let line = search.format_info(result.depth, result.score, ScoreTypes::Exact, &pv);
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["depth"], result.depth);
+        assert_eq!(json["score"], result.score);
+        assert!(json["nodes"].as_u64().unwrap() > 0);
+        assert!(json["nps"].is_u64());
+        assert_eq!(json["pv"][0], result.best_move.unwrap().uci());
+
+        search.set_info_format(InfoFormat::Uci);
+        let line = search.format_info(result.depth, result.score, ScoreTypes::Exact, &pv);
+        assert!(line.starts_with("info depth 4 score cp "), "{line}");
+    }
+
+    #[test]
+    fn test_material_only_eval_search() {
+        let conf = SearchConfig {
+            emit_info: false,
+            material_only_eval: true,
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(3));
+
+        // Takes the hanging queen
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1");
+        let result = search.find_best_move(&board);
+        assert_eq!(result.best_move.unwrap().uci(), "d2d5");
+        assert!(result.score > 0, "{}", result.score);
+    }
+
+    #[test]
+    fn test_engines_with_different_params_coexist() {
+        let board = Board::from_fen(KIWIPETE);
+        let mut tweaked = TunableParams::default();
+        tweaked.material[Piece::Knight.index()] += Score::splat(60);
+        for (i, value) in tweaked.psts.iter_mut().enumerate() {
+            *value += Score::splat(i as i32 % 9);
+        }
+
+        let mut engines = [TunableParams::default(), tweaked].map(|params| {
+            let conf = SearchConfig {
+                emit_info: false,
+                ..Default::default()
+            };
+            AlphaBetaSearch::with_eval(params)
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(3))
+        });
+
+        let mut evals = Vec::new();
+        for search in &mut engines {
+            // Each engine values the incremental scores with its own params
+            let params = search.get_params();
+            search.find_best_move(&board);
+            search
+                .incremental
+                .push(IncrementalScore::compute(&board, &params));
+            let full = board.evaluate_position(&params);
+            assert_eq!(search.evaluate(&board), full);
+            search.pawn_table = None;
+            assert_eq!(search.evaluate(&board), full);
+            evals.push(full);
+        }
+        assert_ne!(evals[0], evals[1]);
+    }
+
+    #[test]
+    fn test_failed_aspiration_reports_bound() {
+        assert_eq!(window_bound(-300, -300, -250), Some(ScoreTypes::UpperBound));
+        assert_eq!(window_bound(-250, -300, -250), Some(ScoreTypes::LowerBound));
+        assert_eq!(window_bound(400, -300, -250), Some(ScoreTypes::LowerBound));
+        assert_eq!(window_bound(20, -30, 30), None);
+
+        let search = AlphaBetaSearch::new();
+        let line = search.format_info(4, 20, ScoreTypes::LowerBound, &[]);
+        assert!(
+            line.starts_with("info depth 4 score cp 20 lowerbound nodes "),
+            "{line}"
+        );
+        let line = search.format_info(4, -20, ScoreTypes::UpperBound, &[]);
+        assert!(
+            line.starts_with("info depth 4 score cp -20 upperbound nodes "),
+            "{line}"
+        );
+        let line = search.format_info(4, 20, ScoreTypes::Exact, &[]);
+        assert!(
+            line.starts_with("info depth 4 score cp 20 nodes "),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn test_aspiration_recovers_from_failed_windows() {
+        let conf = SearchConfig {
+            emit_info: false,
+            enable_asp: true,
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new().with_config(conf).unwrap();
+        let mut board = Board::new();
+        let mut legal_moves = MoveBuffer::new();
+        board.generate_legal_moves(&mut legal_moves, false);
+
+        search.prepare_for_search();
+        search.in_progress = true;
+        search.root_stm = board.stm;
+        search.repetition_table.push(board.repetition_key());
+
+        // The start position is nowhere near either guess, so the first window fails high
+        // and the second fails low, but both still return a move
+        for prev_score in [-300, 300] {
+            let (best_move, score) = search
+                .root_search_with_aspiration(
+                    &mut board,
+                    4,
+                    &mut legal_moves,
+                    None,
+                    prev_score,
+                    None,
+                )
+                .unwrap();
+            assert!(best_move.is_some());
+            assert!(score.abs() < 100, "{score}");
+        }
+    }
+
+    #[test]
+    fn test_full_pv_is_a_legal_line() {
+        let mut conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        conf.set_analyse_mode(true);
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(5));
+        let mut board = Board::from_fen(KIWIPETE);
+        let result = search.find_best_move(&board);
+
+        let pv = result.pv.unwrap();
+        assert_eq!(pv[0], result.best_move.unwrap());
+        assert!(pv.len() > 1 && pv.len() <= 5, "{pv:?}");
+        for mv in pv {
+            board.try_move(mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_history_indexing_first_move_cutoffs() {
+        // Piece-to shares entries between pieces of the same kind
+        let board = Board::from_fen(KIWIPETE);
+        let (c3b5, a1b1) = (Move::new(18, 33, Move::QUIET), Move::new(0, 1, Move::QUIET));
+        let (e2b5, h1f1) = (Move::new(12, 33, Move::QUIET), Move::new(7, 5, Move::QUIET));
+        let mut history = HistoryTable::new(HistoryIndexing::PieceTo);
+        history.add(&board, a1b1, 10);
+        assert_eq!(history.get(&board, a1b1), 10);
+        assert_eq!(history.get(&board, c3b5), 0);
+        history.add(&board, e2b5, 5);
+        assert_eq!(history.get(&board, Move::new(11, 33, Move::QUIET)), 5);
+        assert_eq!(history.get(&board, h1f1), 0);
+
+        for indexing in [HistoryIndexing::FromTo, HistoryIndexing::PieceTo] {
+            let conf = SearchConfig {
+                emit_info: false,
+                collect_stats: true,
+                deterministic: true,
+                history_indexing: indexing,
+                ..Default::default()
+            };
+            let mut search = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(5));
+            search.find_best_move(&board);
+
+            let stats = search.get_stats();
+            assert!(stats.beta_cutoffs_main > 0);
+            let rate = stats.cutoff_at_move[0] as f64 / stats.beta_cutoffs_main as f64;
+            assert!(rate > 0.5, "{indexing:?}: {rate}");
+        }
+    }
+
+    #[test]
+    fn test_fail_soft() {
+        let search_with = |fail_soft: bool| {
+            let conf = SearchConfig {
+                emit_info: false,
+                deterministic: true,
+                fail_soft,
+                ..Default::default()
+            };
+            AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(5))
+        };
+
+        // Same best move either way, a hanging queen and a mate in one
+        for (fen, best) in [
+            ("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", "d2d5"),
+            ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"),
+        ] {
+            let board = Board::from_fen(fen);
+            for fail_soft in [false, true] {
+                let result = search_with(fail_soft).find_best_move(&board);
+                assert_eq!(result.best_move.unwrap().uci(), best, "{fen} {fail_soft}");
+            }
+        }
+
+        // White is a queen up, so a window far below the score fails high
+        let (alpha, beta) = (-1001, -1000);
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1");
+        for fail_soft in [false, true] {
+            let mut search = search_with(fail_soft);
+            search.in_progress = true;
+            let score = search.alpha_beta(&mut board, SearchContext::root(), 3, alpha, beta);
+
+            let entry = search.tt.probe(board.hash).unwrap();
+            assert_eq!(entry.get_score_type(), ScoreTypes::LowerBound);
+            assert_eq!(entry.get_score(), score);
+            if fail_soft {
+                assert!(score > 500, "{score}");
+            } else {
+                assert_eq!(score, beta);
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_qsearch_captures() {
+        // Nxd5 wins the queen and Nxe2 the rook. Both are searched without a cap, since the
+        // rook is still worth enough to pass delta pruning after the queen raised alpha.
+        // After either Black has nothing to capture, so every child is a single node
+        let mut board = Board::from_fen("7k/8/8/3q4/8/2N4K/4r3/8 w - - 0 1");
+        let qsearch_with = |max_qsearch_captures, board: &mut Board| {
+            let conf = SearchConfig {
+                emit_info: false,
+                max_qsearch_captures,
+                ..Default::default()
+            };
+            let mut search = AlphaBetaSearch::new().with_config(conf).unwrap();
+            search.in_progress = true;
+            let score =
+                search.quiescence_search(board, SearchContext::root(), 0, -MATE_SCORE, MATE_SCORE);
+            (score, search.nodes_searched)
+        };
+
+        let (full_score, full_nodes) = qsearch_with(None, &mut board);
+        let (capped_score, capped_nodes) = qsearch_with(Some(1), &mut board);
+
+        // The root and both captures, against the root and the queen capture only
+        assert_eq!(full_nodes, 3);
+        assert_eq!(capped_nodes, 2);
+        // Still finds the queen capture, Nxe2 would leave White a queen for a knight down
+        assert_eq!(capped_score, full_score);
+        assert!(capped_score > -500, "{capped_score}");
+    }
+
+    #[test]
+    fn test_contempt_ramps_down_in_endgame() {
+        let params = SearchParams {
+            contempt: 40,
+            ..Default::default()
+        };
+        assert_eq!(params.effective_contempt(Phase(MIDGAME_PHASE)), 40);
+        assert_eq!(params.effective_contempt(Phase(ENDGAME_PHASE)), 0);
+
+        let mut prev = params.effective_contempt(Phase(MIDGAME_PHASE));
+        for phase in (MIDGAME_PHASE..=ENDGAME_PHASE).step_by(16) {
+            let contempt = params.effective_contempt(Phase(phase));
+            assert!(contempt <= prev, "contempt grew at phase {phase}");
+            prev = contempt;
+        }
+
+        // Real positions: full board vs bare kings
+        let start = Board::new();
+        let kings = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(params.effective_contempt(start.game_phase()), 40);
+        assert_eq!(params.effective_contempt(kings.game_phase()), 0);
+
+        // Scale of 0 keeps contempt constant
+        let flat = SearchParams {
+            contempt_endgame_scale: 0,
+            ..params
+        };
+        assert_eq!(flat.effective_contempt(Phase(ENDGAME_PHASE)), 40);
+
+        // Draw score is from the root side's point of view
+        let mut search = AlphaBetaSearch::new().with_params(params);
+        search.root_stm = Side::White;
+        assert_eq!(search.draw_score(&start), -40);
+        let black_to_move =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(search.draw_score(&black_to_move), 40);
+    }
+
+    #[test]
+    fn test_limit_strength() {
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        let strength = StrengthLimit::from_elo(StrengthLimit::MIN_ELO);
+        assert!(strength.depth_cap < StrengthLimit::from_elo(StrengthLimit::MAX_ELO).depth_cap);
+        assert_eq!(
+            StrengthLimit::from_elo(StrengthLimit::MAX_ELO).blunder_permille,
+            0
+        );
+
+        let mut blunders = 0;
+        for fen in [
+            START_FEN,
+            KIWIPETE,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        ] {
+            let board = Board::from_fen(fen);
+
+            let mut weak = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(10));
+            weak.set_strength(Some(strength));
+            let weak_result = weak.find_best_move(&board);
+            assert!(weak_result.depth <= strength.depth_cap);
+
+            // Same result is deterministic
+            let mut again = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(10));
+            again.set_strength(Some(strength));
+            assert_eq!(
+                again.find_best_move(&board).best_move,
+                weak_result.best_move
+            );
+
+            let mut full = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(weak_result.depth));
+            if full.find_best_move(&board).best_move != weak_result.best_move {
+                blunders += 1;
+            }
+        }
+        assert!(
+            blunders > 0,
+            "Low elo should pick a non-best move sometimes"
+        );
+    }
+
+    #[test]
+    fn test_prefers_shortest_mate() {
+        // TT adjustments are inverses of each other for wins and losses
+        for score in [MATE_SCORE - 3, -MATE_SCORE + 4, 150] {
+            for ply in [0, 1, 7] {
+                assert_eq!(
+                    adjust_score_for_ply(adjust_score_from_ply(score, ply), ply),
+                    score
+                );
+            }
+        }
+
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(6));
+
+        // Rh8# mates at once, slower mates like Rh7 Kb8 Rh8# are available too
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1");
+        let result = search.find_best_move(&board);
+        assert_eq!(result.best_move.unwrap().uci(), "h1h8");
+        assert_eq!(result.score, MATE_SCORE - 1);
+        assert!(result.is_mate);
+        assert_eq!(result.mate_in, Some(1));
+
+        // The losing side sees how long it has left
+        search.clear();
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/6R1 b - - 0 1");
+        let result = search.find_best_move(&board);
+        assert_eq!(result.best_move.unwrap().uci(), "a8b8");
+        assert_eq!(result.score, -MATE_SCORE + 2);
+        assert_eq!(result.mate_in, Some(-1));
+    }
+
+    #[test]
+    fn test_mate_on_fiftieth_move_wins() {
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(4));
+
+        // Rh8# completes the fifty moves, but mate comes first
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 99 80");
+        let result = search.find_best_move(&board);
+        assert_eq!(result.best_move.unwrap().uci(), "h1h8");
+        assert_eq!(result.score, MATE_SCORE - 1);
+    }
+
+    #[test]
+    fn test_depth_and_time_limits() {
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        let board = Board::from_fen(KIWIPETE);
+        let search_with = |limits: SearchLimits| {
+            AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(limits)
+                .find_best_move(&board)
+        };
+        let budget = Duration::from_millis(50);
+        // Slack for the last node batch and a slow CI machine
+        let slack = Duration::from_millis(250);
+
+        // Depth only completes the depth
+        let result = search_with(SearchLimits::depth(4));
+        assert_eq!(result.depth, 4);
+
+        // Time only stops on time, long before running out of depth
+        let result = search_with(SearchLimits::time(50));
+        assert!(
+            result.time_taken < budget + slack,
+            "{:?}",
+            result.time_taken
+        );
+        assert!(result.depth < MAX_PLY as u16);
+        assert!(result.best_move.is_some());
+
+        // Both set, the depth comes first
+        let result = search_with(SearchLimits {
+            max_depth: Some(3),
+            max_time: Some(Duration::from_secs(60)),
+            ..Default::default()
+        });
+        assert_eq!(result.depth, 3);
+
+        // Both set, the time comes first, even in the middle of an iteration
+        let result = search_with(SearchLimits {
+            max_depth: Some(60),
+            max_time: Some(budget),
+            ..Default::default()
+        });
+        assert!(
+            result.time_taken < budget + slack,
+            "{:?}",
+            result.time_taken
+        );
+        assert!(result.depth < 60);
+    }
+
+    #[test]
+    fn test_search_survives_tt_collisions() {
+        // Every position shares a single cluster
+        let conf = SearchConfig {
+            emit_info: false,
+            tt_index_bits: Some(0),
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(5));
+
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1");
+        let result = search.find_best_move(&board);
+        assert_eq!(result.best_move.unwrap().uci(), "h1h8");
+        assert_eq!(result.score, MATE_SCORE - 1);
+
+        // Takes the hanging queen
+        search.clear();
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1");
+        let result = search.find_best_move(&board);
+        assert_eq!(result.best_move.unwrap().uci(), "d2d5");
+
+        search.clear();
+        let board = Board::from_fen(KIWIPETE);
+        let result = search.find_best_move(&board);
+        let mut legal = MoveBuffer::new();
+        board.generate_legal_moves(&mut legal, false);
+        assert!(legal.contains(&result.best_move.unwrap()));
+        // Every PV move is legal in its position
+        let pv = result.pv.unwrap();
+        assert_eq!(board.san_line(&pv).len(), pv.len());
+    }
 }
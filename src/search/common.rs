@@ -1,7 +1,14 @@
 use tracing::debug_span;
 
-use crate::prelude::*;
-use std::{ops::Add, time::Duration};
+use crate::{
+    prelude::*,
+    search::{alpha_beta::ONE_PLY, tt::TtReplacement},
+};
+use std::{
+    ops::Add,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
 
 /// Common statistics for all search types
 #[derive(Debug, Clone, Copy)]
@@ -16,6 +23,7 @@ pub struct SearchStats {
     // Node type
     pub main_search_nodes: u64, // Only main search nodes (excludes qsearch)
     pub qsearch_nodes: u64,     // Quiescence search nodes
+    pub qsearch_max_depth: u16, // Deepest ply reached inside quiescence search
 
     // Early exit tracking
     pub tt_exact_returns: u64, // Returned exact score from TT
@@ -37,14 +45,24 @@ pub struct SearchStats {
     pub lmr_attempts: u64,       // Times LMR was attempted
     pub lmr_research: u64,       // Times LMR failed high and re-search was needed
 
+    // Extensions
+    pub extended_moves: u64,       // Moves searched with a non-zero extension
+    pub extension_total: u64,      // Sum of applied extensions, in fractions of `ONE_PLY`
+    pub max_branch_extension: u16, // Largest total extension along a single branch
+    pub recapture_extensions: u64, // Recaptures on the previous move's square that were extended
+
     // QSearch Pruning
     pub delta_pruning_cutoffs: u64, // Times delta pruning succeeded
     pub see_pruning_cutoffs: u64,   // Times SEE pruning helped
 
+    // Main search SEE pruning
+    pub main_see_pruning_cutoffs: u64, // Losing captures skipped at shallow depth
+
     // Aspiration windows
-    pub asp_fail_high: u64, // Times aspiration window failed high
-    pub asp_fail_low: u64,  // Times aspiration window failed low
-    pub asp_research: u64,  // Total re-searches due to ASP
+    pub asp_fail_high: u64,         // Times aspiration window failed high
+    pub asp_fail_low: u64,          // Times aspiration window failed low
+    pub asp_research: u64,          // Total re-searches due to ASP
+    pub asp_oscillation_widen: u64, // Windows widened to the swing of oscillating iterations
 
     // Alpha-Beta window
     pub beta_cutoffs_main: u64, // Times alpha >= beta (fail-high) in main search
@@ -66,6 +84,7 @@ impl Default for SearchStats {
             hash_full: Default::default(),
             pruned_nodes: Default::default(),
             qsearch_nodes: Default::default(),
+            qsearch_max_depth: Default::default(),
             tt_probes: Default::default(),
             tt_hits: Default::default(),
             tt_cutoffs: Default::default(),
@@ -83,11 +102,17 @@ impl Default for SearchStats {
             null_move_attempts: Default::default(),
             lmr_attempts: Default::default(),
             lmr_research: Default::default(),
+            extended_moves: Default::default(),
+            extension_total: Default::default(),
+            max_branch_extension: Default::default(),
+            recapture_extensions: Default::default(),
             asp_fail_high: Default::default(),
             asp_fail_low: Default::default(),
             asp_research: Default::default(),
+            asp_oscillation_widen: Default::default(),
             delta_pruning_cutoffs: Default::default(),
             see_pruning_cutoffs: Default::default(),
+            main_see_pruning_cutoffs: Default::default(),
         }
     }
 }
@@ -115,6 +140,7 @@ impl Add for SearchStats {
 
             main_search_nodes: self.main_search_nodes + rhs.main_search_nodes,
             qsearch_nodes: self.qsearch_nodes + rhs.qsearch_nodes,
+            qsearch_max_depth: self.qsearch_max_depth.max(rhs.qsearch_max_depth),
 
             tt_exact_returns: self.tt_exact_returns + rhs.tt_exact_returns,
             draw_returns: self.draw_returns + rhs.draw_returns,
@@ -132,12 +158,19 @@ impl Add for SearchStats {
             lmr_attempts: self.lmr_attempts + rhs.lmr_attempts,
             lmr_research: self.lmr_research + rhs.lmr_research,
 
+            extended_moves: self.extended_moves + rhs.extended_moves,
+            extension_total: self.extension_total + rhs.extension_total,
+            max_branch_extension: self.max_branch_extension.max(rhs.max_branch_extension),
+            recapture_extensions: self.recapture_extensions + rhs.recapture_extensions,
+
             delta_pruning_cutoffs: self.delta_pruning_cutoffs + rhs.delta_pruning_cutoffs,
             see_pruning_cutoffs: self.see_pruning_cutoffs + rhs.see_pruning_cutoffs,
+            main_see_pruning_cutoffs: self.main_see_pruning_cutoffs + rhs.main_see_pruning_cutoffs,
 
             asp_fail_high: self.asp_fail_high + rhs.asp_fail_high,
             asp_fail_low: self.asp_fail_low + rhs.asp_fail_low,
             asp_research: self.asp_research + rhs.asp_research,
+            asp_oscillation_widen: self.asp_oscillation_widen + rhs.asp_oscillation_widen,
 
             beta_cutoffs_main: self.beta_cutoffs_main + rhs.beta_cutoffs_main,
             beta_cutoffs_qs: self.beta_cutoffs_qs + rhs.beta_cutoffs_qs,
@@ -183,6 +216,15 @@ impl SearchStats {
         }
     }
 
+    /// Average extension per main search node, in plies
+    pub fn avg_extension(&self) -> f64 {
+        if self.main_search_nodes == 0 {
+            0.0
+        } else {
+            self.extension_total as f64 / (ONE_PLY as f64 * self.main_search_nodes as f64)
+        }
+    }
+
     pub fn log_summary(&self) {
         let _span = debug_span!("search_stats").entered();
         debug!("=> SEARCH STATISTICS (depth {})", self.depth_reached);
@@ -237,7 +279,10 @@ impl SearchStats {
 
         // QSearch Analysis
         debug!("");
-        debug!("==> QSearch ({} nodes)", self.qsearch_nodes);
+        debug!(
+            "==> QSearch ({} nodes, max depth {})",
+            self.qsearch_nodes, self.qsearch_max_depth
+        );
         debug!(
             "  - Beta Cutoffs:      {:>9} ({:>6.2}%)",
             self.beta_cutoffs_qs,
@@ -260,6 +305,7 @@ impl SearchStats {
             self.tt_cutoffs,
             Self::percent(self.tt_cutoffs, self.tt_hits)
         );
+        debug!("  - SEE Pruned:       {:>9}", self.main_see_pruning_cutoffs);
         debug!("  - NMP Attempts:     {:>9}", self.null_move_attempts);
         debug!(
             "    - NMP Cutoffs:    {:>9} ({:>6.2}% success rate)",
@@ -272,11 +318,22 @@ impl SearchStats {
             self.lmr_research,
             Self::percent(self.lmr_research, self.lmr_attempts)
         );
+        debug!(
+            "  - Extensions:       {:>9} (avg {:.3} ply/node, max branch {:.2} ply)",
+            self.extended_moves,
+            self.avg_extension(),
+            self.max_branch_extension as f64 / ONE_PLY as f64
+        );
+        debug!("    - Recaptures:     {:>9}", self.recapture_extensions);
         if self.asp_research > 0 {
             debug!(
                 "  - ASP Researches:   {:>9} (high: {}, low: {})",
                 self.asp_research, self.asp_fail_high, self.asp_fail_low
             );
+            debug!(
+                "    - Oscillation:    {:>9} widened",
+                self.asp_oscillation_widen
+            );
         }
 
         // Move Ordering
@@ -314,8 +371,43 @@ pub struct SearchConfig {
     pub enable_lmr: bool,
     pub emit_info: bool,
     pub sing_ext: bool,
+    pub enable_recapture_ext: bool,
+    /// Skip losing captures at shallow non-PV nodes, see `SearchParams::see_prune_max_depth`
+    pub enable_main_see_pruning: bool,
+    /// Widen aspiration windows to the score swing when iterations oscillate
+    pub asp_widen_on_oscillation: bool,
+    /// Max plies searched in quiescence, from the qsearch entry ply
+    pub max_qsearch_depth: u16,
+    /// Transposition table replacement policy
+    pub tt_replacement: TtReplacement,
+    /// Index the TT with only this many bits of the hash, to stress test collision handling.
+    /// Debug only, see `TranspositionTable::set_index_bits`
+    pub tt_index_bits: Option<u8>,
+    /// Pawn hash table size, 0 disables pawn structure caching
+    pub pawn_hash_size_kb: usize,
+    /// Format of the per-depth info lines
+    pub info_format: InfoFormat,
     pub collect_stats: bool, // TODO: feature-gate this
     pub hash_size_mb: usize,
+    /// Rehash existing TT entries when the hash size changes, instead of clearing the table
+    pub preserve_hash_on_resize: bool,
+    /// Break move ordering ties by move encoding instead of random noise
+    pub deterministic: bool,
+    /// Record the search tree up to this ply, see `search::tree_dump`. Debug only, slow
+    pub dump_tree: Option<u16>,
+    /// Report the whole principal variation, followed through the TT, instead of just the best move
+    pub full_pv: bool,
+    /// How quiet moves are looked up in the history table
+    pub history_indexing: HistoryIndexing,
+    /// Return and store the best score found even if it's outside the (alpha, beta) window,
+    /// instead of clamping it to the window bound it crossed
+    pub fail_soft: bool,
+    /// Search at most this many moves at each quiescence node that isn't in check, the best
+    /// ones by SEE. `None` searches all of them
+    pub max_qsearch_captures: Option<usize>,
+    /// Evaluate with material only, see `evaluation::material::evaluate_material`.
+    /// A baseline for testing search changes apart from the eval
+    pub material_only_eval: bool,
 }
 
 impl Default for SearchConfig {
@@ -326,13 +418,173 @@ impl Default for SearchConfig {
             enable_lmr: true,
             emit_info: true,
             sing_ext: false,
+            enable_recapture_ext: false,
+            enable_main_see_pruning: false,
+            asp_widen_on_oscillation: false,
+            max_qsearch_depth: 32,
+            tt_replacement: TtReplacement::default(),
+            tt_index_bits: None,
+            pawn_hash_size_kb: 1024,
+            info_format: InfoFormat::default(),
             collect_stats: true, // Disabled for perf
             hash_size_mb: 16,
+            preserve_hash_on_resize: false,
+            deterministic: false,
+            dump_tree: None,
+            full_pv: false,
+            history_indexing: HistoryIndexing::default(),
+            fail_soft: false,
+            max_qsearch_captures: None,
+            material_only_eval: false,
         }
     }
 }
 
-/// Search limits (time, depth, nodes)
+impl SearchConfig {
+    /// `UCI_AnalyseMode`. Turns off the forward pruning and reductions that can hide a
+    /// good move (NMP, LMR and main search SEE pruning) and reports the full PV.
+    /// Disabling it puts those flags back to their defaults
+    pub fn set_analyse_mode(&mut self, enabled: bool) {
+        let default = Self::default();
+        self.enable_nmp = !enabled && default.enable_nmp;
+        self.enable_lmr = !enabled && default.enable_lmr;
+        self.enable_main_see_pruning = !enabled && default.enable_main_see_pruning;
+        self.full_pv = enabled || default.full_pv;
+    }
+}
+
+/// Format of the info lines emitted after every completed depth
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InfoFormat {
+    /// Standard `info depth .. score cp ..` strings
+    #[default]
+    Uci,
+    /// One JSON object per line, for tools that don't want to parse UCI text
+    Json,
+}
+
+/// Index of a quiet move in the history table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistoryIndexing {
+    /// `[from][to]`
+    #[default]
+    FromTo,
+    /// `[moving piece][to]`, the piece including its colour
+    PieceTo,
+}
+
+/// Pruning, reduction and extension thresholds used in the hot paths of search.
+/// Defaults match the previously hardcoded values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchParams {
+    /// Late Move Reductions
+    pub lmr_min_depth: u16,
+    pub lmr_min_move_index: usize,
+    pub lmr_base: f32,
+    pub lmr_divisor: f32,
+    /// Null Move Pruning
+    pub nmp_min_depth: u16,
+    pub nmp_reduction: u16,
+    /// Depth at and above which `nmp_deep_reduction` is used instead of `nmp_reduction`
+    pub nmp_deep_depth: u16,
+    pub nmp_deep_reduction: u16,
+    /// Aspiration Windows
+    pub asp_start_window: i32,
+    pub asp_max_window: i32,
+    /// Singular Extensions
+    pub se_min_depth: u16,
+    pub se_margin: i32,
+    /// QSearch pruning
+    pub delta_margin: i32,
+    pub qs_see_threshold: i32,
+    /// Main search SEE pruning. Captures losing more than `see_prune_margin * depth` are
+    /// skipped at depths up to `see_prune_max_depth`
+    pub see_prune_max_depth: u16,
+    pub see_prune_margin: i32,
+    /// Draw score penalty for the root side, in cp, with full material on the board
+    pub contempt: i32,
+    /// How much of `contempt` is gone by the endgame, in percent.
+    /// 100 ramps it down to 0 as the phase approaches `ENDGAME_PHASE`
+    pub contempt_endgame_scale: i32,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self {
+            lmr_min_depth: 3,
+            lmr_min_move_index: 3,
+            lmr_base: 0.20,
+            lmr_divisor: 3.35,
+            nmp_min_depth: 5,
+            nmp_reduction: 2,
+            nmp_deep_depth: 6,
+            nmp_deep_reduction: 4,
+            asp_start_window: 48,
+            asp_max_window: 4096,
+            se_min_depth: 8,
+            se_margin: 2,
+            delta_margin: 700,
+            qs_see_threshold: -100,
+            see_prune_max_depth: 3,
+            see_prune_margin: 100,
+            contempt: 0,
+            contempt_endgame_scale: 100,
+        }
+    }
+}
+
+impl SearchParams {
+    /// Contempt scaled by game phase. Draws are fine to avoid in the middlegame, but in
+    /// (mostly drawn) endgames the engine shouldn't be playing on for a non-existent win
+    pub fn effective_contempt(&self, phase: Phase) -> i32 {
+        let phase = phase.0.clamp(MIDGAME_PHASE, ENDGAME_PHASE);
+        let ramp = phase * self.contempt_endgame_scale.clamp(0, 100) / 100;
+        self.contempt * (ENDGAME_PHASE - ramp) / ENDGAME_PHASE
+    }
+}
+
+/// Weakened play for `UCI_LimitStrength`.
+/// The engine searches to at most `depth_cap` plies, and with a `blunder_permille`
+/// chance plays a random legal move instead of the best one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrengthLimit {
+    pub elo: u16,
+    pub depth_cap: u16,
+    pub blunder_permille: u16,
+}
+
+impl StrengthLimit {
+    pub const MIN_ELO: u16 = 800;
+    pub const MAX_ELO: u16 = 2800;
+    /// Depth cap at `MIN_ELO`, grows linearly up to `MAX_DEPTH_CAP` at `MAX_ELO`
+    const MIN_DEPTH_CAP: u16 = 1;
+    const MAX_DEPTH_CAP: u16 = 16;
+    /// Blunder chance at `MIN_ELO`, shrinks linearly down to 0 at `MAX_ELO`
+    const MAX_BLUNDER_PERMILLE: u16 = 400;
+    /// Fixed seed for the blunder PRNG, mixed with the position hash
+    pub(crate) const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    /// Maps `elo` (clamped to `MIN_ELO..=MAX_ELO`) to a depth cap and blunder chance
+    pub fn from_elo(elo: u16) -> Self {
+        let elo = elo.clamp(Self::MIN_ELO, Self::MAX_ELO);
+        let range = (Self::MAX_ELO - Self::MIN_ELO) as u32;
+        let above_min = (elo - Self::MIN_ELO) as u32;
+        let below_max = range - above_min;
+
+        let depth_span = (Self::MAX_DEPTH_CAP - Self::MIN_DEPTH_CAP) as u32;
+        let depth_cap = Self::MIN_DEPTH_CAP + (above_min * depth_span / range) as u16;
+        let blunder_permille = (below_max * Self::MAX_BLUNDER_PERMILLE as u32 / range) as u16;
+
+        Self {
+            elo,
+            depth_cap,
+            blunder_permille,
+        }
+    }
+}
+
+/// Search limits (time, depth, nodes). Any combination can be set, the search stops at
+/// whichever is hit first. Time and nodes are checked mid-iteration, depth between iterations
 #[derive(Default, Debug, Clone, Copy)]
 pub struct SearchLimits {
     pub max_depth: Option<u16>,
@@ -368,6 +620,36 @@ impl SearchLimits {
     }
 }
 
+/// Best move of the last completed iteration, published by the search thread so others can
+/// read it mid-search without taking the search lock. Move, score and depth are packed into
+/// one atomic so a reader never sees them torn
+#[derive(Debug, Default)]
+pub struct SharedBestMove(AtomicU64);
+
+impl SharedBestMove {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Depth 0 is reserved for an empty slot, so completed iterations always publish
+    pub fn publish(&self, mv: Move, score: i32, depth: u16) {
+        debug_assert!(depth > 0, "Only completed iterations are published");
+        let packed = (depth as u64) << 48 | (score as u32 as u64) << 16 | mv.0 as u64;
+        self.0.store(packed, Ordering::Release);
+    }
+
+    /// Best move, score and depth of the last completed iteration
+    pub fn load(&self) -> Option<(Move, i32, u16)> {
+        let packed = self.0.load(Ordering::Acquire);
+        let depth = (packed >> 48) as u16;
+        (depth > 0).then_some((Move(packed as u16), (packed >> 16) as u32 as i32, depth))
+    }
+
+    pub fn clear(&self) {
+        self.0.store(0, Ordering::Release);
+    }
+}
+
 /// Result of a search
 #[derive(Debug, Default, Clone)]
 pub struct SearchResult {
@@ -426,6 +708,16 @@ pub fn adjust_score_from_ply(score: i32, ply: usize) -> i32 {
     }
 }
 
+/// Moves until mate for a root relative mate score, negative if the side to move gets mated.
+/// `None` if `score` isn't a mate score
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score.abs() <= MATE_THRESHOLD {
+        return None;
+    }
+    let moves = (MATE_SCORE - score.abs() + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
 #[inline(always)]
 pub fn has_non_pawn_material(board: &Board) -> bool {
     let side = board.stm;
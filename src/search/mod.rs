@@ -9,6 +9,7 @@ pub mod alpha_beta;
 pub mod common;
 pub mod move_ordering;
 pub mod move_picker;
+pub mod tree_dump;
 pub mod tt;
 
 use std::sync::{Arc, atomic::AtomicBool};
@@ -17,7 +18,7 @@ pub use common::{SearchResult, SearchStats};
 
 use crate::{
     prelude::*,
-    search::common::{SearchConfig, SearchLimits},
+    search::common::{SearchConfig, SearchLimits, SharedBestMove},
     tuning::params::TunableParams,
 };
 
@@ -27,7 +28,8 @@ pub trait SearchEngine: Send {
     /// Search for the best move from the current position
     fn search(&mut self, board: &Board) -> SearchResult;
 
-    fn init(self, search_running: Arc<AtomicBool>) -> Self::Output;
+    /// Hooks up the stop flag and the slot the best move of every completed depth goes to
+    fn init(self, search_running: Arc<AtomicBool>, best_move: Arc<SharedBestMove>) -> Self::Output;
 
     /// Set maximum search depth
     fn set_depth(&mut self, depth: u16);
@@ -62,6 +64,8 @@ pub trait SearchEngine: Send {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use crate::{search::common::SearchLimits, utils::log::init};
 
     use super::*;
@@ -117,4 +121,140 @@ mod tests {
             "NMP should reduce node count"
         );
     }
+
+    #[test]
+    fn test_aborted_iteration_is_discarded() {
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        let board = Board::from_fen(KIWIPETE);
+
+        // Tight movetime, so the search is stopped in the middle of an iteration
+        let mut timed = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::time(50));
+        let timed_result = timed.find_best_move(&board);
+        assert!(timed_result.depth >= 1, "Should complete at least depth 1");
+
+        // Fresh search to the last fully completed depth
+        let mut fixed = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(timed_result.depth));
+        let fixed_result = fixed.find_best_move(&board);
+
+        assert_eq!(timed_result.best_move, fixed_result.best_move);
+        assert_eq!(timed_result.score, fixed_result.score);
+    }
+
+    #[test]
+    fn test_check_extensions_are_bounded() {
+        use crate::search::alpha_beta::{EXT_BUDGET, ONE_PLY};
+
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        // Black's king is exposed to a long series of queen and rook checks
+        let board = Board::from_fen("6k1/8/8/8/8/8/1Q6/1R4K1 w - - 0 1");
+
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(6));
+        search.find_best_move(&board);
+        let stats = search.get_stats();
+
+        assert!(stats.extended_moves > 0, "Checks should be extended");
+        assert!(stats.max_branch_extension >= ONE_PLY);
+        assert!(stats.max_branch_extension <= EXT_BUDGET);
+    }
+
+    #[test]
+    fn test_recapture_extension() {
+        // d5 is attacked by Nc3 and Rd1, and defended only by Nf6.
+        // Nxd5 Nxd5 Rxd5 wins a pawn
+        let board = Board::from_fen("6k1/8/5n2/3p4/8/2N5/8/3R2K1 w - - 0 1");
+        let d5 = Square::from_str("d5").unwrap();
+
+        let search_with = |enable_recapture_ext| {
+            let conf = SearchConfig {
+                emit_info: false,
+                enable_recapture_ext,
+                ..Default::default()
+            };
+            let mut search = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(4));
+            let result = search.find_best_move(&board);
+            (result, search.get_stats())
+        };
+
+        let (result, stats) = search_with(true);
+        assert!(stats.recapture_extensions > 0);
+        let best_move = result.best_move.unwrap();
+        assert_eq!(best_move.to_sq(), d5, "Should win the d5 pawn");
+        assert!(result.score > 0);
+
+        let (_, stats) = search_with(false);
+        assert_eq!(stats.recapture_extensions, 0);
+    }
+
+    #[test]
+    fn test_asp_widens_on_oscillation() {
+        // Scores from the start position swing between odd and even depths
+        let board = Board::from_fen(START_FEN);
+
+        let search_with = |asp_widen_on_oscillation| {
+            let conf = SearchConfig {
+                emit_info: false,
+                asp_widen_on_oscillation,
+                ..Default::default()
+            };
+            let mut search = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(6));
+            search.find_best_move(&board);
+            search.get_stats()
+        };
+
+        let with = search_with(true);
+        let without = search_with(false);
+
+        assert!(with.asp_oscillation_widen > 0);
+        assert_eq!(without.asp_oscillation_widen, 0);
+        assert!(with.asp_research <= without.asp_research);
+    }
+
+    #[test]
+    fn test_qsearch_depth_limit() {
+        // Lots of pieces bearing on d5, so the capture sequence there runs long
+        let board =
+            Board::from_fen("3r1rk1/1b1q1pp1/1pnbpn1p/3p4/2PP4/1PNBPN1P/1B1Q1PP1/3R1RK1 w - - 0 1");
+
+        let search_with = |max_qsearch_depth| {
+            let conf = SearchConfig {
+                emit_info: false,
+                max_qsearch_depth,
+                ..Default::default()
+            };
+            let mut search = AlphaBetaSearch::new()
+                .with_config(conf)
+                .unwrap()
+                .with_limits(SearchLimits::depth(2));
+            search.find_best_move(&board);
+            search.get_stats()
+        };
+
+        let unbounded = search_with(SearchConfig::default().max_qsearch_depth);
+        assert!(unbounded.qsearch_max_depth > 2);
+
+        let bounded = search_with(2);
+        assert!(bounded.qsearch_max_depth <= 2);
+        assert!(bounded.qsearch_nodes < unbounded.qsearch_nodes);
+    }
 }
@@ -1,6 +1,7 @@
 use std::mem::MaybeUninit;
 
 use crate::prelude::*;
+use crate::search::common::HistoryIndexing;
 
 // Using large offsets to create distinct "buckets" for move types.
 // This ensures that any capture is scored higher than any killer move, etc.
@@ -10,13 +11,117 @@ const KILLER_MOVE_SCORE: i32 = 1_000_000;
 const PROMOTION_SCORE: i32 = 1_000_000;
 const BAD_CAPTURE_SCORE: i32 = 900_000;
 
+/// Capture history entries stay within `-CAPTURE_HISTORY_MAX..=CAPTURE_HISTORY_MAX`
+const CAPTURE_HISTORY_MAX: i32 = 16_384;
+/// Capture history is divided by this before it's added to the SEE score, so it reorders
+/// captures within their bucket but never moves one into another bucket
+const CAPTURE_HISTORY_DIVISOR: i32 = 16;
+
+/// History heuristic scores of moves that caused beta cutoffs
+#[derive(Debug, Clone)]
+pub struct HistoryTable {
+    indexing: HistoryIndexing,
+    /// Quiet moves. Rows are the from square, or `side * NUM_PIECES + piece` with `PieceTo`
+    table: [[i32; NUM_SQUARES]; NUM_SQUARES],
+    /// Captures, as `[side * NUM_PIECES + piece][to][captured piece]`
+    captures: [[[i32; NUM_PIECES]; NUM_SQUARES]; NUM_SIDES * NUM_PIECES],
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        Self::new(HistoryIndexing::default())
+    }
+}
+
+impl HistoryTable {
+    /// All zeros, for callers that don't keep a history
+    pub const EMPTY: Self = Self::new(HistoryIndexing::FromTo);
+
+    pub const fn new(indexing: HistoryIndexing) -> Self {
+        Self {
+            indexing,
+            table: [[0; NUM_SQUARES]; NUM_SQUARES],
+            captures: [[[0; NUM_PIECES]; NUM_SQUARES]; NUM_SIDES * NUM_PIECES],
+        }
+    }
+
+    pub fn indexing(&self) -> HistoryIndexing {
+        self.indexing
+    }
+
+    /// `board` is the position `mv` is played from
+    #[inline(always)]
+    fn row(&self, board: &Board, mv: Move) -> usize {
+        match self.indexing {
+            HistoryIndexing::FromTo => mv.from_idx() as usize,
+            HistoryIndexing::PieceTo => {
+                let piece = board.get_piece_at(mv.from_sq()).unwrap_or(Piece::Pawn);
+                board.stm.index() * NUM_PIECES + piece.index()
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, board: &Board, mv: Move) -> i32 {
+        self.table[self.row(board, mv)][mv.to_idx() as usize]
+    }
+
+    pub fn add(&mut self, board: &Board, mv: Move, bonus: i32) {
+        let row = self.row(board, mv);
+        self.table[row][mv.to_idx() as usize] += bonus;
+    }
+
+    /// Index into `captures` for the capture `mv`, played from `board`
+    #[inline(always)]
+    fn capture_index(board: &Board, mv: Move) -> (usize, usize, usize) {
+        let piece = board.get_piece_at(mv.from_sq()).unwrap_or(Piece::Pawn);
+        let captured = if mv.is_enpassant() {
+            Piece::Pawn
+        } else {
+            board.get_piece_at(mv.to_sq()).unwrap_or(Piece::Pawn)
+        };
+        (
+            board.stm.index() * NUM_PIECES + piece.index(),
+            mv.to_idx() as usize,
+            captured.index(),
+        )
+    }
+
+    #[inline(always)]
+    pub fn get_capture(&self, board: &Board, mv: Move) -> i32 {
+        let (piece, to, captured) = Self::capture_index(board, mv);
+        self.captures[piece][to][captured]
+    }
+
+    /// Moves the entry towards `CAPTURE_HISTORY_MAX` (or its negative for a negative `bonus`),
+    /// by less the closer it already is, so entries stay bounded
+    pub fn add_capture(&mut self, board: &Board, mv: Move, bonus: i32) {
+        let (piece, to, captured) = Self::capture_index(board, mv);
+        let entry = &mut self.captures[piece][to][captured];
+        let bonus = bonus.clamp(-CAPTURE_HISTORY_MAX, CAPTURE_HISTORY_MAX);
+        *entry += bonus - *entry * bonus.abs() / CAPTURE_HISTORY_MAX;
+    }
+
+    /// Halves every entry
+    pub fn decay(&mut self) {
+        for row in self.table.iter_mut() {
+            for score in row.iter_mut() {
+                *score /= 2;
+            }
+        }
+        for score in self.captures.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+    }
+}
+
 pub trait MoveScoringPolicy {
     fn score(
         board: &Board,
         mv: Move,
         killers: &[Option<Move>; 2],
         tt_move: Option<Move>,
-        history: &[[i32; NUM_SQUARES]; NUM_SQUARES],
+        history: &HistoryTable,
     ) -> i32;
 }
 
@@ -28,27 +133,30 @@ impl MoveScoringPolicy for MainSearchPolicy {
         mv: Move,
         killers: &[Option<Move>; 2],
         tt_move: Option<Move>,
-        history: &[[i32; NUM_SQUARES]; NUM_SQUARES],
+        history: &HistoryTable,
     ) -> i32 {
         if tt_move.is_some_and(|tt_mv| tt_mv == mv) {
             return TT_MOVE_SCORE;
         }
         if mv.is_capture() {
             let see_score = board.static_exchange_evaluation(mv);
+            let history_score = history.get_capture(board, mv) / CAPTURE_HISTORY_DIVISOR;
             if see_score > 0 {
                 // Most Valuable Victim - Least Valuable Attacker
-                MVV_LVA_OFFSET + see_score
+                MVV_LVA_OFFSET + see_score + history_score
             } else {
-                BAD_CAPTURE_SCORE + see_score
+                BAD_CAPTURE_SCORE + see_score + history_score
             }
         } else if killers.contains(&Some(mv)) {
             KILLER_MOVE_SCORE
         } else {
-            history[mv.from_idx() as usize][mv.to_idx() as usize]
+            history.get(board, mv)
         }
     }
 }
 
+/// Orders qsearch moves by SEE: winning and even exchanges first, then quiet promotions,
+/// then losing captures that survived SEE pruning
 pub struct QSearchPolicy;
 
 impl MoveScoringPolicy for QSearchPolicy {
@@ -57,10 +165,15 @@ impl MoveScoringPolicy for QSearchPolicy {
         mv: Move,
         _killers: &[Option<Move>; 2],
         _tt_move: Option<Move>,
-        _history: &[[i32; NUM_SQUARES]; NUM_SQUARES],
+        _history: &HistoryTable,
     ) -> i32 {
         if mv.is_capture() {
-            MVV_LVA_OFFSET + board.static_exchange_evaluation(mv)
+            let see_score = board.static_exchange_evaluation(mv);
+            if see_score >= 0 {
+                MVV_LVA_OFFSET + see_score
+            } else {
+                BAD_CAPTURE_SCORE + see_score
+            }
         } else if mv.is_promotion() {
             if mv.promoted_piece() == Some(Piece::Queen) {
                 PROMOTION_SCORE
@@ -73,23 +186,28 @@ impl MoveScoringPolicy for QSearchPolicy {
     }
 }
 
-/// Sorts a slice of moves in-place from best to worst based on their score
+/// Sorts a slice of moves in-place from best to worst based on their score.
+/// With a `seed`, a little noise breaks ties between equal scores. Without one,
+/// ties are broken by the move encoding
 pub fn sort_moves<P: MoveScoringPolicy>(
     board: &Board,
     moves: &mut [Move],
     killers: &[Option<Move>; 2],
     tt_move: Option<Move>,
-    history: &[[i32; 64]; 64],
-    seed: u64,
+    history: &HistoryTable,
+    seed: Option<u64>,
 ) {
     let num_moves = moves.len();
     let mut scored_moves: [MaybeUninit<(i32, Move)>; MAX_MOVES] =
         unsafe { MaybeUninit::uninit().assume_init() };
-    let mut prng = Prng::init(seed);
+    let mut prng = seed.map(Prng::init);
 
     for i in 0..num_moves {
         let base_score = P::score(board, moves[i], killers, tt_move, history);
-        let final_score = base_score.saturating_add((prng.rand() % 10) as i32);
+        let final_score = match &mut prng {
+            Some(prng) => base_score.saturating_add((prng.rand() % 10) as i32),
+            None => base_score,
+        };
         scored_moves[i].write((-final_score, moves[i])); // Negate for descending sort
     }
 
@@ -98,7 +216,11 @@ pub fn sort_moves<P: MoveScoringPolicy>(
         std::slice::from_raw_parts_mut(ptr, num_moves)
     };
 
-    scored_slice.sort_unstable_by_key(|(score, _)| *score);
+    if prng.is_some() {
+        scored_slice.sort_unstable_by_key(|(score, _)| *score);
+    } else {
+        scored_slice.sort_unstable();
+    }
 
     for i in 0..num_moves {
         moves[i] = scored_slice[i].1;
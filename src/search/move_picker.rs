@@ -1,6 +1,6 @@
 use crate::{
     prelude::*,
-    search::move_ordering::{MainSearchPolicy, MoveScoringPolicy, QSearchPolicy},
+    search::move_ordering::{HistoryTable, MainSearchPolicy, MoveScoringPolicy, QSearchPolicy},
 };
 
 /// MovePicker: Efficiently picks moves one at a time without re-scoring.
@@ -49,7 +49,7 @@ impl<'a> MovePicker<'a> {
         moves: &'a mut [Move],
         killers: &[Option<Move>; 2],
         tt_move: Option<Move>,
-        history: &[[i32; NUM_SQUARES]; NUM_SQUARES],
+        history: &HistoryTable,
     ) -> Self {
         debug_assert!(moves.len() <= MAX_MOVES, "Too many moves");
 
@@ -77,7 +77,7 @@ impl<'a> MovePicker<'a> {
         let mut scores = [0i32; MAX_MOVES];
 
         for (i, &mv) in moves.iter().enumerate() {
-            scores[i] = QSearchPolicy::score(board, mv, &[None; 2], None, &[[0; 64]; 64]);
+            scores[i] = QSearchPolicy::score(board, mv, &[None; 2], None, &HistoryTable::EMPTY);
         }
 
         Self {
@@ -187,7 +187,7 @@ mod tests {
             moves.as_mut_slice(),
             &[None; 2],
             None,
-            &[[0; 64]; 64],
+            &HistoryTable::EMPTY,
         );
 
         // Should pick exactly original_count moves
@@ -217,7 +217,7 @@ mod tests {
             moves.as_mut_slice(),
             &[None; 2],
             None,
-            &[[0; 64]; 64],
+            &HistoryTable::EMPTY,
         );
 
         // First few moves should be captures (higher scores)
@@ -245,7 +245,7 @@ mod tests {
             moves.as_mut_slice(),
             &[None; 2],
             Some(tt_move),
-            &[[0; 64]; 64],
+            &HistoryTable::EMPTY,
         );
 
         // TT move should be picked first (highest score = TT_MOVE_SCORE)
@@ -265,7 +265,7 @@ mod tests {
             moves.as_mut_slice(),
             &[None; 2],
             None,
-            &[[0; 64]; 64],
+            &HistoryTable::EMPTY,
         );
 
         assert_eq!(picker.remaining(), total);
@@ -288,7 +288,7 @@ mod tests {
             moves.as_mut_slice(),
             &[None; 2],
             None,
-            &[[0; 64]; 64],
+            &HistoryTable::EMPTY,
         );
 
         let peeked_score = picker.peek_best_score();
@@ -310,11 +310,55 @@ mod tests {
             moves.as_mut_slice(),
             &[None; 2],
             None,
-            &[[0; 64]; 64],
+            &HistoryTable::EMPTY,
         );
 
         // Can use as iterator
         let collected: Vec<Move> = picker.collect();
         assert_eq!(collected.len(), total);
     }
+
+    #[test]
+    fn test_qsearch_picker_prefers_winning_captures() {
+        // Qxa5 takes the bigger victim but loses the queen to bxa5
+        let board = Board::from_fen("4k3/8/1p6/r7/3p4/4P3/8/Q3K3 w - - 0 1");
+        let mut moves = MoveBuffer::new();
+        board.generate_legal_moves(&mut moves, true);
+
+        let losing = Move::from_uci(&board, "a1a5").unwrap();
+        assert!(moves.contains(&losing));
+        assert!(board.static_exchange_evaluation(losing) < 0);
+
+        // Forcing moves also include quiet checks, only the captures matter here
+        let captures: Vec<Move> = MovePicker::new_qsearch(&board, moves.as_mut_slice())
+            .filter(|mv| mv.is_capture())
+            .collect();
+        assert_eq!(captures.len(), 3);
+        assert_eq!(captures.last(), Some(&losing));
+        assert!(board.static_exchange_evaluation(captures[0]) > 0);
+    }
+
+    #[test]
+    fn test_capture_history_reorders_equal_captures() {
+        // exd5 and exf5 both win a pawn
+        let board = Board::from_fen("4k3/8/8/3p1p2/4P3/8/8/4K3 w - - 0 1");
+        let exd5 = Move::from_uci(&board, "e4d5").unwrap();
+        let exf5 = Move::from_uci(&board, "e4f5").unwrap();
+        assert_eq!(
+            board.static_exchange_evaluation(exd5),
+            board.static_exchange_evaluation(exf5)
+        );
+
+        for good in [exd5, exf5] {
+            let mut history = HistoryTable::default();
+            history.add_capture(&board, good, 64);
+            assert!(history.get_capture(&board, good) > 0);
+
+            let mut moves = MoveBuffer::new();
+            board.generate_legal_moves(&mut moves, false);
+            let mut picker =
+                MovePicker::new(&board, moves.as_mut_slice(), &[None; 2], None, &history);
+            assert_eq!(picker.next_best(), Some(good), "{}", good.uci());
+        }
+    }
 }
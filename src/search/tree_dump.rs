@@ -0,0 +1,113 @@
+//! Debug dump of the search tree
+//!
+//! With `SearchConfig::dump_tree` set, every `alpha_beta` node up to that ply records the
+//! move leading to it, its depth, the window it was searched with and the score it returned.
+//! Only the last root iteration is kept. Nodes visited twice, eg. a zero window search
+//! followed by a re-search, show up twice.
+
+use std::fmt::{self, Display};
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeNode {
+    /// Move leading to this node, `None` at the root and after a null move
+    pub mv: Option<Move>,
+    pub depth: u16,
+    pub alpha: i32,
+    pub beta: i32,
+    /// Score returned by the node, relative to its side to move
+    pub score: i32,
+    /// Indices into `SearchTree::nodes`
+    pub children: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchTree {
+    nodes: Vec<TreeNode>,
+    /// Path from the root to the node being searched
+    open: Vec<usize>,
+    max_ply: u16,
+}
+
+impl SearchTree {
+    pub fn new(max_ply: u16) -> Self {
+        Self {
+            max_ply,
+            ..Default::default()
+        }
+    }
+
+    /// Whether nodes at `ply` are recorded
+    #[inline]
+    pub fn records(&self, ply: usize) -> bool {
+        ply <= self.max_ply as usize
+    }
+
+    /// Throws away the tree and opens a new root node
+    pub fn reset(&mut self, depth: u16, alpha: i32, beta: i32) {
+        self.nodes.clear();
+        self.open.clear();
+        self.open(None, depth, alpha, beta);
+    }
+
+    /// Starts a child of the node being searched
+    pub fn open(&mut self, mv: Option<Move>, depth: u16, alpha: i32, beta: i32) {
+        let idx = self.nodes.len();
+        self.nodes.push(TreeNode {
+            mv,
+            depth,
+            alpha,
+            beta,
+            score: 0,
+            children: Vec::new(),
+        });
+        if let Some(&parent) = self.open.last() {
+            self.nodes[parent].children.push(idx);
+        }
+        self.open.push(idx);
+    }
+
+    /// Finishes the node being searched with the score it returned
+    pub fn close(&mut self, score: i32) {
+        if let Some(idx) = self.open.pop() {
+            self.nodes[idx].score = score;
+        }
+    }
+
+    pub fn root(&self) -> Option<&TreeNode> {
+        self.nodes.first()
+    }
+
+    pub fn nodes(&self) -> &[TreeNode] {
+        &self.nodes
+    }
+
+    fn fmt_node(&self, f: &mut fmt::Formatter<'_>, idx: usize, indent: usize) -> fmt::Result {
+        let node = &self.nodes[idx];
+        let mv = node.mv.map_or("root".to_string(), |mv| mv.uci());
+        writeln!(
+            f,
+            "{:indent$}{mv} d={} [{}, {}] -> {}",
+            "",
+            node.depth,
+            node.alpha,
+            node.beta,
+            node.score,
+            indent = indent * 2
+        )?;
+        for &child in &node.children {
+            self.fmt_node(f, child, indent + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SearchTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.nodes.is_empty() {
+            return writeln!(f, "(empty)");
+        }
+        self.fmt_node(f, 0, 0)
+    }
+}
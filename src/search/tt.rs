@@ -1,9 +1,25 @@
 #[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use miette::IntoDiagnostic;
+use tracing::warn;
 
 use crate::{consts::MAX_HASH, moves::move_info::Move};
 
-const NUM_ENTRIES_PER_CLUSTER: usize = 4;
+/// Six 10 byte entries, padded to one 64 byte cache line per cluster
+const NUM_ENTRIES_PER_CLUSTER: usize = 6;
+
+/// Start of a saved table, see `TranspositionTable::save`
+const SAVE_MAGIC: [u8; 8] = *b"ESCHECTT";
+/// Bumped whenever the saved layout or the meaning of a stored field changes
+const SAVE_VERSION: u32 = 1;
+/// Magic, version, entries per cluster and number of clusters
+const SAVE_HEADER_SIZE: usize = 8 + 4 + 4 + 8;
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -16,19 +32,46 @@ pub enum ScoreTypes {
     UpperBound = 2,
 }
 
+/// Replacement policy used by `TranspositionTable::store`
+///
+/// - `AlwaysReplace`: the newest result always wins, even over a deeper entry for the
+///   same position. Cheapest and keeps the table fresh, but deep results from the
+///   main line get thrown away by shallow re-searches (NMP, LMR, qsearch cutoffs).
+/// - `DepthPreferred`: an entry for the same position is only overwritten by an equal
+///   or deeper search, and the shallowest entry in the cluster is evicted. Keeps the
+///   most expensive work, but entries from earlier searches are never aged out and
+///   slowly clog the table during a game.
+/// - `AgeAndDepth`: like `DepthPreferred`, but entries from older search cycles are
+///   evicted first and can always be overwritten. Best default for game play.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TtReplacement {
+    AlwaysReplace,
+    DepthPreferred,
+    #[default]
+    AgeAndDepth,
+}
+
+/// A single 10 byte TT slot.
+///
+/// Only the lower 32 bits of the hash are kept as the key. The cluster index comes from
+/// the lower bits of the hash as well, so every entry in a cluster already agrees on
+/// those and only `32 - log2(num_clusters)` bits actually tell positions apart. With the
+/// default 16 MB table (2^18 clusters) that leaves 14 bits, so a probe of a position that
+/// isn't stored gets a false hit with probability of about `6 / 2^14`, roughly 1 in 2700.
+/// The search never trusts a TT move without checking it against the legal moves, so a
+/// collision costs a bad cutoff at worst, never an illegal move.
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct TranspositionEntry {
-    // 4 bytes for the key. Storing only the lower 32 bits of the hash
-    // Collisions are handled by checking the full hash on the board
+    // 4 bytes for the key, the lower 32 bits of the hash
     key: u32,
-    // 2 byts for the best move found
+    // 2 bytes for the best move found
     best_move: Move,
     // 2 bytes for the eval score
     score: i16,
     // 1 byte for the depth
     depth: u8,
-    // 1 byte for flags
+    // 1 byte for flags, score type in the lower 2 bits and age in the upper 6
     flags: u8,
 }
 
@@ -48,7 +91,7 @@ impl TranspositionEntry {
     ) -> Self {
         let packed_score = score as i16;
 
-        let packed_flags = (age << Self::AGE_SHIFT) | (score_type as u8);
+        let packed_flags = (Self::packed_age(age) << Self::AGE_SHIFT) | (score_type as u8);
 
         Self {
             key: hash as u32,
@@ -59,6 +102,12 @@ impl TranspositionEntry {
         }
     }
 
+    /// The age as `get_age` returns it, only the lower 6 bits fit in the flags
+    #[inline]
+    pub fn packed_age(age: u8) -> u8 {
+        age & (u8::MAX >> Self::AGE_SHIFT)
+    }
+
     /// Checks if the key of this entry matches the lower 32 bits of full hash
     #[inline]
     pub fn matches(&self, hash: u64) -> bool {
@@ -93,8 +142,31 @@ impl TranspositionEntry {
     pub fn get_depth(&self) -> u16 {
         self.depth as u16
     }
+
+    /// Layout used by `TranspositionTable::save`, independent of the in memory one
+    fn to_le_bytes(self) -> [u8; Self::ENTRY_SIZE] {
+        let mut bytes = [0; Self::ENTRY_SIZE];
+        bytes[0..4].copy_from_slice(&{ self.key }.to_le_bytes());
+        bytes[4..6].copy_from_slice(&{ self.best_move }.0.to_le_bytes());
+        bytes[6..8].copy_from_slice(&{ self.score }.to_le_bytes());
+        bytes[8] = self.depth;
+        bytes[9] = self.flags;
+        bytes
+    }
+
+    fn from_le_bytes(bytes: [u8; Self::ENTRY_SIZE]) -> Self {
+        Self {
+            key: u32::from_le_bytes(bytes[0..4].try_into().expect("4 bytes")),
+            best_move: Move(u16::from_le_bytes(bytes[4..6].try_into().expect("2 bytes"))),
+            score: i16::from_le_bytes(bytes[6..8].try_into().expect("2 bytes")),
+            depth: bytes[8],
+            flags: bytes[9],
+        }
+    }
 }
 
+/// Aligned so a probe touches exactly one cache line
+#[repr(C, align(64))]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Cluster {
     entries: [TranspositionEntry; NUM_ENTRIES_PER_CLUSTER],
@@ -108,6 +180,9 @@ impl Cluster {
 pub struct TranspositionTable {
     clusters: Vec<Cluster>,
     size: usize,
+    replacement: TtReplacement,
+    /// Applied to the hash on top of the table size when picking a cluster, see `set_index_bits`
+    index_mask: usize,
 }
 
 /// Default to 16 MB Transposition Table
@@ -119,30 +194,82 @@ impl Default for TranspositionTable {
 
 impl TranspositionTable {
     pub fn new(size_mb: usize) -> Self {
-        let num_entries = (size_mb * 1024 * 1024) / TranspositionEntry::ENTRY_SIZE;
-        let num_clusters = (num_entries / NUM_ENTRIES_PER_CLUSTER).next_power_of_two();
+        let num_clusters = Self::num_clusters(size_mb);
         Self {
             clusters: vec![Cluster::default(); num_clusters],
             size: num_clusters,
+            replacement: TtReplacement::default(),
+            index_mask: usize::MAX,
         }
     }
 
+    pub fn set_replacement(&mut self, replacement: TtReplacement) {
+        self.replacement = replacement;
+    }
+
+    pub fn replacement(&self) -> TtReplacement {
+        self.replacement
+    }
+
+    /// Debug only. Picks clusters from just the lowest `bits` bits of the hash, crowding
+    /// every position into at most `2^bits` clusters so collisions are frequent. The keys
+    /// are left alone, so `TranspositionEntry::matches` still tells positions apart.
+    /// `None` uses the whole table
+    pub fn set_index_bits(&mut self, bits: Option<u8>) {
+        self.index_mask = match bits {
+            Some(bits) if (bits as u32) < usize::BITS => (1 << bits) - 1,
+            _ => usize::MAX,
+        };
+    }
+
+    /// Power of two number of clusters that fit in `size_mb`
+    fn num_clusters(size_mb: usize) -> usize {
+        ((size_mb * 1024 * 1024) / Cluster::CLUSTER_SIZE).next_power_of_two()
+    }
+
+    /// Resizes the table, dropping every stored entry.
+    /// Sizes above the available memory are clamped, see [`safe_size_mb`]. If the
+    /// allocation still fails, the old table is kept and an error returned
     pub fn change_size(&mut self, new_size_mb: usize) -> miette::Result<()> {
         miette::ensure!(
             new_size_mb <= MAX_HASH,
             "Hash table size ({new_size_mb} MB) exceeds max allowed {MAX_HASH} MB"
         );
-        let new_entries_num = (new_size_mb * 1024 * 1024) / TranspositionEntry::ENTRY_SIZE;
-        let new_size = (new_entries_num / NUM_ENTRIES_PER_CLUSTER).next_power_of_two();
-        self.clusters.resize_with(new_size, Cluster::default);
+        let size_mb = safe_size_mb(new_size_mb, available_memory_mb());
+        if size_mb != new_size_mb {
+            warn!("Only {size_mb} MB of the requested {new_size_mb} MB hash fit in memory");
+        }
+        let new_size = Self::num_clusters(size_mb);
+        self.clusters = allocate_clusters(new_size)?;
+        self.size = new_size;
+
+        Ok(())
+    }
+
+    /// Resizes the table, rehashing the stored entries into their new clusters.
+    /// Growing keeps every entry. When shrinking, entries from clusters that fold onto
+    /// the same new cluster compete through the replacement policy and some are lost
+    pub fn resize_preserving(&mut self, new_size_mb: usize) -> miette::Result<()> {
+        miette::ensure!(
+            new_size_mb <= MAX_HASH,
+            "Hash table size ({new_size_mb} MB) exceeds max allowed {MAX_HASH} MB"
+        );
+        let new_size = Self::num_clusters(safe_size_mb(new_size_mb, available_memory_mb()));
+        let old_clusters = std::mem::replace(&mut self.clusters, allocate_clusters(new_size)?);
         self.size = new_size;
 
+        for entry in old_clusters.iter().flat_map(|c| c.entries.iter()) {
+            if entry.key != 0 {
+                self.store(*entry);
+            }
+        }
+
         Ok(())
     }
 
     #[inline(always)]
     fn index(&self, hash: u64) -> usize {
-        hash as usize & (self.size - 1)
+        hash as usize & (self.size - 1) & self.index_mask
     }
 
     pub fn probe(&self, hash: u64) -> Option<&TranspositionEntry> {
@@ -162,26 +289,33 @@ impl TranspositionTable {
         None
     }
 
-    /// Store an entry with a Depth+Age prefered replacement strategy
+    /// Store an entry according to the configured `TtReplacement` policy
     pub fn store(&mut self, new_entry: TranspositionEntry) {
         let index = self.index(new_entry.key as u64);
+        let replacement = self.replacement;
         let cluster = &mut self.clusters[index];
 
         // Check if an entry for the same position already exists.
-        // Only replace an entry if the new one is from a deeper or equal search.
-        // This is to prevents shallow searches, like from NMP, from overwriting
-        // information from deeper searches.
+        // Unless always replacing, only replace an entry if the new one is from a deeper
+        // or equal search. This is to prevents shallow searches, like from NMP, from
+        // overwriting information from deeper searches.
         for entry in &mut cluster.entries {
             if entry.key == new_entry.key {
-                if new_entry.depth >= entry.depth {
+                let replace = match replacement {
+                    TtReplacement::AlwaysReplace => true,
+                    TtReplacement::DepthPreferred => new_entry.depth >= entry.depth,
+                    TtReplacement::AgeAndDepth => {
+                        new_entry.depth >= entry.depth || new_entry.get_age() != entry.get_age()
+                    }
+                };
+                if replace {
                     *entry = new_entry;
                 }
                 return;
             }
         }
 
-        // Depth + Age Preferred Replacement:
-        // if no existing entry was found, replace one of the current 'worst' entries
+        // If no existing entry was found, replace one of the current 'worst' entries
         let mut replace_idx = 0;
         let mut worst_score = i32::MAX;
 
@@ -190,8 +324,13 @@ impl TranspositionTable {
             // A lower score is worse and a better candidate for replacement.
             // - Prioritize replacing entries from older search cycles.
             // - Among entries from the same cycle, replace the one with the shallowest depth.
-            let age_difference = new_entry.get_age().wrapping_sub(entry.get_age());
-            let score = (entry.depth as i32) - (age_difference as i32) * 4; // Weight age more heavily
+            let score = match replacement {
+                TtReplacement::DepthPreferred => entry.depth as i32,
+                TtReplacement::AlwaysReplace | TtReplacement::AgeAndDepth => {
+                    let age_difference = new_entry.get_age().wrapping_sub(entry.get_age());
+                    (entry.depth as i32) - (age_difference as i32) * 4 // Weight age more heavily
+                }
+            };
 
             if score < worst_score {
                 worst_score = score;
@@ -209,21 +348,396 @@ impl TranspositionTable {
         }
     }
 
-    /// Return the hash table fullness in per-mille (0-1000)
-    pub fn hash_full(&self) -> u16 {
-        let sample_size = 1000.min(self.size);
+    /// Writes the table to `path`: a header with the format version and the number of
+    /// clusters, followed by every entry field by field in little endian
+    pub fn save(&self, path: &Path) -> miette::Result<()> {
+        let mut out = BufWriter::new(File::create(path).into_diagnostic()?);
+        out.write_all(&SAVE_MAGIC).into_diagnostic()?;
+        out.write_all(&SAVE_VERSION.to_le_bytes())
+            .into_diagnostic()?;
+        out.write_all(&(NUM_ENTRIES_PER_CLUSTER as u32).to_le_bytes())
+            .into_diagnostic()?;
+        out.write_all(&(self.size as u64).to_le_bytes())
+            .into_diagnostic()?;
+        for entry in self.clusters.iter().flat_map(|c| c.entries.iter()) {
+            out.write_all(&entry.to_le_bytes()).into_diagnostic()?;
+        }
+        out.flush().into_diagnostic()
+    }
+
+    /// Replaces the table with the one saved at `path`. Files from another format version
+    /// are ignored with a warning and leave the table untouched, in which case this returns
+    /// false. The entries are rehashed, so the saved table doesn't need to be the same size
+    pub fn load(&mut self, path: &Path) -> miette::Result<bool> {
+        let mut input = BufReader::new(File::open(path).into_diagnostic()?);
+        let mut header = [0; SAVE_HEADER_SIZE];
+        if input.read_exact(&mut header).is_err() || header[..8] != SAVE_MAGIC {
+            warn!("{} is not a saved hash table, ignoring it", path.display());
+            return Ok(false);
+        }
+        let version = u32::from_le_bytes(header[8..12].try_into().expect("4 bytes"));
+        let per_cluster = u32::from_le_bytes(header[12..16].try_into().expect("4 bytes"));
+        let num_clusters = u64::from_le_bytes(header[16..24].try_into().expect("8 bytes"));
+        let max_clusters = Self::num_clusters(MAX_HASH) as u64;
+        if version != SAVE_VERSION
+            || per_cluster as usize != NUM_ENTRIES_PER_CLUSTER
+            || !num_clusters.is_power_of_two()
+            || num_clusters > max_clusters
+        {
+            warn!(
+                "Ignoring incompatible hash file {} (version {version}, {num_clusters} clusters)",
+                path.display()
+            );
+            return Ok(false);
+        }
+
+        // Read everything first, so a truncated file doesn't leave a half loaded table
+        let mut loaded = allocate_clusters(num_clusters as usize)?;
+        let mut bytes = [0; TranspositionEntry::ENTRY_SIZE];
+        for entry in loaded.iter_mut().flat_map(|c| c.entries.iter_mut()) {
+            input.read_exact(&mut bytes).into_diagnostic()?;
+            *entry = TranspositionEntry::from_le_bytes(bytes);
+        }
+
+        // Always rehash, the saved table may have had another size or index mask
+        self.clear();
+        for entry in loaded.iter().flat_map(|c| c.entries.iter()) {
+            if entry.key != 0 {
+                self.store(*entry);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Number of entries sampled by `hash_full`
+    const HASH_FULL_SAMPLE: usize = 1000;
+
+    /// Return the hash table fullness in per-mille (0-1000), the UCI `hashfull` convention.
+    /// Only the first 1000 entries are sampled so this is cheap regardless of table size,
+    /// and only entries written during the search cycle `age` count as used.
+    pub fn hash_full(&self, age: u8) -> u16 {
+        let current_age = TranspositionEntry::packed_age(age);
+
+        let sampled = self
+            .clusters
+            .iter()
+            .flat_map(|cluster| cluster.entries.iter())
+            .take(Self::HASH_FULL_SAMPLE);
+
+        let mut total = 0;
         let mut filled = 0;
+        for entry in sampled {
+            total += 1;
+            if entry.key != 0 && entry.get_age() == current_age {
+                filled += 1;
+            }
+        }
+
+        ((filled * 1000) / total.max(1)) as u16
+    }
+}
+
+/// `requested_mb`, clamped to the largest power of two that fits in `available_mb`.
+/// Tables are a power of two clusters, so anything in between would be rounded up again
+pub fn safe_size_mb(requested_mb: usize, available_mb: Option<usize>) -> usize {
+    match available_mb {
+        Some(available) if requested_mb > available => {
+            let clamped = if available.is_power_of_two() {
+                available
+            } else {
+                available.next_power_of_two() / 2
+            };
+            clamped.max(1)
+        }
+        _ => requested_mb,
+    }
+}
+
+/// Best effort estimate of the memory free for new allocations, `None` if unknown
+fn available_memory_mb() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let kb = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<usize>()
+        .ok()?;
+    Some(kb / 1024)
+}
+
+/// Allocates `num_clusters` empty clusters, failing instead of aborting when out of memory
+fn allocate_clusters(num_clusters: usize) -> miette::Result<Vec<Cluster>> {
+    let mut clusters = Vec::new();
+    if let Err(e) = clusters.try_reserve_exact(num_clusters) {
+        miette::bail!(
+            "Failed to allocate {} MB for the hash table: {e}",
+            num_clusters.saturating_mul(Cluster::CLUSTER_SIZE) / (1024 * 1024)
+        );
+    }
+    clusters.resize(num_clusters, Cluster::default());
+    Ok(clusters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::prng::Prng;
+
+    /// Hash of the `n`th position that lands in the same cluster as `base`
+    fn colliding(tt: &TranspositionTable, base: u64, n: u64) -> u64 {
+        base + n * tt.size as u64
+    }
+
+    fn entry(hash: u64, depth: u8, age: u8) -> TranspositionEntry {
+        TranspositionEntry::new(hash, Move::NULL, 0, depth, ScoreTypes::Exact, age)
+    }
+
+    fn stored_depth(tt: &TranspositionTable, hash: u64) -> Option<u16> {
+        tt.probe(hash).map(|e| e.get_depth())
+    }
+
+    #[test]
+    fn test_entry_layout() {
+        assert_eq!(TranspositionEntry::ENTRY_SIZE, 10);
+        assert_eq!(Cluster::CLUSTER_SIZE, 64);
+        assert_eq!(std::mem::align_of::<Cluster>(), 64);
+
+        let tt = TranspositionTable::new(1);
+        assert_eq!(tt.clusters.as_ptr() as usize % 64, 0);
+    }
+
+    #[test]
+    fn test_store_probe_round_trip() {
+        let mut tt = TranspositionTable::new(1);
+        // Upper half of the hash is dropped, the lower half is the key
+        let hash = 0xDEAD_BEEF_0123_4567;
+        let mv = Move::new(12, 28, Move::DOUBLE_PAWN);
+        tt.store(TranspositionEntry::new(
+            hash,
+            mv,
+            -19_950,
+            42,
+            ScoreTypes::LowerBound,
+            63,
+        ));
+
+        let stored = *tt.probe(hash).unwrap();
+        assert_eq!(stored.get_best_move(), mv);
+        assert_eq!(stored.get_score(), -19_950);
+        assert_eq!(stored.get_depth(), 42);
+        assert_eq!(stored.get_score_type(), ScoreTypes::LowerBound);
+        assert_eq!(stored.get_age(), 63);
+
+        // Same lower 32 bits, so this is indistinguishable after truncation
+        assert!(tt.probe(0x0123_4567).is_some());
+        assert!(tt.probe(hash ^ 1).is_none());
+    }
+
+    #[test]
+    fn test_tiny_index_space_never_mixes_up_positions() {
+        let mut tt = TranspositionTable::new(1);
+        tt.set_index_bits(Some(1));
+        let mut prng = Prng::init(0x0C0_111DE);
+
+        // Far more positions than the 12 slots left, so most get evicted
+        let hashes: Vec<u64> = (0..200).map(|_| prng.rand()).collect();
+        for (depth, &hash) in hashes.iter().enumerate() {
+            tt.store(entry(hash, depth as u8, 0));
+        }
+        let mut found = 0;
+        for (depth, &hash) in hashes.iter().enumerate() {
+            if let Some(stored) = tt.probe(hash) {
+                assert!(stored.matches(hash));
+                assert_eq!(stored.get_depth(), depth as u16);
+                found += 1;
+            }
+        }
+        assert_eq!(found, 2 * NUM_ENTRIES_PER_CLUSTER);
+
+        tt.set_index_bits(None);
+        assert_eq!(tt.index(u64::MAX), tt.size - 1);
+    }
+
+    #[test]
+    fn test_save_and_load_keeps_probeable_entries() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("eschec_tt_{}.bin", std::process::id()));
+        let mut tt = TranspositionTable::new(1);
+        let mut prng = Prng::init(0x5A7E_D7AB);
+        let stored: Vec<TranspositionEntry> = (0..500u16)
+            .map(|i| {
+                let score_type = [ScoreTypes::Exact, ScoreTypes::LowerBound][i as usize % 2];
+                let (score, depth) = (i as i32 * 7 - 1000, (i % 64) as u8);
+                TranspositionEntry::new(prng.rand(), Move(i + 1), score, depth, score_type, 3)
+            })
+            .collect();
+        for &e in &stored {
+            tt.store(e);
+        }
+        tt.save(&path).unwrap();
+
+        // Into a table of the same size, and rehashed into a bigger one
+        for size_mb in [1, 4] {
+            let mut loaded = TranspositionTable::new(size_mb);
+            assert!(loaded.load(&path).unwrap());
+            for e in &stored {
+                let hash = { e.key } as u64;
+                assert_eq!(loaded.probe(hash), Some(e), "{size_mb} MB");
+            }
+        }
+
+        // Files from another version are ignored and leave the table alone
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[8..12].copy_from_slice(&(SAVE_VERSION + 1).to_le_bytes());
+        let incompatible = dir.join(format!("eschec_tt_{}_old.bin", std::process::id()));
+        std::fs::write(&incompatible, &bytes).unwrap();
+        let mut other = TranspositionTable::new(1);
+        other.store(entry(42, 9, 0));
+        assert!(!other.load(&incompatible).unwrap());
+        std::fs::write(&incompatible, b"not a hash table").unwrap();
+        assert!(!other.load(&incompatible).unwrap());
+        assert_eq!(stored_depth(&other, 42), Some(9));
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&incompatible).unwrap();
+    }
+
+    #[test]
+    fn test_hash_full_sampling() {
+        let mut tt = TranspositionTable::new(1);
+        let mut rng = Prng::init(0xC0FFEE);
+
+        // Entries from an older search shouldn't count
+        for _ in 0..tt.size {
+            tt.store(entry(rng.rand(), 1, 1));
+        }
+        assert_eq!(tt.hash_full(2), 0);
+
+        for _ in 0..tt.size {
+            tt.store(entry(rng.rand(), 1, 2));
+        }
+
+        let current = tt
+            .clusters
+            .iter()
+            .flat_map(|c| c.entries.iter())
+            .filter(|e| e.key != 0 && e.get_age() == 2)
+            .count();
+        let true_fill = (current * 1000 / (tt.size * NUM_ENTRIES_PER_CLUSTER)) as i32;
+        let sampled = tt.hash_full(2) as i32;
+        assert!(true_fill > 100, "true fill: {true_fill}");
+        assert!(
+            (sampled - true_fill).abs() <= 60,
+            "sampled: {sampled}, true: {true_fill}"
+        );
+
+        // Only the lower 6 bits of the age are stored, so ages 64 apart look the same
+        assert_eq!(tt.hash_full(2 + 64), sampled as u16);
+    }
+
+    #[test]
+    fn test_resize_preserving() {
+        let mut tt = TranspositionTable::new(1);
+        let old_size = tt.size as u64;
+        // Lands in different clusters once the table grows
+        let hashes = [0x1234, 0x1234 + old_size, 0x1234 + 3 * old_size];
+        for (depth, &hash) in hashes.iter().enumerate() {
+            tt.store(entry(hash, depth as u8 + 1, 1));
+        }
 
-        for i in 0..sample_size {
-            let cluster = &self.clusters[i];
-            for entry in &cluster.entries {
-                if entry.key != 0 {
-                    filled += 1;
-                    break; // Only count cluster as filled if atleast one entry is used
+        tt.resize_preserving(4).unwrap();
+        assert!(tt.size as u64 > old_size);
+        for (depth, &hash) in hashes.iter().enumerate() {
+            assert_eq!(stored_depth(&tt, hash), Some(depth as u16 + 1));
+        }
+
+        // Shrinking back still finds them, they fit in one cluster
+        tt.resize_preserving(1).unwrap();
+        for (depth, &hash) in hashes.iter().enumerate() {
+            assert_eq!(stored_depth(&tt, hash), Some(depth as u16 + 1));
+        }
+
+        // A plain resize starts from an empty table
+        tt.change_size(2).unwrap();
+        assert!(hashes.iter().all(|&hash| tt.probe(hash).is_none()));
+    }
+
+    #[test]
+    fn test_replacement_same_position() {
+        let hash = 0x1234;
+        let expected = [
+            (TtReplacement::AlwaysReplace, [2, 3]),
+            (TtReplacement::DepthPreferred, [10, 10]),
+            (TtReplacement::AgeAndDepth, [10, 3]),
+        ];
+
+        for (policy, [same_cycle, next_cycle]) in expected {
+            let mut tt = TranspositionTable::new(1);
+            tt.set_replacement(policy);
+            tt.store(entry(hash, 10, 1));
+
+            // Shallower result for the same position from the same search
+            tt.store(entry(hash, 2, 1));
+            assert_eq!(stored_depth(&tt, hash), Some(same_cycle), "{policy:?}");
+
+            // Shallower result from a later search
+            tt.store(entry(hash, 3, 2));
+            assert_eq!(stored_depth(&tt, hash), Some(next_cycle), "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn test_replacement_victim_in_full_cluster() {
+        for policy in [
+            TtReplacement::AlwaysReplace,
+            TtReplacement::DepthPreferred,
+            TtReplacement::AgeAndDepth,
+        ] {
+            let mut tt = TranspositionTable::new(1);
+            tt.set_replacement(policy);
+            let hashes: Vec<u64> = (1..=NUM_ENTRIES_PER_CLUSTER as u64 + 1)
+                .map(|n| colliding(&tt, 0x42, n))
+                .collect();
+            let (&newest, filling) = hashes.split_last().unwrap();
+
+            // Deep entry from an old search, shallow entries from the current one
+            tt.store(entry(filling[0], 12, 1));
+            for (i, &hash) in filling.iter().enumerate().skip(1) {
+                tt.store(entry(hash, 3 + i as u8, 5));
+            }
+
+            tt.store(entry(newest, 3, 5));
+            assert!(tt.probe(newest).is_some(), "{policy:?}");
+
+            let old_deep_survives = tt.probe(filling[0]).is_some();
+            let shallow_survives = tt.probe(filling[1]).is_some();
+            match policy {
+                TtReplacement::DepthPreferred => {
+                    assert!(old_deep_survives && !shallow_survives, "{policy:?}")
+                }
+                TtReplacement::AlwaysReplace | TtReplacement::AgeAndDepth => {
+                    assert!(!old_deep_survives && shallow_survives, "{policy:?}")
                 }
             }
         }
+    }
 
-        ((filled * 1000) / sample_size) as u16
+    #[test]
+    fn test_absurd_hash_sizes_are_rejected_or_clamped() {
+        let mut tt = TranspositionTable::new(1);
+        let size = tt.size;
+        assert!(tt.change_size(usize::MAX / 2).is_err());
+        assert!(tt.change_size(MAX_HASH + 1).is_err());
+        assert_eq!(tt.size, size, "A failed resize keeps the old table");
+
+        assert!(allocate_clusters(usize::MAX / Cluster::CLUSTER_SIZE).is_err());
+
+        assert_eq!(safe_size_mb(1024, Some(300)), 256);
+        assert_eq!(safe_size_mb(1024, Some(512)), 512);
+        assert_eq!(safe_size_mb(64, Some(300)), 64);
+        assert_eq!(safe_size_mb(1024, Some(0)), 1);
+        assert_eq!(safe_size_mb(1024, None), 1024);
     }
 }
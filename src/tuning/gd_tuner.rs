@@ -65,7 +65,7 @@ pub fn run_gd_tuning(
                         let grad = error_term * count as f64;
                         // Update MG and EG gradients based on Phase
                         grads[spsa_idx] += grad * (1.0 - entry.phase);
-                        grads[spsa_idx + 1] += grad * entry.phase;
+                        grads[spsa_idx + 1] += grad * entry.phase * entry.eg_scale;
                     }
                 }
 
@@ -28,6 +28,21 @@ macro_rules! define_tunable_params {
                 vec
             }
 
+            /// Rust source for a struct literal holding these values, to bake tuned
+            /// params back in as new defaults
+            pub fn to_rust_source(&self) -> String {
+                let mut src = format!("{} {{\n", stringify!($name));
+                $(
+                    src.push_str(&format!(
+                        "    {}: {},\n",
+                        stringify!($field),
+                        self.$field.to_rust_source()
+                    ));
+                )*
+                src.push('}');
+                src
+            }
+
             pub fn from_vector(vec: &[f64]) -> Self {
                 let mut idx = 0;
 
@@ -10,4 +10,6 @@ pub mod trace;
 pub trait Tunable {
     fn push_to_vector(&self, vec: &mut Vec<f64>);
     fn read_from_vector(vec: &[f64], idx: &mut usize) -> Self;
+    /// Rust expression that evaluates to `self`
+    fn to_rust_source(&self) -> String;
 }
@@ -55,8 +55,42 @@ pub const KNIGHT_OUTPOST_BONUS: usize = 26;
 // Tempo Bonus
 pub const TEMPO_BONUS: usize = 27;
 
+// Threats
+pub const THREAT_BY_PAWN: usize = 28;
+pub const THREAT_BY_MINOR: usize = 29;
+pub const THREAT_BY_ROOK: usize = 30;
+
+// Pawn Chains
+pub const PHALANX_BONUS: usize = 31;
+
+// King Tropism (Knight, Bishop, Rook, Queen)
+pub const KING_TROPISM_START: usize = 32; // 32..35
+
+// Heavy pieces on the 7th rank
+pub const ROOK_ON_SEVENTH: usize = 36;
+pub const QUEEN_ON_SEVENTH: usize = 37;
+
+// Trapped Pieces
+pub const TRAPPED_KNIGHT_PENALTY: usize = 38;
+pub const TRAPPED_BISHOP_PENALTY: usize = 39;
+pub const TRAPPED_ROOK_PENALTY: usize = 40;
+
+// King Activity
+pub const KING_CENTRALIZATION: usize = 41;
+pub const KING_PASSER_PROXIMITY: usize = 42;
+
+// Passed Pawn Support
+pub const PASSED_PAWN_BLOCKADE: usize = 43;
+pub const PASSED_PAWN_KING_SUPPORT: usize = 44;
+
+// Connected Rooks
+pub const CONNECTED_ROOKS_BONUS: usize = 45;
+
+// File Batteries
+pub const FILE_BATTERY_BONUS: usize = 46;
+
 // PSTs (6 pieces * 64 squares = 384 params)
-pub const PST_START: usize = 28;
+pub const PST_START: usize = 47;
 pub const NUM_PST_PARAMS: usize = NUM_PIECES * NUM_SQUARES;
 
 // Mobility Offsets
@@ -74,7 +108,10 @@ pub const NUM_TRACE_FEATURES: usize = MOBILITY_QUEEN_START + QUEEN_MAX;
 pub const SPSA_VECTOR_SIZE: usize = NUM_TRACE_FEATURES * 2;
 
 crate::define_tunable_params! {
+/// Keys missing from a params file fall back to `Default`, so a new term only needs a
+/// value in `default_params.toml` until a tuning run produces one
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TunableParams {
     // Index 0=Pawn, 1=Knight, 2=Bishop, 3=Rook, 4=Queen
     pub material: [Score; 5],
@@ -101,9 +138,46 @@ pub struct TunableParams {
     pub rook_semi_file_bonus: Score,
     pub knight_outpost_bonus: Score,
 
-    // Tempo
+    // Tempo, tapered like every other term so having the move counts for more in the
+    // middlegame (`mg`) than in quiet endgames (`eg`)
     pub tempo_bonus: Score,
 
+    // Threats
+    pub threat_by_pawn: Score,
+    pub threat_by_minor: Score,
+    pub threat_by_rook: Score,
+
+    // Pawn Chains
+    pub phalanx_bonus: Score,
+
+    // King Tropism, Index 0=Knight, 1=Bishop, 2=Rook, 3=Queen
+    pub king_tropism: [Score; 4],
+
+    // Heavy pieces on the 7th rank
+    pub rook_on_seventh: Score,
+    pub queen_on_seventh: Score,
+
+    // Trapped Pieces
+    pub trapped_knight_penalty: Score,
+    pub trapped_bishop_penalty: Score,
+    pub trapped_rook_penalty: Score,
+
+    // King Activity, per step closer to the center and to each passed pawn
+    pub king_centralization: Score,
+    pub king_passer_proximity: Score,
+
+    // Passed Pawn Support, per passer with an enemy piece or the friendly king next to
+    // the square in front of it
+    pub passed_pawn_blockade: Score,
+    pub passed_pawn_king_support: Score,
+
+    // Connected Rooks, per pair of rooks defending each other along a rank or file
+    pub connected_rooks_bonus: Score,
+
+    // File Batteries, per file with a rook stacked on a rook or queen, aimed at the
+    // enemy king or an undefended pawn
+    pub file_battery_bonus: Score,
+
     // PSTs
     #[serde(with = "BigArray")]
     pub psts: [Score; NUM_PST_PARAMS],
@@ -316,6 +390,27 @@ impl Default for TunableParams {
             rook_semi_file_bonus: Score::new(15, 10),
             knight_outpost_bonus: Score::new(30, 20),
             tempo_bonus: Score::new(15, 5),
+            threat_by_pawn: Score::new(60, 40),
+            threat_by_minor: Score::new(40, 30),
+            threat_by_rook: Score::new(35, 20),
+            phalanx_bonus: Score::new(5, 10),
+            king_tropism: [
+                Score::new(3, 0), // Knight
+                Score::new(2, 0), // Bishop
+                Score::new(2, 0), // Rook
+                Score::new(4, 1), // Queen
+            ],
+            rook_on_seventh: Score::new(20, 30),
+            queen_on_seventh: Score::new(10, 15),
+            trapped_knight_penalty: Score::new(-30, -20),
+            trapped_bishop_penalty: Score::new(-80, -60),
+            trapped_rook_penalty: Score::new(-40, -5),
+            king_centralization: Score::new(0, 10),
+            king_passer_proximity: Score::new(0, 4),
+            passed_pawn_blockade: Score::new(-10, -25),
+            passed_pawn_king_support: Score::new(0, 20),
+            connected_rooks_bonus: Score::new(15, 5),
+            file_battery_bonus: Score::new(20, 5),
             mobility_knight: [Score::default(); KNIGHT_MAX],
             mobility_bishop: [Score::default(); BISHOP_MAX],
             mobility_rook: [Score::default(); ROOK_MAX],
@@ -345,6 +440,22 @@ impl TunableParams {
             rook_semi_file_bonus: Score::default(),
             knight_outpost_bonus: Score::default(),
             tempo_bonus: Score::default(),
+            threat_by_pawn: Score::default(),
+            threat_by_minor: Score::default(),
+            threat_by_rook: Score::default(),
+            phalanx_bonus: Score::default(),
+            king_tropism: [Score::default(); 4],
+            rook_on_seventh: Score::default(),
+            queen_on_seventh: Score::default(),
+            trapped_knight_penalty: Score::default(),
+            trapped_bishop_penalty: Score::default(),
+            trapped_rook_penalty: Score::default(),
+            king_centralization: Score::default(),
+            king_passer_proximity: Score::default(),
+            passed_pawn_blockade: Score::default(),
+            passed_pawn_king_support: Score::default(),
+            connected_rooks_bonus: Score::default(),
+            file_battery_bonus: Score::default(),
             mobility_knight: [Score::default(); KNIGHT_MAX],
             mobility_bishop: [Score::default(); BISHOP_MAX],
             mobility_rook: [Score::default(); ROOK_MAX],
@@ -387,9 +498,27 @@ impl TunableParams {
             ROOK_SEMI_FILE_BONUS => self.rook_semi_file_bonus,
             KNIGHT_OUTPOST_BONUS => self.knight_outpost_bonus,
             TEMPO_BONUS => self.tempo_bonus,
+            THREAT_BY_PAWN => self.threat_by_pawn,
+            THREAT_BY_MINOR => self.threat_by_minor,
+            THREAT_BY_ROOK => self.threat_by_rook,
+            PHALANX_BONUS => self.phalanx_bonus,
+            ROOK_ON_SEVENTH => self.rook_on_seventh,
+            QUEEN_ON_SEVENTH => self.queen_on_seventh,
+            TRAPPED_KNIGHT_PENALTY => self.trapped_knight_penalty,
+            TRAPPED_BISHOP_PENALTY => self.trapped_bishop_penalty,
+            TRAPPED_ROOK_PENALTY => self.trapped_rook_penalty,
+            KING_CENTRALIZATION => self.king_centralization,
+            KING_PASSER_PROXIMITY => self.king_passer_proximity,
+            PASSED_PAWN_BLOCKADE => self.passed_pawn_blockade,
+            PASSED_PAWN_KING_SUPPORT => self.passed_pawn_king_support,
+            CONNECTED_ROOKS_BONUS => self.connected_rooks_bonus,
+            FILE_BATTERY_BONUS => self.file_battery_bonus,
             i if (PASSED_PAWN_START..PASSED_PAWN_START + 8).contains(&i) => {
                 self.passed_pawn_scores[i - PASSED_PAWN_START]
             }
+            i if (KING_TROPISM_START..KING_TROPISM_START + 4).contains(&i) => {
+                self.king_tropism[i - KING_TROPISM_START]
+            }
             _ => Score::default(),
         }
     }
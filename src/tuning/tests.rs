@@ -160,6 +160,7 @@ fn test_texel_entry_phase_normalization() {
         fixed_score: Score::new(100, 200), // MG 100, EG 200
         result: 0.5,
         phase: 0.5, // Exact middle
+        eg_scale: 1.0,
     };
 
     let weights = vec![0.0; 1000];
@@ -172,3 +173,68 @@ fn test_texel_entry_phase_normalization() {
         "Texel Phase Interpolation incorrect"
     );
 }
+
+#[test]
+fn test_to_rust_source_round_trip() {
+    let params = TunableParams {
+        tempo_bonus: Score::new(-7, 13),
+        ..Default::default()
+    };
+    let src = params.to_rust_source();
+
+    assert!(src.starts_with("TunableParams {\n    material: [\n"));
+    assert!(src.contains("    tempo_bonus: Score::new(-7, 13),\n"));
+    assert!(src.contains("    psts: [\n"));
+    assert!(src.ends_with("    ],\n}"));
+    assert_eq!(src.matches('[').count(), src.matches(']').count());
+    assert_eq!(src.matches('(').count(), src.matches(')').count());
+
+    // Every weight shows up once, in `to_vector` order
+    let weights: Vec<f64> = src
+        .split("Score::new(")
+        .skip(1)
+        .flat_map(|rest| {
+            let args = &rest[..rest.find(')').unwrap()];
+            args.split(", ")
+                .map(|n| n.parse::<i32>().unwrap() as f64)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(weights.len(), SPSA_VECTOR_SIZE);
+    assert_eq!(weights, params.to_vector());
+}
+
+#[test]
+fn test_missing_param_keys_fall_back_to_defaults() {
+    // The tuned files predate the newer terms, which only live in default_params.toml
+    let tuned = TunableParams::tuned();
+    let defaults = TunableParams::default();
+    assert_eq!(tuned.phalanx_bonus, defaults.phalanx_bonus);
+    assert_eq!(tuned.file_battery_bonus, defaults.file_battery_bonus);
+
+    let from_defaults = TunableParams::load_from_file("./config/default_params.toml").unwrap();
+    assert_eq!(from_defaults.to_vector(), defaults.to_vector());
+}
+
+#[test]
+fn test_texel_entries_carry_the_endgame_scale() {
+    use crate::evaluation::endgame::{SCALE_NORMAL, scale_factor};
+    use crate::tuning::texel::load_texel_dataset;
+
+    let path = std::env::temp_dir().join(format!("eschec-texel-{}.book", std::process::id()));
+    std::fs::write(
+        &path,
+        "8/3k1b2/8/8/P1P1P3/8/4K3/2B5 w - - 0 1 [0.5]\n\
+         rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [0.5]\n",
+    )
+    .unwrap();
+    let entries = load_texel_dataset(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // The eval scales the endgame half of fortresses, so the tuner has to as well
+    let fortress = Board::from_fen("8/3k1b2/8/8/P1P1P3/8/4K3/2B5 w - - 0 1");
+    let expected = scale_factor(&fortress) as f64 / SCALE_NORMAL as f64;
+    assert!(expected < 1.0);
+    assert_eq!(entries[0].eg_scale, expected);
+    assert_eq!(entries[1].eg_scale, 1.0);
+}
@@ -22,6 +22,8 @@ pub struct TexelEntry {
     pub result: f64,
     /// Normalized Phase: 0.0 (MG) -> 1.0 (EG)
     pub phase: f64,
+    /// Endgame scale from `endgame::scale_factor`, 1.0 for a normal position
+    pub eg_scale: f64,
 }
 
 impl TexelEntry {
@@ -39,7 +41,7 @@ impl TexelEntry {
             }
         }
 
-        mg * (1.0 - self.phase) + eg * self.phase
+        mg * (1.0 - self.phase) + eg * self.eg_scale * self.phase
     }
 }
 /// Loads the dataset from a .book  file
@@ -116,6 +118,8 @@ fn parse_book_line(line: &str) -> Option<TexelEntry> {
         fixed_score,
         result: stm_result,
         phase: phase_normalized,
+        eg_scale: evaluation::endgame::scale_factor(&board) as f64
+            / evaluation::endgame::SCALE_NORMAL as f64,
     })
 }
 
@@ -36,9 +36,44 @@ pub const KNIGHT_OUTPOST: usize = 26;
 // Tempo Bonus
 pub const TEMPO_BONUS: usize = 27;
 
+// Threats
+pub const THREAT_BY_PAWN: usize = 28;
+pub const THREAT_BY_MINOR: usize = 29;
+pub const THREAT_BY_ROOK: usize = 30;
+
+// Pawn Chains
+pub const PHALANX_BONUS: usize = 31;
+
+// King Tropism (Knight, Bishop, Rook, Queen)
+pub const KING_TROPISM_START: usize = 32;
+// Indices 32..35 are king tropism
+
+// Heavy pieces on the 7th rank
+pub const ROOK_ON_SEVENTH: usize = 36;
+pub const QUEEN_ON_SEVENTH: usize = 37;
+
+// Trapped Pieces
+pub const TRAPPED_KNIGHT: usize = 38;
+pub const TRAPPED_BISHOP: usize = 39;
+pub const TRAPPED_ROOK: usize = 40;
+
+// King Activity
+pub const KING_CENTRALIZATION: usize = 41;
+pub const KING_PASSER_PROXIMITY: usize = 42;
+
+// Passed Pawn Support
+pub const PASSED_PAWN_BLOCKADE: usize = 43;
+pub const PASSED_PAWN_KING_SUPPORT: usize = 44;
+
+// Connected Rooks
+pub const CONNECTED_ROOKS: usize = 45;
+
+// File Batteries
+pub const FILE_BATTERY: usize = 46;
+
 // PSTs (384 params)
 // We put PSTs before mobility in the 'features' array to keep i8s together
-pub const PST_START: usize = 28;
+pub const PST_START: usize = 47;
 
 // Mobility (5 params)
 // These are stored in a separate i16 array because counts can exceed 127
@@ -103,6 +138,33 @@ impl EvalTrace {
 
             TEMPO_BONUS => params::TEMPO_BONUS,
 
+            THREAT_BY_PAWN => params::THREAT_BY_PAWN,
+            THREAT_BY_MINOR => params::THREAT_BY_MINOR,
+            THREAT_BY_ROOK => params::THREAT_BY_ROOK,
+
+            PHALANX_BONUS => params::PHALANX_BONUS,
+
+            // King Tropism
+            i if (KING_TROPISM_START..KING_TROPISM_START + 4).contains(&i) => {
+                params::KING_TROPISM_START + (i - KING_TROPISM_START)
+            }
+
+            ROOK_ON_SEVENTH => params::ROOK_ON_SEVENTH,
+            QUEEN_ON_SEVENTH => params::QUEEN_ON_SEVENTH,
+
+            TRAPPED_KNIGHT => params::TRAPPED_KNIGHT_PENALTY,
+            TRAPPED_BISHOP => params::TRAPPED_BISHOP_PENALTY,
+            TRAPPED_ROOK => params::TRAPPED_ROOK_PENALTY,
+
+            KING_CENTRALIZATION => params::KING_CENTRALIZATION,
+            KING_PASSER_PROXIMITY => params::KING_PASSER_PROXIMITY,
+
+            PASSED_PAWN_BLOCKADE => params::PASSED_PAWN_BLOCKADE,
+            PASSED_PAWN_KING_SUPPORT => params::PASSED_PAWN_KING_SUPPORT,
+
+            CONNECTED_ROOKS => params::CONNECTED_ROOKS_BONUS,
+            FILE_BATTERY => params::FILE_BATTERY_BONUS,
+
             // PSTs
             i if (params::PST_START..params::MOBILITY_KNIGHT_START).contains(&i) => {
                 params::PST_START + (i - params::PST_START)
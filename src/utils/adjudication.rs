@@ -0,0 +1,290 @@
+//! Early game termination for self-play and quick games
+//!
+//! Feeds on the search score after every ply. A side resigns once its score stays below
+//! `-resign_score` for `resign_plies` plies in a row, and the game is called a draw once
+//! the score stays near 0 late in a game with little material left.
+//!
+//! `adjudicate` covers the games that are over by the rules instead.
+
+use std::fmt::Display;
+
+use crate::prelude::*;
+
+/// Thresholds for ending a game early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdjudicationConfig {
+    /// Score in cp a side has to be losing by to resign
+    pub resign_score: i32,
+    /// Consecutive plies the score has to stay beyond `resign_score`
+    pub resign_plies: u16,
+    /// Scores within `draw_score` cp of 0 count towards a draw
+    pub draw_score: i32,
+    /// Consecutive plies the score has to stay within `draw_score`
+    pub draw_plies: u16,
+    /// Draws are only adjudicated from this move on
+    pub draw_min_move: u8,
+    /// Draws are only adjudicated once the game phase is at least this far into the endgame
+    pub draw_min_phase: i32,
+}
+
+impl Default for AdjudicationConfig {
+    fn default() -> Self {
+        Self {
+            resign_score: 900,
+            resign_plies: 6,
+            draw_score: 10,
+            draw_plies: 10,
+            draw_min_move: 40,
+            draw_min_phase: ENDGAME_PHASE * 3 / 4,
+        }
+    }
+}
+
+/// Why a game was ended early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjudication {
+    /// The given side resigned
+    Resign(Side),
+    Draw,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Adjudicator {
+    config: AdjudicationConfig,
+    /// Consecutive plies White has been losing/winning by more than `resign_score`
+    white_losing: u16,
+    white_winning: u16,
+    drawish: u16,
+}
+
+impl Adjudicator {
+    pub fn new(config: AdjudicationConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> AdjudicationConfig {
+        self.config
+    }
+
+    /// Forgets the score history, eg. after a takeback or a new position
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// Records the search `score` of `board`, relative to its side to move,
+    /// and returns a verdict if the game should end here
+    pub fn update(&mut self, board: &Board, score: i32) -> Option<Adjudication> {
+        let white_score = match board.stm {
+            Side::White => score,
+            Side::Black => -score,
+        };
+        let conf = &self.config;
+
+        let bump = |count: u16, cond: bool| if cond { count + 1 } else { 0 };
+        self.white_losing = bump(self.white_losing, white_score <= -conf.resign_score);
+        self.white_winning = bump(self.white_winning, white_score >= conf.resign_score);
+        self.drawish = bump(
+            self.drawish,
+            white_score.abs() <= conf.draw_score
+                && board.fullmove_counter >= conf.draw_min_move
+                && board.game_phase().0 >= conf.draw_min_phase,
+        );
+
+        if self.white_losing >= conf.resign_plies {
+            Some(Adjudication::Resign(Side::White))
+        } else if self.white_winning >= conf.resign_plies {
+            Some(Adjudication::Resign(Side::Black))
+        } else if self.drawish >= conf.draw_plies {
+            Some(Adjudication::Draw)
+        } else {
+            None
+        }
+    }
+}
+
+/// Why a game ended in a draw by the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+}
+
+/// Outcome of a game that is over by the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::WhiteWins => write!(f, "1-0"),
+            GameResult::BlackWins => write!(f, "0-1"),
+            GameResult::Draw(reason) => write!(f, "1/2-1/2 ({reason:?})"),
+        }
+    }
+}
+
+/// Returns the result if the game on `board` is over. `history` holds the moves that
+/// led to `board`, oldest first, and is only needed to spot repetitions
+pub fn adjudicate(board: &Board, history: &[MoveInfo]) -> Option<GameResult> {
+    let mut legal_moves = MoveBuffer::new();
+    board.generate_legal_moves(&mut legal_moves, false);
+    if legal_moves.is_empty() {
+        // Mate takes precedence over the fifty move rule
+        return Some(if !board.is_in_check(board.stm) {
+            GameResult::Draw(DrawReason::Stalemate)
+        } else if board.stm == Side::White {
+            GameResult::BlackWins
+        } else {
+            GameResult::WhiteWins
+        });
+    }
+
+    if board.halfmove_clock >= 100 {
+        return Some(GameResult::Draw(DrawReason::FiftyMoveRule));
+    }
+
+    // Positions before the last irreversible move can't repeat
+    let repetitions = history
+        .iter()
+        .rev()
+        .take(board.halfmove_clock as usize)
+        .filter(|info| info.zobrist_hash == board.hash)
+        .count();
+    if repetitions >= 2 {
+        return Some(GameResult::Draw(DrawReason::ThreefoldRepetition));
+    }
+
+    if board.is_insufficient_material() {
+        return Some(GameResult::Draw(DrawReason::InsufficientMaterial));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::common::{SearchConfig, SearchLimits};
+
+    #[test]
+    fn test_lost_position_resigns_in_window() {
+        // Black has a lone king against two queens
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/QQ2K3 b - - 0 1");
+        let conf = SearchConfig {
+            emit_info: false,
+            ..Default::default()
+        };
+        let mut search = AlphaBetaSearch::new()
+            .with_config(conf)
+            .unwrap()
+            .with_limits(SearchLimits::depth(3));
+        let mut adjudicator = Adjudicator::default();
+        let window = adjudicator.config().resign_plies;
+
+        let mut verdict = None;
+        for _ in 0..window {
+            let result = search.find_best_move(&board);
+            verdict = adjudicator.update(&board, result.score);
+            if verdict.is_some() {
+                break;
+            }
+            board.make_move(result.best_move.unwrap()).unwrap();
+        }
+        assert_eq!(verdict, Some(Adjudication::Resign(Side::Black)));
+    }
+
+    #[test]
+    fn test_draw_needs_late_quiet_endgame() {
+        let mut adjudicator = Adjudicator::new(AdjudicationConfig {
+            draw_plies: 2,
+            ..Default::default()
+        });
+
+        // Bare kings on move 60
+        let late = Board::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 60");
+        assert_eq!(adjudicator.update(&late, 0), None);
+        assert_eq!(adjudicator.update(&late, 5), Some(Adjudication::Draw));
+
+        // Too early in the game
+        adjudicator.reset();
+        let early = Board::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 10");
+        assert_eq!(adjudicator.update(&early, 0), None);
+        assert_eq!(adjudicator.update(&early, 0), None);
+
+        // Too much material left
+        let start = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 60");
+        assert_eq!(adjudicator.update(&start, 0), None);
+        assert_eq!(adjudicator.update(&start, 0), None);
+    }
+
+    /// Plays `moves` from `fen`, returning the board and the history that led to it
+    fn play_out(fen: &str, moves: &[&str]) -> (Board, Vec<MoveInfo>) {
+        let mut board = Board::from_fen(fen);
+        let history = moves
+            .iter()
+            .map(|uci| {
+                let mv = Move::from_uci(&board, uci).unwrap();
+                board.try_move(mv).unwrap()
+            })
+            .collect();
+        (board, history)
+    }
+
+    #[test]
+    fn test_adjudicate_mates() {
+        // Fool's mate
+        let (board, history) = play_out(START_FEN, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+        assert_eq!(adjudicate(&board, &history), Some(GameResult::BlackWins));
+
+        // Back rank mate
+        let (board, history) = play_out("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", &["a1a8"]);
+        assert_eq!(adjudicate(&board, &history), Some(GameResult::WhiteWins));
+
+        // Mate on the 100th half move still counts
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80");
+        assert_eq!(adjudicate(&board, &[]), Some(GameResult::WhiteWins));
+    }
+
+    #[test]
+    fn test_adjudicate_draws() {
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        assert_eq!(
+            adjudicate(&stalemate, &[]),
+            Some(GameResult::Draw(DrawReason::Stalemate))
+        );
+
+        let fifty = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 100 80");
+        assert_eq!(
+            adjudicate(&fifty, &[]),
+            Some(GameResult::Draw(DrawReason::FiftyMoveRule))
+        );
+
+        let bare_kings = Board::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 60");
+        assert_eq!(
+            adjudicate(&bare_kings, &[]),
+            Some(GameResult::Draw(DrawReason::InsufficientMaterial))
+        );
+
+        // Knights shuffle back and forth, the start position occurs for the third time
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let (board, history) = play_out(START_FEN, &shuffle);
+        assert_eq!(adjudicate(&board, &history), None);
+        let moves: Vec<&str> = shuffle.iter().chain(&shuffle).copied().collect();
+        let (board, history) = play_out(START_FEN, &moves);
+        assert_eq!(
+            adjudicate(&board, &history),
+            Some(GameResult::Draw(DrawReason::ThreefoldRepetition))
+        );
+
+        assert_eq!(adjudicate(&Board::new(), &[]), None);
+    }
+}
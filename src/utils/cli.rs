@@ -1,11 +1,25 @@
-use std::{io::Write, path::PathBuf, time::Duration};
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+    time::Duration,
+};
 
 use clap::{Parser, Subcommand, ValueEnum};
 use tracing::{Level, span};
 
-use crate::{prelude::*, search::common::SearchLimits, tuning::params::TunableParams};
+use crate::{
+    prelude::*,
+    search::common::{SearchConfig, SearchLimits},
+    tuning::params::TunableParams,
+    utils::{
+        adjudication::{Adjudication, AdjudicationConfig, Adjudicator, GameResult, adjudicate},
+        sts_runner::{self, SuiteSummary},
+    },
+};
 
 const INITIAL_TIME: u64 = 10_000;
+/// Depth used by `analyze` if neither depth nor movetime is given
+const ANALYZE_DEPTH: u16 = 8;
 
 #[derive(Parser)]
 #[command(name = env!("CARGO_PKG_NAME"), version = env!("APP_VERSION"), about = env!("CARGO_PKG_DESCRIPTION") )]
@@ -48,6 +62,26 @@ pub enum Commands {
         #[arg(short, long, default_value = "uci")]
         protocol: Option<String>,
     },
+
+    /// Read FENs from stdin, one per line, and print `fen;bestmove;score;depth` for each
+    Analyze {
+        /// set search depth
+        #[arg(short, long)]
+        depth: Option<u16>,
+        /// set search time per position in ms
+        #[arg(short, long)]
+        movetime: Option<u64>,
+    },
+
+    /// Run a Strategic Test Suite and report its score and an estimated rating
+    Sts {
+        /// EPD file, or a directory of EPD files
+        #[arg(short, long)]
+        file: PathBuf,
+        /// set search time per position in ms
+        #[arg(short, long, default_value = "100")]
+        movetime: u64,
+    },
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
@@ -89,6 +123,8 @@ pub enum SetSubcommand {
     LogFile { enable: String },
     /// Set Hash table size
     Hash { size: u16 },
+    /// Resign once the eval stays below -score cp for the given number of plies
+    Resign { score: i32, plies: u16 },
 }
 
 #[derive(Parser, Debug)]
@@ -110,7 +146,11 @@ pub enum GameSubcommand {
 
     /// Undo the last move
     #[clap(visible_alias = "u")]
-    Undo,
+    Undo {
+        /// Undo a full move (both sides) instead of a single ply
+        #[arg(short, long, default_value = "false")]
+        full: bool,
+    },
 
     /// Save the current game to a PGN file
     #[clap(visible_alias = "s")]
@@ -155,11 +195,66 @@ pub enum GameSubcommand {
     Quit,
 }
 
-pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Result<()> {
+/// Board of the interactive game along with the moves played on it, so they can be taken back
+#[derive(Debug, Clone)]
+struct GameState {
+    board: Board,
+    history: Vec<MoveInfo>,
+    /// Fed with the score of every hint
+    adjudicator: Adjudicator,
+}
+
+impl GameState {
+    fn new(board: Board) -> Self {
+        Self {
+            board,
+            history: Vec::new(),
+            adjudicator: Adjudicator::default(),
+        }
+    }
+
+    /// Plays `mv` if it's legal
+    fn play(&mut self, mv: Move) -> miette::Result<()> {
+        let move_info = self.board.try_move(mv).into_diagnostic()?;
+        self.history.push(move_info);
+        Ok(())
+    }
+
+    /// Takes back up to `plies` moves, returns how many were actually undone
+    fn undo(&mut self, plies: usize) -> miette::Result<usize> {
+        let mut undone = 0;
+        while undone < plies
+            && let Some(move_info) = self.history.pop()
+        {
+            self.board.unmake_move(&move_info)?;
+            undone += 1;
+        }
+        if undone > 0 {
+            self.adjudicator.reset();
+        }
+        Ok(undone)
+    }
+
+    /// The result if the game is over by the rules
+    fn result(&self) -> Option<GameResult> {
+        adjudicate(&self.board, &self.history)
+    }
+}
+
+/// Board to start `Play`/`Perft` from: `fen` if given, otherwise the standard start position.
+/// Errors instead of panicking if the FEN can't be parsed or the position isn't playable
+pub fn start_position(fen: Option<&str>) -> miette::Result<Board> {
+    let fen = fen.unwrap_or(START_FEN);
+    let board = Board::try_from_fen(fen).into_diagnostic()?;
+    board.validate().into_diagnostic()?;
+    Ok(board)
+}
+
+pub fn game_loop(board: Board, depth: u16, params: TunableParams) -> miette::Result<()> {
     let inp_depth = depth;
-    let inp_fen = fen.clone();
+    let inp_board = board;
 
-    let mut board = Board::from_fen(&fen);
+    let mut game = GameState::new(board);
     let limits = SearchLimits {
         max_depth: Some(depth),
         max_time: Some(Duration::from_millis(INITIAL_TIME)),
@@ -170,14 +265,14 @@ pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Resu
 
     let stdin = std::io::stdin();
 
-    println!("{board}");
+    println!("{}", game.board);
     loop {
         let span = span!(Level::DEBUG, "game_loop");
         let _guard = span.enter();
 
         trace!("inside game_loop");
 
-        print!("{} >> ", board.stm);
+        print!("{} >> ", game.board.stm);
         std::io::stdout().flush().into_diagnostic()?;
 
         let mut input = String::new();
@@ -202,7 +297,7 @@ pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Resu
         match GameCommand::try_parse_from(args) {
             Ok(game_cmd) => match game_cmd.cmd {
                 GameSubcommand::Move { move_str } => {
-                    let mov = match Move::from_uci(&board, &move_str) {
+                    let mov = match Move::from_uci(&game.board, &move_str) {
                         Ok(m) => m,
                         Err(e) => {
                             eprintln!("{e:?}");
@@ -211,14 +306,18 @@ pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Resu
                     };
                     info!("Attempting move: {}", mov.uci());
                     // Already verified that mov is legal when parsing for uci move above
-                    if let Err(e) = board.try_move(mov) {
+                    if let Err(e) = game.play(mov) {
                         eprintln!("{e:?}");
                         continue;
                     }
+                    if let Some(result) = game.result() {
+                        println!("{}", game.board);
+                        println!("Game over: {result}");
+                    }
                 }
                 GameSubcommand::Print => {
                     info!("Printing board..");
-                    println!("{board}");
+                    println!("{}", game.board);
                 }
                 GameSubcommand::Perft { depth, divide } => {
                     info!(
@@ -226,42 +325,63 @@ pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Resu
                         depth.unwrap_or(5),
                         divide
                     );
-                    let mut board_copy = board;
+                    let mut board_copy = game.board;
                     if divide {
-                        perft_divide(&mut board_copy, depth.unwrap_or(5));
+                        perft_divide(&mut board_copy, depth.unwrap_or(5), true);
                     } else {
                         run_perft_suite(&mut board_copy, depth.unwrap_or(5));
                     }
                 }
                 GameSubcommand::Restart => {
                     info!("Restarting game...");
-                    board = Board::from_fen(&inp_fen);
+                    game = GameState {
+                        adjudicator: Adjudicator::new(game.adjudicator.config()),
+                        ..GameState::new(inp_board)
+                    };
                 }
                 GameSubcommand::Fen => {
                     info!("Printing fen...");
-                    println!("{}", fen::to_fen(&board)?);
+                    println!("{}", fen::to_fen(&game.board)?);
                 }
                 GameSubcommand::Quit => {
                     info!("Exiting game loop...");
                     break;
                 }
-                GameSubcommand::Undo => {
-                    todo!("Undo last state")
+                GameSubcommand::Undo { full } => {
+                    let plies = if full { 2 } else { 1 };
+                    match game.undo(plies) {
+                        Ok(0) => warn!("Nothing to undo"),
+                        Ok(undone) => {
+                            info!("Undid {undone} ply");
+                            println!("{}", game.board);
+                        }
+                        Err(e) => error!("Failed to undo: {e:?}"),
+                    }
                 }
                 GameSubcommand::Save { filename } => {
                     todo!("Saving to file: {filename}");
                 }
                 GameSubcommand::Hint => {
                     info!("Here's a Hint. Support for multiple hints coming soon");
-                    let result = search.find_best_move(&board);
+                    let result = search.find_best_move(&game.board);
                     if let Some(mov) = result.best_move {
                         info!("Best move: {} ", mov.uci());
+                        if let Some(pv) = &result.pv {
+                            info!("pv: {}", game.board.san_line(pv).join(" "));
+                        }
                         info!(
                             "score: {}, time_taken: {} ms, nodes: {}",
                             result.score,
                             result.time_taken.as_millis(),
                             result.nodes_searched,
                         );
+                        match game.adjudicator.update(&game.board, result.score) {
+                            Some(Adjudication::Resign(side)) => {
+                                info!("Adjudicated: {side} resigns")
+                            }
+                            Some(Adjudication::Draw) => info!("Adjudicated: draw"),
+                            None => {}
+                        }
                     } else {
                         error!("No legal moves available");
                     }
@@ -281,8 +401,18 @@ pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Resu
                         }
                         let fen_str = parts.join(" ");
                         info!("Setting fen to {fen_str}");
-                        board = Board::from_fen(&fen_str);
-                        println!("{board}");
+                        let board = match start_position(Some(&fen_str)) {
+                            Ok(b) => b,
+                            Err(e) => {
+                                eprintln!("{e:?}");
+                                continue;
+                            }
+                        };
+                        game = GameState {
+                            adjudicator: Adjudicator::new(game.adjudicator.config()),
+                            ..GameState::new(board)
+                        };
+                        println!("{}", game.board);
                     }
                     SetSubcommand::Depth { depth } => {
                         let curr_depth = search.get_limits().max_depth.unwrap_or(inp_depth);
@@ -325,6 +455,15 @@ pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Resu
                             error!("Failed to toggle file logging: {e:?}");
                         }
                     }
+                    SetSubcommand::Resign { score, plies } => {
+                        info!("Resigning below -{score} cp after {plies} plies");
+                        let conf = AdjudicationConfig {
+                            resign_score: score,
+                            resign_plies: plies,
+                            ..game.adjudicator.config()
+                        };
+                        game.adjudicator = Adjudicator::new(conf);
+                    }
                     SetSubcommand::Hash { size } => {
                         info!("Changing Hash table size to {size}");
                         let mut conf = search.get_config();
@@ -351,3 +490,191 @@ pub fn game_loop(fen: String, depth: u16, params: TunableParams) -> miette::Resu
 
     Ok(())
 }
+
+/// Batch analysis: reads one FEN per line from `input` and writes
+/// `fen;bestmove;score;depth` to `out` for each. Blank lines and lines starting
+/// with `#` are skipped, invalid FENs are logged and skipped.
+pub fn analyze(
+    input: impl BufRead,
+    out: &mut impl Write,
+    depth: Option<u16>,
+    movetime: Option<u64>,
+    params: TunableParams,
+) -> miette::Result<()> {
+    let limits = SearchLimits {
+        max_depth: depth.or(movetime.is_none().then_some(ANALYZE_DEPTH)),
+        max_time: movetime.map(Duration::from_millis),
+        max_nodes: None,
+        mate_depth: None,
+    };
+    // Info lines would be mixed into the output
+    let conf = SearchConfig {
+        emit_info: false,
+        ..Default::default()
+    };
+    let mut search = AlphaBetaSearch::with_eval(params)
+        .with_config(conf)?
+        .with_limits(limits);
+
+    for line in input.lines() {
+        let line = line.into_diagnostic()?;
+        let fen = line.trim();
+        if fen.is_empty() || fen.starts_with('#') {
+            continue;
+        }
+
+        let board = match Board::try_from_fen(fen) {
+            Ok(board) => board,
+            Err(e) => {
+                error!("Skipping invalid FEN '{fen}': {e:?}");
+                continue;
+            }
+        };
+
+        search.clear();
+        let result = search.find_best_move(&board);
+        let best_move = result.best_move.unwrap_or(Move::NULL).uci();
+        writeln!(out, "{fen};{best_move};{};{}", result.score, result.depth).into_diagnostic()?;
+    }
+
+    Ok(())
+}
+
+/// Runs the STS positions in `path` for `movetime` ms each and writes the score, computed
+/// from the `c0` weights of each position, along with the estimated rating to `out`
+pub fn run_sts(
+    path: &PathBuf,
+    movetime: u64,
+    params: TunableParams,
+    out: &mut impl Write,
+) -> miette::Result<SuiteSummary> {
+    let tests = sts_runner::load_epd_files_from_path(path)?;
+    let results = sts_runner::run_suite(&tests, &params, movetime, None);
+    let summary = SuiteSummary::from_results(&path.display().to_string(), &results);
+
+    writeln!(
+        out,
+        "{}: {} positions, {}ms per move",
+        summary.name, summary.num_tests, movetime
+    )
+    .into_diagnostic()?;
+    writeln!(
+        out,
+        "score: {}/{} ({:.2}%), best move found: {}/{}",
+        summary.score,
+        summary.max_score,
+        summary.percentage(),
+        summary.bm_correct,
+        summary.num_tests
+    )
+    .into_diagnostic()?;
+    writeln!(out, "estimated rating: {}", summary.estimated_elo()).into_diagnostic()?;
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_position() {
+        assert_eq!(start_position(None).unwrap(), Board::from_fen(START_FEN));
+        assert_eq!(
+            start_position(Some(KIWIPETE)).unwrap(),
+            Board::from_fen(KIWIPETE)
+        );
+
+        // Unparseable
+        assert!(start_position(Some("not a fen")).is_err());
+        // Parses, but can't be played from
+        assert!(start_position(Some("8/8/8/8/8/8/8/8 w - - 0 1")).is_err());
+        assert!(start_position(Some("4k3/8/8/8/8/8/8/4KK2 w - - 0 1")).is_err());
+        assert!(start_position(Some("4k2P/8/8/8/8/8/8/4K3 w - - 0 1")).is_err());
+        // Black to move would capture the white king
+        assert!(start_position(Some("4k3/8/8/8/8/8/8/r3K3 b - - 0 1")).is_err());
+    }
+
+    #[test]
+    fn test_game_undo_restores_board() {
+        let start = Board::from_fen(KIWIPETE);
+        let mut game = GameState::new(start);
+
+        // Castling, a capture and a double push, then take them back one at a time
+        let moves = ["e1g1", "h3g2", "a2a4"];
+        let mut positions = vec![game.board];
+        for uci in moves {
+            let mv = Move::from_uci(&game.board, uci).unwrap();
+            game.play(mv).unwrap();
+            positions.push(game.board);
+        }
+
+        positions.pop();
+        while let Some(expected) = positions.pop() {
+            assert_eq!(game.undo(1).unwrap(), 1);
+            assert_eq!(game.board, expected);
+        }
+        assert_eq!(game.undo(1).unwrap(), 0);
+        assert_eq!(game.board, start);
+
+        // A full move takes back both sides
+        for uci in ["e2a6", "b4c3"] {
+            let mv = Move::from_uci(&game.board, uci).unwrap();
+            game.play(mv).unwrap();
+        }
+        assert_eq!(game.undo(2).unwrap(), 2);
+        assert_eq!(game.board, start);
+
+        // Illegal moves don't end up in the history
+        let illegal = Move::new(0, 63, Move::QUIET);
+        assert!(game.play(illegal).is_err());
+        assert!(game.history.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_batch() {
+        let input = format!("# comment\n{START_FEN}\n\n{KIWIPETE}\n");
+        let mut out = Vec::new();
+        analyze(
+            input.as_bytes(),
+            &mut out,
+            Some(3),
+            None,
+            TunableParams::default(),
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2, "{out}");
+
+        for (line, fen) in lines.iter().zip([START_FEN, KIWIPETE]) {
+            let fields: Vec<&str> = line.split(';').collect();
+            assert_eq!(fields.len(), 4, "{line}");
+            assert_eq!(fields[0], fen);
+
+            let board = Board::from_fen(fen);
+            assert!(Move::from_uci(&board, fields[1]).is_ok(), "{line}");
+            assert!(fields[2].parse::<i32>().is_ok());
+            assert_eq!(fields[3], "3");
+        }
+    }
+
+    #[test]
+    fn test_run_sts_scores_from_c0() {
+        let path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_suites/fixtures/sts_mini.epd");
+        let mut out = Vec::new();
+        let summary = run_sts(&path, 200, TunableParams::default(), &mut out).unwrap();
+
+        // Both positions are mate in one, worth 10 points each
+        assert_eq!(summary.num_tests, 2);
+        assert_eq!(summary.score, 20);
+        assert_eq!(summary.max_score, 20);
+        assert_eq!(summary.bm_correct, 2);
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("score: 20/20 (100.00%)"), "{out}");
+        assert!(out.contains(&format!("estimated rating: {}", summary.estimated_elo())));
+    }
+}
@@ -125,7 +125,7 @@ pub fn init() {
     LazyLock::force(&LOG_HANDLES);
     #[cfg(feature = "simd")]
     {
-        info!("Simd Enabled, but nothing for now");
+        info!("Simd Enabled for PST accumulation");
     }
     #[cfg(not(feature = "simd"))]
     {
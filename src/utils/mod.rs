@@ -1,3 +1,4 @@
+pub mod adjudication;
 pub mod cli;
 pub mod log;
 pub mod perft;
@@ -135,21 +135,63 @@ pub fn debug_perft_vs_stockfish(
 }
 
 pub fn perft_recursive(board: &mut Board, depth: u8) -> u64 {
-    if depth == 1 {
-        let mut moves = MoveBuffer::new();
-        board.generate_legal_moves(&mut moves, false);
-        return moves.len() as u64;
+    perft_make_unmake::<false>(board, depth)
+}
+
+/// Copy-make perft, every child is counted on a copy of the board so `unmake_move` is never used.
+/// Leaf moves are played too, like in `perft_unmake`, which this is the reference for
+pub fn perft_copy_make(board: &Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
     }
 
     let mut moves = MoveBuffer::new();
     board.generate_legal_moves(&mut moves, false);
 
+    let mut nodes = 0;
+    for m in moves {
+        let mut child = *board;
+        if child.make_move(m).is_ok() {
+            nodes += perft_copy_make(&child, depth - 1);
+        }
+    }
+    nodes
+}
+
+/// `perft_recursive` without bulk counting, so every leaf move is made and unmade as well,
+/// checking after each `unmake_move` that the board is exactly what it was before the move.
+/// Counts must match `perft_copy_make`
+pub fn perft_unmake(board: &mut Board, depth: u8) -> u64 {
+    perft_make_unmake::<true>(board, depth)
+}
+
+/// Make/unmake perft on a single board. Without `CHECKED`, the last ply is bulk counted
+fn perft_make_unmake<const CHECKED: bool>(board: &mut Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut moves = MoveBuffer::new();
+    board.generate_legal_moves(&mut moves, false);
+    if !CHECKED && depth == 1 {
+        return moves.len() as u64;
+    }
+
     let mut nodes = 0;
 
     for m in moves {
+        let before = CHECKED.then_some(*board);
         if let Ok(undo_info) = board.make_move(m) {
-            nodes += perft_recursive(board, depth - 1);
+            nodes += perft_make_unmake::<CHECKED>(board, depth - 1);
             board.unmake_move(&undo_info).expect("Unmake failed");
+            if let Some(before) = before {
+                assert!(
+                    *board == before,
+                    "Unmaking {} didn't restore '{}'",
+                    m.uci(),
+                    before.to_fen().unwrap_or_default()
+                );
+            }
         }
     }
     nodes
@@ -193,10 +235,14 @@ pub fn perft(board: &mut Board, depth: u8, divide: bool) -> PerftResult {
     PerftResult::new(total_nodes, start_time.elapsed(), move_counts)
 }
 
-/// Performs a Perft test and prints a detailed breakdown
-pub fn perft_divide(board: &mut Board, depth: u8) -> PerftResult {
+/// Perft with a per move breakdown. With `sorted`, moves are listed alphabetically by their
+/// UCI string, like Stockfish does, instead of in move generation order
+pub fn perft_divide(board: &mut Board, depth: u8, sorted: bool) -> PerftResult {
     println!("Starting perft...");
-    let result = perft(board, depth, true);
+    let mut result = perft(board, depth, true);
+    if sorted && let Some(move_counts) = &mut result.move_counts {
+        move_counts.sort_by_cached_key(|(mov, _)| mov.uci());
+    }
 
     if let Some(ref move_counts) = result.move_counts {
         println!("Perft results at depth {depth}");
@@ -215,6 +261,53 @@ pub fn perft_divide(board: &mut Board, depth: u8) -> PerftResult {
     result
 }
 
+/// Perft that cross-checks `generate_legal_moves` against `generate_legal_moves_reference`
+/// at every node instead of trusting the fast generator. On the first position where the
+/// two disagree, prints the moves found by only one of them and returns an error
+#[cfg(any(test, debug_assertions))]
+pub fn perft_differential(board: &Board, depth: u8) -> miette::Result<u64> {
+    let mut fast = MoveBuffer::new();
+    board.generate_legal_moves(&mut fast, false);
+    let mut reference = MoveBuffer::new();
+    board.generate_legal_moves_reference(&mut reference);
+
+    let only_fast: Vec<String> = fast
+        .iter()
+        .filter(|m| !reference.contains(m))
+        .map(|m| m.uci())
+        .collect();
+    let only_reference: Vec<String> = reference
+        .iter()
+        .filter(|m| !fast.contains(m))
+        .map(|m| m.uci())
+        .collect();
+
+    if fast.len() != reference.len() || !only_fast.is_empty() || !only_reference.is_empty() {
+        let fen = board.to_fen()?;
+        println!("Legal move generation mismatch for FEN: '{fen}'");
+        println!(
+            "generate_legal_moves: {} moves, reference: {} moves",
+            fast.len(),
+            reference.len()
+        );
+        println!("Only in generate_legal_moves: {only_fast:?}");
+        println!("Only in reference: {only_reference:?}");
+        miette::bail!("Legal move generation mismatch for FEN: '{fen}'");
+    }
+
+    if depth <= 1 {
+        return Ok(fast.len() as u64);
+    }
+
+    let mut nodes = 0;
+    for m in fast {
+        let mut child = *board;
+        child.make_move(m)?;
+        nodes += perft_differential(&child, depth - 1)?;
+    }
+    Ok(nodes)
+}
+
 /// Runs a suite of perft tests for depths 1 through max_depth
 pub fn run_perft_suite(board: &mut Board, max_depth: u8) {
     println!("Running Perft suite up to depth {max_depth}");
@@ -244,10 +337,104 @@ pub fn run_perft_suite(board: &mut Board, max_depth: u8) {
     println!("----------------------------");
 }
 
+/// Start position and CPW perft positions 2 to 6, used by `bench_movegen`
+pub const MOVEGEN_BENCH_POSITIONS: [&str; 6] = [
+    START_FEN,
+    KIWIPETE,
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+];
+
+/// Move generation throughput on one position, see `bench_movegen`
+#[derive(Debug, Clone)]
+pub struct MoveGenBench {
+    pub fen: String,
+    /// Legal moves generated per second, over repeated `generate_legal_moves` calls
+    pub moves_per_sec: u64,
+    /// Nodes counted by the perft run
+    pub perft_nodes: u64,
+    /// Perft nodes per second
+    pub perft_nps: u64,
+}
+
+/// Measures legal move generation and perft speed on each of `fens`, printing one line
+/// per position and a total. Move generation is timed over `iterations` calls on the root
+/// position, perft runs once to `perft_depth`
+pub fn bench_movegen(fens: &[&str], iterations: u32, perft_depth: u8) -> Vec<MoveGenBench> {
+    let per_sec = |count: u64, duration: Duration| {
+        (count as u128 * 1_000_000_000)
+            .checked_div(duration.as_nanos())
+            .unwrap_or(0) as u64
+    };
+
+    println!("Move generation benchmark: {iterations} iterations, perft depth {perft_depth}");
+    println!("----------------------------");
+
+    let mut results = Vec::with_capacity(fens.len());
+    let mut total_moves = 0;
+    let mut total_nodes = 0;
+    let mut movegen_time = Duration::ZERO;
+    let mut perft_time = Duration::ZERO;
+
+    for fen in fens {
+        let mut board = Board::from_fen(fen);
+
+        let mut moves = MoveBuffer::new();
+        let mut generated = 0;
+        let start = Instant::now();
+        for _ in 0..iterations {
+            moves.clear();
+            board.generate_legal_moves(std::hint::black_box(&mut moves), false);
+            generated += moves.len() as u64;
+        }
+        let movegen_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let perft_nodes = perft_recursive(&mut board, perft_depth);
+        let perft_elapsed = start.elapsed();
+
+        let result = MoveGenBench {
+            fen: fen.to_string(),
+            moves_per_sec: per_sec(generated, movegen_elapsed),
+            perft_nodes,
+            perft_nps: per_sec(perft_nodes, perft_elapsed),
+        };
+        println!(
+            "{:>12} moves/s | {:>10} nodes in {:>6} ms ({:>10} nps) | {}",
+            result.moves_per_sec,
+            result.perft_nodes,
+            perft_elapsed.as_millis(),
+            result.perft_nps,
+            result.fen
+        );
+
+        total_moves += generated;
+        total_nodes += perft_nodes;
+        movegen_time += movegen_elapsed;
+        perft_time += perft_elapsed;
+        results.push(result);
+    }
+
+    println!("----------------------------");
+    println!(
+        "Total: {} moves/s, {} nodes ({} nps)",
+        per_sec(total_moves, movegen_time),
+        total_nodes,
+        per_sec(total_nodes, perft_time)
+    );
+
+    results
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{consts::KIWIPETE, utils::log::init};
+    use crate::{
+        consts::{KIWIPETE, START_FEN},
+        utils::log::init,
+    };
 
     /// Known Perft values for the starting position
     const STARTING_PERFT: &[(u8, u64)] = &[
@@ -266,6 +453,30 @@ mod tests {
         (4, 4085603), // depth 4: 4,085,603 nodes
     ];
 
+    /// Run with `cargo test --release bench_movegen -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn test_bench_movegen() {
+        let results = bench_movegen(&MOVEGEN_BENCH_POSITIONS, 100_000, 4);
+        let nodes: Vec<u64> = results.iter().map(|r| r.perft_nodes).collect();
+        assert_eq!(nodes, [197281, 4085603, 43238, 422333, 2103487, 3894594]);
+    }
+
+    #[test]
+    fn test_perft_divide_sorted() {
+        let mut board = Board::from_fen(KIWIPETE);
+        let result = perft_divide(&mut board, 2, true);
+        let moves: Vec<String> = result
+            .move_counts
+            .unwrap()
+            .into_iter()
+            .map(|(mov, _)| mov.uci())
+            .collect();
+        assert_eq!(moves.len(), 48);
+        assert!(moves.is_sorted(), "{moves:?}");
+        assert_eq!(result.nodes, 2039);
+    }
+
     #[test]
     fn test_perft_starting_position() {
         init();
@@ -336,6 +547,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_perft_differential_standard_positions() {
+        init();
+        // Start position and CPW positions 2 to 6
+        let positions: &[(&str, [u64; 2])] = &[
+            (START_FEN, [20, 400]),
+            (KIWIPETE, [48, 2039]),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", [14, 191]),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                [6, 264],
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                [44, 1486],
+            ),
+            (
+                "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+                [46, 2079],
+            ),
+        ];
+
+        for (fen, expected) in positions {
+            let board = Board::from_fen(fen);
+            for (depth, &nodes) in (1..=2).zip(expected) {
+                let result = perft_differential(&board, depth)
+                    .unwrap_or_else(|e| panic!("{e} (depth {depth})"));
+                assert_eq!(result, nodes, "Perft failed at depth {depth} for {fen}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_perft_unmake_matches_copy_make() {
+        init();
+        let mut board = Board::from_fen(KIWIPETE);
+        let original = board;
+
+        let unmake = perft_unmake(&mut board, 4);
+        assert_eq!(unmake, perft_copy_make(&original, 4));
+        assert_eq!(unmake, KIWIPETE_PERFT[3].1);
+        assert_eq!(board, original);
+    }
+
     #[test]
     fn test_make_unmake_consistency() {
         init();
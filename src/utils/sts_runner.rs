@@ -27,6 +27,18 @@ pub struct EpdTest {
     pub c9_uci_moves: Option<Vec<String>>,
 }
 
+impl EpdTest {
+    /// Points for playing `uci`, from the `c0` weights. Moves without a weight score nothing
+    pub fn score_move(&self, uci: &str) -> i32 {
+        self.move_scores.get(uci).copied().unwrap_or(0)
+    }
+
+    /// Points for the best move of the position
+    pub fn max_score(&self) -> i32 {
+        self.move_scores.values().max().copied().unwrap_or(0)
+    }
+}
+
 /// A summary of the results from running a suite of tests.
 #[derive(Default, Debug, Clone)]
 pub struct SuiteSummary {
@@ -37,6 +49,37 @@ pub struct SuiteSummary {
     pub bm_correct: u64,
 }
 
+impl SuiteSummary {
+    /// Sums up `results` into a single summary called `name`
+    pub fn from_results(name: &str, results: &[TestResult]) -> Self {
+        Self {
+            name: name.to_owned(),
+            score: results.iter().map(|r| r.score).sum(),
+            max_score: results.iter().map(|r| r.max_score).sum(),
+            num_tests: results.len(),
+            bm_correct: results.iter().filter(|r| r.bm_correct).count() as u64,
+        }
+    }
+
+    /// Score as a percentage of the maximum score
+    pub fn percentage(&self) -> f64 {
+        if self.max_score == 0 {
+            return 0.0;
+        }
+        self.score as f64 * 100.0 / self.max_score as f64
+    }
+
+    /// Estimated rating from the usual linear STS fit, `44.523 * percentage - 242.85`.
+    /// It was calibrated on the full 1500 position suite, so it is only a rough
+    /// estimate for subsets or very different time controls
+    pub fn estimated_elo(&self) -> i32 {
+        (STS_ELO_SLOPE * self.percentage() + STS_ELO_INTERCEPT).round() as i32
+    }
+}
+
+const STS_ELO_SLOPE: f64 = 44.523;
+const STS_ELO_INTERCEPT: f64 = -242.85;
+
 #[derive(Default, Debug, Clone)]
 pub struct TestResult {
     pub theme: String,
@@ -57,7 +100,7 @@ pub struct TestResult {
 ///
 /// # Arguments
 /// * `tests` - A slice of `EpdTest`s to run.
-/// * `evaluator` - A boxed `Evaluator` trait object representing the engine's configuration.
+/// * `params` - The evaluation parameters the engine searches with.
 /// * `time_ms_per_move` - The time limit in milliseconds for each search.
 pub fn run_suite(
     tests: &[EpdTest],
@@ -85,9 +128,8 @@ pub fn run_suite(
         let result = search.find_best_move(&board);
         let engine_move_uci = result.best_move.map(|m| m.uci()).unwrap_or_default();
 
-        let score = *test.move_scores.get(&engine_move_uci).unwrap_or(&0);
-        let max_score_for_pos = *test.move_scores.values().max().unwrap_or(&0);
-        let engine_move_uci = result.best_move.map(|m| m.uci()).unwrap_or_default();
+        let score = test.score_move(&engine_move_uci);
+        let max_score_for_pos = test.max_score();
         let bm_correct = test
             .best_move
             .as_ref()
@@ -298,7 +340,7 @@ pub fn parse_epd_line(line: &str) -> miette::Result<EpdTest> {
 
 #[cfg(test)]
 mod epd_tests {
-    use crate::utils::sts_runner::parse_epd_line;
+    use crate::utils::sts_runner::{SuiteSummary, TestResult, parse_epd_line};
 
     #[test]
     fn test_epd_parse() {
@@ -311,4 +353,29 @@ mod epd_tests {
         assert_eq!(result.theme, "Undermine".to_owned());
         assert_eq!(result.best_move, Some("f4f5".to_owned()));
     }
+
+    #[test]
+    fn test_score_from_c0_weights() {
+        let epd_line = r#"2r2rk1/1b1qb1pp/p2p4/1p1PpP2/4Q3/1P2BN1P/P4PP1/R3R1K1 w - - bm g4; id "Adv. f/g/h pawns.015"; c0 "g4=10, a4=2";"#;
+        let test = parse_epd_line(epd_line).unwrap();
+
+        assert_eq!(test.score_move("g2g4"), 10);
+        assert_eq!(test.score_move("a2a4"), 2);
+        assert_eq!(test.score_move("h3h4"), 0);
+        assert_eq!(test.max_score(), 10);
+
+        let results = [("a2a4", 2), ("g2g4", 10), ("h3h4", 0)].map(|(uci, score)| TestResult {
+            score,
+            max_score: test.max_score(),
+            bm_correct: test.best_move.as_deref() == Some(uci),
+            ..Default::default()
+        });
+        let summary = SuiteSummary::from_results("fixture", &results);
+        assert_eq!(summary.score, 12);
+        assert_eq!(summary.max_score, 30);
+        assert_eq!(summary.bm_correct, 1);
+        assert_eq!(summary.percentage(), 40.0);
+        // 44.523 * 40 - 242.85
+        assert_eq!(summary.estimated_elo(), 1538);
+    }
 }
@@ -0,0 +1,139 @@
+//! Bindings for running the engine in a browser
+//!
+//! `WasmGame` wraps a board, the moves played on it and a search behind a small,
+//! string based API that `wasm-bindgen` can export as is. Moves go in and out as UCI
+//! strings, positions as FEN.
+//!
+//! The search here is single threaded and always time limited, so a call to `best_move`
+//! can't hang the page. With the `wasm` feature the search measures time with `web-time`,
+//! which uses `performance.now()` on `wasm32-unknown-unknown` where `std::time::Instant`
+//! panics.
+
+use wasm_bindgen::prelude::*;
+
+use crate::prelude::*;
+use crate::search::common::{SearchConfig, SearchLimits};
+
+/// Time a search may take when the caller doesn't pass one
+pub const DEFAULT_MOVE_TIME_MS: u32 = 1000;
+/// Transposition table size, kept small since browsers are stingy with memory
+const WASM_HASH_MB: usize = 8;
+
+#[wasm_bindgen]
+pub struct WasmGame {
+    board: Board,
+    /// Moves played since `new_game`, oldest first, used for repetition detection
+    history: Vec<MoveInfo>,
+    search: AlphaBetaSearch,
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    /// Starts a game from the initial position
+    #[wasm_bindgen(constructor)]
+    pub fn new_game() -> WasmGame {
+        let config = SearchConfig {
+            emit_info: false,
+            collect_stats: false,
+            hash_size_mb: WASM_HASH_MB,
+            ..Default::default()
+        };
+        let search = AlphaBetaSearch::new()
+            .with_config(config)
+            .expect("Small transposition table should always allocate");
+        WasmGame {
+            board: Board::new(),
+            history: Vec::new(),
+            search,
+        }
+    }
+
+    /// Plays `uci`, eg. "e2e4" or "e7e8q". The position is left untouched on error
+    #[wasm_bindgen(js_name = makeUciMove)]
+    pub fn make_uci_move(&mut self, uci: &str) -> Result<(), String> {
+        let mv = Move::from_uci(&self.board, uci).map_err(|e| e.to_string())?;
+        let info = self.board.try_move(mv).map_err(|e| e.to_string())?;
+        self.history.push(info);
+        Ok(())
+    }
+
+    /// Searches the current position and returns the best move in UCI notation,
+    /// or `None` if the game is over. The search stops at `depth` if given, and after
+    /// `time_ms` (`DEFAULT_MOVE_TIME_MS` if not given) at the latest
+    #[wasm_bindgen(js_name = bestMove)]
+    pub fn best_move(&mut self, depth: Option<u16>, time_ms: Option<u32>) -> Option<String> {
+        let time_ms = time_ms.unwrap_or(DEFAULT_MOVE_TIME_MS);
+        self.search.set_limits(SearchLimits {
+            max_depth: depth,
+            ..SearchLimits::time(time_ms as u64)
+        });
+
+        // Same as `position ... moves ...` over UCI, so the search sees repetitions
+        self.search
+            .set_game_history(self.history.iter().map(|info| info.zobrist_hash));
+        let result = self.search.find_best_move(&self.board);
+        self.search.set_game_history([]);
+
+        result.best_move.map(|mv| mv.uci())
+    }
+
+    /// FEN of the current position
+    pub fn fen(&self) -> String {
+        self.board
+            .to_fen()
+            .expect("Board reachable from the start position should have a valid FEN")
+    }
+
+    /// Legal moves in the current position in UCI notation
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        let mut moves = MoveBuffer::new();
+        self.board.generate_legal_moves(&mut moves, false);
+        moves.iter().map(|mv| mv.uci()).collect()
+    }
+}
+
+impl Default for WasmGame {
+    fn default() -> Self {
+        Self::new_game()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_game_round_trip() {
+        let mut game = WasmGame::new_game();
+        assert_eq!(game.fen(), START_FEN);
+        assert_eq!(game.legal_moves().len(), 20);
+
+        game.make_uci_move("e2e4").unwrap();
+        assert!(game.make_uci_move("e2e4").is_err());
+        assert!(game.make_uci_move("nonsense").is_err());
+        assert_eq!(
+            game.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        let mv = game.best_move(Some(3), None).unwrap();
+        assert!(game.legal_moves().contains(&mv), "{mv}");
+        game.make_uci_move(&mv).unwrap();
+    }
+
+    #[test]
+    fn test_wasm_game_best_move_respects_time() {
+        let mut game = WasmGame::new_game();
+        let start = std::time::Instant::now();
+        assert!(game.best_move(None, Some(50)).is_some());
+        assert!(start.elapsed().as_millis() < 1000);
+
+        // Fool's mate, nothing left to play
+        for mv in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            game.make_uci_move(mv).unwrap();
+        }
+        assert!(game.legal_moves().is_empty());
+        assert_eq!(game.best_move(Some(2), None), None);
+    }
+}